@@ -3,42 +3,206 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 //! Audio extraction and conversion via ffmpeg.
+//!
+//! Whisper decodes any format ffmpeg reads, so media is transcribed as
+//! downloaded. Conversion only happens when kept audio is exported to a
+//! chosen codec with `--keep-format`.
 
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use serde::Deserialize;
+
+use crate::deps::{ffmpeg_path, ffprobe_path};
+use crate::download::VideoInfo;
+use crate::download::youtube::format_upload_date;
 use crate::error::{Error, Result};
+use crate::formats::Chapter;
+
+/// Raw `ffprobe -show_format -show_chapters` JSON output.
+#[derive(Debug, Default, Deserialize)]
+struct ProbeOutput {
+    #[serde(default)]
+    format: ProbeFormat,
+    #[serde(default)]
+    chapters: Vec<ProbeChapter>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ProbeFormat {
+    duration: Option<String>,
+    #[serde(default)]
+    tags: HashMap<String, String>,
+}
 
-/// Check if ffmpeg is available in PATH.
+#[derive(Debug, Deserialize)]
+struct ProbeChapter {
+    start_time: String,
+    end_time: String,
+    #[serde(default)]
+    tags: HashMap<String, String>,
+}
+
+/// Check if ffmpeg is available, bundled or on PATH.
 pub fn check_ffmpeg() -> Result<()> {
-    Command::new("ffmpeg")
+    Command::new(ffmpeg_path())
         .arg("-version")
         .output()
         .map_err(|_| Error::FfmpegNotFound)?;
     Ok(())
 }
 
-/// Build ffmpeg arguments for MP3 conversion.
-pub fn ffmpeg_convert_args(input: &Path, output: &Path) -> Vec<String> {
-    vec![
+/// Codecs that kept audio can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioCodec {
+    Mp3,
+    Flac,
+    Wav,
+    Opus,
+    M4a,
+}
+
+impl AudioCodec {
+    /// File extension of the exported file.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Mp3 => "mp3",
+            Self::Flac => "flac",
+            Self::Wav => "wav",
+            Self::Opus => "opus",
+            Self::M4a => "m4a",
+        }
+    }
+
+    /// The ffmpeg encoder.
+    fn encoder(&self) -> &'static str {
+        match self {
+            Self::Mp3 => "mp3",
+            Self::Flac => "flac",
+            Self::Wav => "pcm_s16le",
+            Self::Opus => "libopus",
+            Self::M4a => "aac",
+        }
+    }
+
+    /// Whether the codec is lossy and takes a bitrate.
+    pub fn is_lossy(&self) -> bool {
+        matches!(self, Self::Mp3 | Self::Opus | Self::M4a)
+    }
+}
+
+/// How `--keep` saves the audio.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum KeepFormat {
+    /// The downloaded stream, or the audio stream of a local file, untouched.
+    #[default]
+    Original,
+    /// Re-encoded with a codec, optionally at a bitrate such as `192k`.
+    Encode {
+        codec: AudioCodec,
+        bitrate: Option<String>,
+    },
+}
+
+impl std::str::FromStr for KeepFormat {
+    type Err = Error;
+
+    /// Parse `original`, or a codec with an optional bitrate such as
+    /// `mp3:192k`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use voxtus::audio::{AudioCodec, KeepFormat};
+    ///
+    /// assert_eq!("original".parse::<KeepFormat>().unwrap(), KeepFormat::Original);
+    /// assert_eq!(
+    ///     "mp3:192k".parse::<KeepFormat>().unwrap(),
+    ///     KeepFormat::Encode { codec: AudioCodec::Mp3, bitrate: Some("192k".to_string()) }
+    /// );
+    /// assert!("flac:192k".parse::<KeepFormat>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::InvalidAudioFormat(s.to_string());
+        let (name, bitrate) = match s.split_once(':') {
+            Some((name, bitrate)) => (name, Some(bitrate)),
+            None => (s, None),
+        };
+
+        let codec = match name.to_lowercase().as_str() {
+            "original" if bitrate.is_none() => return Ok(Self::Original),
+            "mp3" => AudioCodec::Mp3,
+            "flac" => AudioCodec::Flac,
+            "wav" => AudioCodec::Wav,
+            "opus" => AudioCodec::Opus,
+            "m4a" | "aac" => AudioCodec::M4a,
+            _ => return Err(invalid()),
+        };
+
+        let bitrate = match bitrate {
+            Some(b) if codec.is_lossy() && is_bitrate(b) => Some(b.to_lowercase()),
+            Some(_) => return Err(invalid()),
+            None => None,
+        };
+
+        Ok(Self::Encode { codec, bitrate })
+    }
+}
+
+impl KeepFormat {
+    /// File extension of the kept audio, or `None` for the original, whose
+    /// extension depends on the source.
+    pub fn extension(&self) -> Option<&'static str> {
+        match self {
+            Self::Original => None,
+            Self::Encode { codec, .. } => Some(codec.extension()),
+        }
+    }
+}
+
+/// A bitrate such as `128k`, `1M` or `96000`.
+fn is_bitrate(s: &str) -> bool {
+    let digits = s.strip_suffix(['k', 'K', 'm', 'M']).unwrap_or(s);
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Build ffmpeg arguments for exporting audio to a codec.
+pub fn ffmpeg_export_args(
+    input: &Path,
+    output: &Path,
+    codec: AudioCodec,
+    bitrate: Option<&str>,
+) -> Vec<String> {
+    let mut args = vec![
         "-i".to_string(),
         input.to_string_lossy().to_string(),
         "-vn".to_string(), // No video
         "-acodec".to_string(),
-        "mp3".to_string(),
-        "-q:a".to_string(),
-        "2".to_string(),  // High quality
+        codec.encoder().to_string(),
+    ];
+    match (codec, bitrate) {
+        (_, Some(bitrate)) => args.extend(["-b:a".to_string(), bitrate.to_string()]),
+        // High quality VBR
+        (AudioCodec::Mp3, None) => args.extend(["-q:a".to_string(), "2".to_string()]),
+        _ => {}
+    }
+    args.extend([
         "-y".to_string(), // Overwrite output
         output.to_string_lossy().to_string(),
-    ]
+    ]);
+    args
 }
 
-/// Convert a media file to MP3 using ffmpeg.
-pub fn convert_to_mp3(input: &Path, output: &Path) -> Result<()> {
-    let args = ffmpeg_convert_args(input, output);
+/// Build ffmpeg arguments for MP3 conversion.
+pub fn ffmpeg_convert_args(input: &Path, output: &Path) -> Vec<String> {
+    ffmpeg_export_args(input, output, AudioCodec::Mp3, None)
+}
 
-    let result = Command::new("ffmpeg")
-        .args(&args)
+/// Run ffmpeg with the given arguments.
+fn run_ffmpeg(args: &[String]) -> Result<()> {
+    let result = Command::new(ffmpeg_path())
+        .args(args)
         .output()
         .map_err(|e| Error::FfmpegError(e.to_string()))?;
 
@@ -54,6 +218,316 @@ pub fn convert_to_mp3(input: &Path, output: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Convert a media file to MP3 using ffmpeg.
+pub fn convert_to_mp3(input: &Path, output: &Path) -> Result<()> {
+    run_ffmpeg(&ffmpeg_convert_args(input, output))
+}
+
+/// Export the audio of a media file to a codec.
+pub fn export_audio(
+    input: &Path,
+    output: &Path,
+    codec: AudioCodec,
+    bitrate: Option<&str>,
+) -> Result<()> {
+    run_ffmpeg(&ffmpeg_export_args(input, output, codec, bitrate))
+}
+
+/// Named preprocessing filters and the ffmpeg filters they expand to.
+///
+/// Presets marked with a default value take an optional `:VALUE`, such as
+/// `highpass:120`.
+pub const FILTER_PRESETS: &[(&str, &str)] = &[
+    ("loudnorm", "loudnorm=I=-16:TP=-1.5:LRA=11"),
+    ("denoise", "afftdn=nf=-25"),
+    ("highpass", "highpass=f={80}"),
+    ("lowpass", "lowpass=f={8000}"),
+    ("speed", "atempo={1.0}"),
+    (
+        "speech",
+        "highpass=f=80,lowpass=f=8000,afftdn=nf=-25,loudnorm=I=-16:TP=-1.5:LRA=11",
+    ),
+];
+
+/// Audio preprocessing applied before transcription.
+///
+/// Built from preset names (see [`FILTER_PRESETS`]) and raw ffmpeg filter
+/// strings, applied in the given order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AudioFilters {
+    /// The ffmpeg filters, one per preset or raw filter.
+    pub filters: Vec<String>,
+    /// Combined playback speed of `atempo` filters; timestamps of the
+    /// transcript are multiplied by it to match the original audio.
+    pub tempo: f64,
+}
+
+impl AudioFilters {
+    /// Parse filter specs: a preset name, a preset with a value such as
+    /// `speed:1.5`, or a raw ffmpeg filter such as `volume=2`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use voxtus::audio::AudioFilters;
+    ///
+    /// let filters = AudioFilters::parse(&["highpass:120", "speed:1.5", "volume=2"]).unwrap();
+    /// assert_eq!(filters.chain(), "highpass=f=120,atempo=1.5,volume=2");
+    /// assert_eq!(filters.tempo, 1.5);
+    /// assert!(AudioFilters::parse(&["reverb"]).is_err());
+    /// ```
+    pub fn parse<S: AsRef<str>>(specs: &[S]) -> Result<Self> {
+        let mut filters = Vec::new();
+        for spec in specs {
+            let spec = spec.as_ref().trim();
+            filters.push(if spec.contains('=') {
+                spec.to_string()
+            } else {
+                expand_preset(spec)?
+            });
+        }
+
+        let mut tempo = 1.0;
+        for filter in filters.iter().flat_map(|f| f.split(',')) {
+            if let Some(value) = filter.strip_prefix("atempo=") {
+                let factor: f64 = value.parse().ok().filter(|f| *f > 0.0).ok_or_else(|| {
+                    Error::InvalidAudioFilter(format!("{} (invalid speed)", filter))
+                })?;
+                tempo *= factor;
+            }
+        }
+
+        Ok(Self { filters, tempo })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    /// The ffmpeg `-af` filter chain.
+    pub fn chain(&self) -> String {
+        self.filters.join(",")
+    }
+}
+
+/// Expand a preset name, with an optional `:VALUE`, into an ffmpeg filter.
+fn expand_preset(spec: &str) -> Result<String> {
+    let invalid = || Error::InvalidAudioFilter(spec.to_string());
+    let (name, value) = match spec.split_once(':') {
+        Some((name, value)) => (name, Some(value)),
+        None => (spec, None),
+    };
+    let (_, filter) = FILTER_PRESETS
+        .iter()
+        .find(|(preset, _)| preset.eq_ignore_ascii_case(name))
+        .ok_or_else(invalid)?;
+
+    // `{default}` marks where a value goes
+    let Some((before, rest)) = filter.split_once('{') else {
+        return match value {
+            Some(_) => Err(invalid()),
+            None => Ok(filter.to_string()),
+        };
+    };
+    let (default, after) = rest.split_once('}').ok_or_else(invalid)?;
+    let value = match value {
+        Some(v) if !v.is_empty() && v.parse::<f64>().is_ok_and(|v| v > 0.0) => v,
+        Some(_) => return Err(invalid()),
+        None => default,
+    };
+    Ok(format!("{}{}{}", before, value, after))
+}
+
+/// Build ffmpeg arguments for preprocessing audio into 16 kHz mono WAV.
+pub fn ffmpeg_preprocess_args(input: &Path, output: &Path, filters: &AudioFilters) -> Vec<String> {
+    vec![
+        "-i".to_string(),
+        input.to_string_lossy().to_string(),
+        "-vn".to_string(),
+        "-af".to_string(),
+        filters.chain(),
+        "-ac".to_string(),
+        "1".to_string(),
+        "-ar".to_string(),
+        "16000".to_string(),
+        "-acodec".to_string(),
+        "pcm_s16le".to_string(),
+        "-y".to_string(),
+        output.to_string_lossy().to_string(),
+    ]
+}
+
+/// Apply preprocessing filters to a media file, writing 16 kHz mono WAV.
+pub fn preprocess_audio(input: &Path, output: &Path, filters: &AudioFilters) -> Result<()> {
+    run_ffmpeg(&ffmpeg_preprocess_args(input, output, filters))
+}
+
+/// Extensions of audio-only files, which are kept as they are.
+const AUDIO_EXTENSIONS: &[&str] = &[
+    "mp3", "m4a", "aac", "opus", "ogg", "oga", "flac", "wav", "wma", "alac",
+];
+
+/// Whether a path has the extension of an audio-only format.
+pub fn is_audio_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| AUDIO_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+}
+
+/// File extension for an audio stream of the given ffprobe codec name.
+///
+/// Falls back to Matroska audio, which holds any codec.
+///
+/// # Examples
+///
+/// ```
+/// use voxtus::audio::extension_for_codec;
+///
+/// assert_eq!(extension_for_codec("aac"), "m4a");
+/// assert_eq!(extension_for_codec("pcm_s24le"), "wav");
+/// assert_eq!(extension_for_codec("eac3"), "mka");
+/// ```
+pub fn extension_for_codec(codec: &str) -> &'static str {
+    match codec {
+        "aac" | "alac" => "m4a",
+        "mp3" => "mp3",
+        "opus" => "opus",
+        "vorbis" => "ogg",
+        "flac" => "flac",
+        c if c.starts_with("pcm_") => "wav",
+        _ => "mka",
+    }
+}
+
+/// Copy the first audio stream of a media file without re-encoding.
+///
+/// Returns the path written in `dir`, named `<stem>.<ext>` with the
+/// extension chosen for the stream's codec.
+pub fn extract_audio_stream(input: &Path, dir: &Path, stem: &str) -> Result<PathBuf> {
+    let result = Command::new(ffprobe_path())
+        .args([
+            "-v",
+            "quiet",
+            "-select_streams",
+            "a:0",
+            "-show_entries",
+            "stream=codec_name",
+            "-of",
+            "csv=p=0",
+        ])
+        .arg(input)
+        .output()
+        .map_err(|e| Error::FfmpegError(format!("Failed to run ffprobe: {}", e)))?;
+    let codec = String::from_utf8_lossy(&result.stdout).trim().to_string();
+    if !result.status.success() || codec.is_empty() {
+        return Err(Error::FfmpegError(format!(
+            "No audio stream found in {}",
+            input.display()
+        )));
+    }
+
+    let output = dir.join(format!("{}.{}", stem, extension_for_codec(&codec)));
+    run_ffmpeg(&[
+        "-i".to_string(),
+        input.to_string_lossy().to_string(),
+        "-vn".to_string(),
+        "-acodec".to_string(),
+        "copy".to_string(),
+        "-y".to_string(),
+        output.to_string_lossy().to_string(),
+    ])?;
+
+    Ok(output)
+}
+
+/// Parse ffprobe JSON output into a [`VideoInfo`] for a local file.
+///
+/// The title is always the given one (the file stem), since it names the
+/// output files; tags only fill the optional fields.
+pub fn parse_probe_json(json: &str, title: &str) -> Result<VideoInfo> {
+    let probe: ProbeOutput = serde_json::from_str(json)?;
+
+    // Tag keys are upper case in Matroska and lower case in MP4/MP3
+    let tags: HashMap<String, String> = probe
+        .format
+        .tags
+        .into_iter()
+        .map(|(k, v)| (k.to_lowercase(), v.trim().to_string()))
+        .filter(|(_, v)| !v.is_empty())
+        .collect();
+    let tag = |keys: &[&str]| keys.iter().find_map(|k| tags.get(*k).cloned());
+
+    let upload_date = tag(&["date", "creation_time", "year"]).map(|d| {
+        // Trim timestamps such as 2024-01-31T10:00:00.000000Z down to the date
+        let date = d.split('T').next().unwrap_or(&d);
+        format_upload_date(date)
+    });
+
+    let tag_list = tag(&["keywords", "genre"])
+        .map(|t| {
+            t.split([',', ';'])
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let chapters = probe
+        .chapters
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, c)| {
+            let start = c.start_time.parse().ok()?;
+            let end = c.end_time.parse().ok()?;
+            let title = c
+                .tags
+                .into_iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case("title"))
+                .map(|(_, v)| v)
+                .unwrap_or_else(|| format!("Chapter {}", i + 1));
+            Some(Chapter::new(start, end, title))
+        })
+        .collect();
+
+    Ok(VideoInfo {
+        id: None,
+        title: title.to_string(),
+        uploader: tag(&["artist", "author", "composer"]),
+        channel: tag(&["album_artist"]),
+        upload_date,
+        description: tag(&["description", "comment", "synopsis"]),
+        duration: probe.format.duration.and_then(|d| d.parse().ok()),
+        tags: tag_list,
+        chapters,
+        ..Default::default()
+    })
+}
+
+/// Read tags, duration and chapters of a local media file with ffprobe.
+pub fn probe_media_info(path: &Path, title: &str) -> Result<VideoInfo> {
+    let result = Command::new(ffprobe_path())
+        .args([
+            "-v",
+            "quiet",
+            "-print_format",
+            "json",
+            "-show_format",
+            "-show_chapters",
+        ])
+        .arg(path)
+        .output()
+        .map_err(|e| Error::FfmpegError(format!("Failed to run ffprobe: {}", e)))?;
+
+    if !result.status.success() {
+        return Err(Error::FfmpegError(format!(
+            "ffprobe exited with status {}",
+            result.status
+        )));
+    }
+
+    parse_probe_json(&String::from_utf8_lossy(&result.stdout), title)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -74,4 +548,161 @@ mod tests {
         assert!(args.contains(&"-y".to_string()));
         assert_eq!(args.last().unwrap(), "/tmp/output.mp3");
     }
+
+    #[test]
+    fn test_ffmpeg_export_args() {
+        let input = PathBuf::from("in.webm");
+        let output = PathBuf::from("out.opus");
+
+        let args = ffmpeg_export_args(&input, &output, AudioCodec::Opus, Some("96k"));
+        assert_eq!(
+            args,
+            vec![
+                "-i", "in.webm", "-vn", "-acodec", "libopus", "-b:a", "96k", "-y", "out.opus"
+            ]
+        );
+
+        let args = ffmpeg_export_args(&input, &output, AudioCodec::Flac, None);
+        assert!(!args.contains(&"-b:a".to_string()));
+        assert!(!args.contains(&"-q:a".to_string()));
+    }
+
+    #[test]
+    fn test_keep_format_from_str() {
+        assert_eq!(
+            "FLAC".parse::<KeepFormat>().unwrap(),
+            KeepFormat::Encode {
+                codec: AudioCodec::Flac,
+                bitrate: None
+            }
+        );
+        assert_eq!(
+            "opus:96K".parse::<KeepFormat>().unwrap(),
+            KeepFormat::Encode {
+                codec: AudioCodec::Opus,
+                bitrate: Some("96k".to_string())
+            }
+        );
+        assert_eq!(
+            "wav".parse::<KeepFormat>().unwrap().extension(),
+            Some("wav")
+        );
+        assert_eq!(KeepFormat::Original.extension(), None);
+
+        for invalid in ["ogg", "mp3:", "mp3:fast", "wav:320k", "original:128k"] {
+            assert!(
+                matches!(
+                    invalid.parse::<KeepFormat>(),
+                    Err(Error::InvalidAudioFormat(_))
+                ),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_audio_filter_presets() {
+        let filters = AudioFilters::parse(&["loudnorm", "Denoise", "lowpass"]).unwrap();
+        assert_eq!(
+            filters.filters,
+            vec![
+                "loudnorm=I=-16:TP=-1.5:LRA=11",
+                "afftdn=nf=-25",
+                "lowpass=f=8000"
+            ]
+        );
+        assert_eq!(filters.tempo, 1.0);
+
+        assert!(AudioFilters::parse::<&str>(&[]).unwrap().is_empty());
+        for invalid in ["loudnorm:3", "highpass:", "highpass:low", "speed:0", "nope"] {
+            assert!(
+                matches!(
+                    AudioFilters::parse(&[invalid]),
+                    Err(Error::InvalidAudioFilter(_))
+                ),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_audio_filter_tempo_from_raw_filters() {
+        let filters = AudioFilters::parse(&["atempo=2,atempo=1.5", "speed:0.5"]).unwrap();
+        assert_eq!(filters.tempo, 1.5);
+        assert!(AudioFilters::parse(&["atempo=fast"]).is_err());
+    }
+
+    #[test]
+    fn test_ffmpeg_preprocess_args() {
+        let filters = AudioFilters::parse(&["speech"]).unwrap();
+        let args = ffmpeg_preprocess_args(Path::new("in.m4a"), Path::new("out.wav"), &filters);
+
+        let af = args.iter().position(|a| a == "-af").unwrap();
+        assert_eq!(args[af + 1], filters.chain());
+        assert!(args.windows(2).any(|w| w == ["-ar", "16000"]));
+        assert_eq!(args.last().unwrap(), "out.wav");
+    }
+
+    #[test]
+    fn test_is_audio_file() {
+        assert!(is_audio_file(Path::new("talk.M4A")));
+        assert!(is_audio_file(Path::new("/tmp/a.opus")));
+        assert!(!is_audio_file(Path::new("talk.mp4")));
+        assert!(!is_audio_file(Path::new("talk")));
+    }
+
+    #[test]
+    fn test_parse_probe_json() {
+        let json = r#"{
+            "chapters": [
+                {"id": 0, "start_time": "0.000000", "end_time": "61.500000", "tags": {"title": "Opening"}},
+                {"id": 1, "start_time": "61.500000", "end_time": "120.000000", "tags": {}}
+            ],
+            "format": {
+                "filename": "talk.mp4",
+                "duration": "120.000000",
+                "tags": {
+                    "title": "Embedded title",
+                    "ARTIST": "Speaker",
+                    "date": "2023-05-04T10:00:00.000000Z",
+                    "comment": "Recorded live",
+                    "genre": "Talk; Tech"
+                }
+            }
+        }"#;
+
+        let info = parse_probe_json(json, "talk").unwrap();
+        assert_eq!(info.title, "talk");
+        assert_eq!(info.uploader.as_deref(), Some("Speaker"));
+        assert_eq!(info.upload_date.as_deref(), Some("2023-05-04"));
+        assert_eq!(info.description.as_deref(), Some("Recorded live"));
+        assert_eq!(info.duration, Some(120.0));
+        assert_eq!(info.tags, vec!["Talk", "Tech"]);
+        assert_eq!(
+            info.chapters,
+            vec![
+                Chapter::new(0.0, 61.5, "Opening"),
+                Chapter::new(61.5, 120.0, "Chapter 2"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_probe_json_without_tags() {
+        let info = parse_probe_json(r#"{"format": {"duration": "3.5"}}"#, "clip").unwrap();
+        assert_eq!(info.title, "clip");
+        assert_eq!(info.duration, Some(3.5));
+        assert!(info.uploader.is_none());
+        assert!(info.upload_date.is_none());
+        assert!(info.chapters.is_empty());
+    }
+
+    #[test]
+    fn test_parse_probe_json_compact_date() {
+        let json = r#"{"format": {"tags": {"DATE": "20230504"}}}"#;
+        let info = parse_probe_json(json, "clip").unwrap();
+        assert_eq!(info.upload_date.as_deref(), Some("2023-05-04"));
+    }
 }
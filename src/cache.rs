@@ -0,0 +1,612 @@
+// Voxtus - Transcribe YouTube videos and local media files to text
+// Copyright (C) 2024 Johan Thorén <johan@thoren.xyz>
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Persistent caches of downloaded media and transcripts.
+//!
+//! Downloaded audio is kept per video (keyed by YouTube video ID, or a hash
+//! of the URL for other sites) so re-running with a different model or
+//! format skips the download. The media cache is size limited, evicting the
+//! least recently used entries first.
+//!
+//! Transcripts are keyed by a hash of the audio content together with the
+//! model and decoding options, so generating another output format for the
+//! same input skips transcription.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::download::VideoInfo;
+use crate::download::youtube::youtube_video_id;
+use crate::error::{Error, Result};
+use crate::formats::Transcript;
+
+/// Default media cache size limit: 2 GiB.
+pub const DEFAULT_MEDIA_CACHE_SIZE: u64 = 2 * 1024 * 1024 * 1024;
+
+const AUDIO_STEM: &str = "audio";
+const INFO_FILE: &str = "info.json";
+
+/// Root directory of all voxtus caches.
+pub fn cache_dir() -> Result<PathBuf> {
+    dirs::cache_dir()
+        .map(|dir| dir.join("voxtus"))
+        .ok_or_else(|| Error::CacheError("Could not determine cache directory".into()))
+}
+
+/// 64-bit FNV-1a hash, stable across platforms and Rust versions.
+///
+/// # Examples
+///
+/// ```
+/// use voxtus::cache::fnv1a;
+///
+/// assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+/// assert_ne!(fnv1a(b"a"), fnv1a(b"b"));
+/// ```
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    fnv1a_update(0xcbf29ce484222325, bytes)
+}
+
+fn fnv1a_update(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// FNV-1a hash of a file's content, read in chunks.
+pub fn hash_file(path: &Path) -> Result<u64> {
+    let mut file = std::fs::File::open(path)?;
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut hash = fnv1a(b"");
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            return Ok(hash);
+        }
+        hash = fnv1a_update(hash, &buf[..n]);
+    }
+}
+
+/// Cache key for a URL downloaded with the given settings: the YouTube
+/// video ID, or a hash of the URL, followed by a hash of `settings`.
+///
+/// `settings` describes everything that changes the downloaded stream,
+/// such as the backend and yt-dlp format selector, so downloads made with
+/// different settings never share an entry.
+///
+/// # Examples
+///
+/// ```
+/// use voxtus::cache::media_cache_key;
+///
+/// // Different URLs for the same video share an entry
+/// assert_eq!(
+///     media_cache_key("https://youtu.be/abc123", "yt-dlp|bestaudio"),
+///     media_cache_key("https://www.youtube.com/watch?v=abc123&t=5", "yt-dlp|bestaudio")
+/// );
+/// // Other settings don't
+/// assert_ne!(
+///     media_cache_key("https://youtu.be/abc123", "yt-dlp|bestaudio"),
+///     media_cache_key("https://youtu.be/abc123", "yt-dlp|bestaudio[ext=webm]")
+/// );
+/// assert!(media_cache_key("https://vimeo.com/1", "yt-dlp").starts_with("url-"));
+/// ```
+pub fn media_cache_key(url: &str, settings: &str) -> String {
+    let settings = fnv1a(settings.as_bytes());
+    match youtube_video_id(url) {
+        Some(id) => format!("yt-{}-{:016x}", id, settings),
+        None => format!("url-{:016x}-{:016x}", fnv1a(url.as_bytes()), settings),
+    }
+}
+
+/// A cached download.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaCacheEntry {
+    pub key: String,
+    pub title: String,
+    /// Size in bytes.
+    pub size: u64,
+    pub last_used: SystemTime,
+}
+
+/// Size-limited LRU cache of downloaded audio and its video info.
+///
+/// Each entry is a directory holding the downloaded stream as
+/// `audio.<ext>` and `info.json`; the modification time of `info.json`
+/// records when it was last used.
+#[derive(Debug, Clone)]
+pub struct MediaCache {
+    dir: PathBuf,
+    max_size: u64,
+}
+
+impl MediaCache {
+    pub fn new(dir: impl Into<PathBuf>, max_size: u64) -> Self {
+        Self {
+            dir: dir.into(),
+            max_size,
+        }
+    }
+
+    /// The media cache in the user's cache directory.
+    pub fn open_default(max_size: u64) -> Result<Self> {
+        Ok(Self::new(cache_dir()?.join("media"), max_size))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Look up cached audio, marking the entry as used.
+    pub fn get(&self, key: &str) -> Option<(PathBuf, VideoInfo)> {
+        let entry_dir = self.dir.join(key);
+        let info_path = entry_dir.join(INFO_FILE);
+        let audio = std::fs::read_dir(&entry_dir)
+            .ok()?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .find(|p| p.is_file() && p.file_stem().is_some_and(|s| s == AUDIO_STEM))?;
+
+        let info = std::fs::read_to_string(&info_path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())?;
+        if let Err(e) = touch(&info_path) {
+            log::debug!("Could not update cache entry time: {}", e);
+        }
+
+        Some((audio, info))
+    }
+
+    /// Store downloaded audio, evicting old entries to stay within the limit.
+    ///
+    /// The file keeps its extension. Returns the path of the cached audio.
+    pub fn put(&self, key: &str, audio: &Path, info: &VideoInfo) -> Result<PathBuf> {
+        let entry_dir = self.dir.join(key);
+        if entry_dir.exists() {
+            std::fs::remove_dir_all(&entry_dir)?;
+        }
+        std::fs::create_dir_all(&entry_dir)?;
+
+        let mut cached = entry_dir.join(AUDIO_STEM);
+        if let Some(ext) = audio.extension() {
+            cached.set_extension(ext);
+        }
+        std::fs::copy(audio, &cached)?;
+        std::fs::write(entry_dir.join(INFO_FILE), serde_json::to_string(info)?)?;
+
+        self.evict(Some(key))?;
+        Ok(cached)
+    }
+
+    /// List entries, most recently used first.
+    pub fn entries(&self) -> Result<Vec<MediaCacheEntry>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut entries = Vec::new();
+        for dir_entry in std::fs::read_dir(&self.dir)? {
+            let path = dir_entry?.path();
+            let info_path = path.join(INFO_FILE);
+            let Ok(metadata) = std::fs::metadata(&info_path) else {
+                continue;
+            };
+            let info: VideoInfo = std::fs::read_to_string(&info_path)
+                .ok()
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default();
+
+            entries.push(MediaCacheEntry {
+                key: path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                title: info.title,
+                size: dir_size(&path)?,
+                last_used: metadata.modified()?,
+            });
+        }
+
+        entries.sort_by_key(|e| std::cmp::Reverse(e.last_used));
+        Ok(entries)
+    }
+
+    /// Remove least recently used entries until the cache fits its limit.
+    ///
+    /// The entry `keep` is never removed. Returns the number of bytes freed.
+    pub fn evict(&self, keep: Option<&str>) -> Result<u64> {
+        let entries = self.entries()?;
+        let mut total: u64 = entries.iter().map(|e| e.size).sum();
+        let mut freed = 0;
+
+        for entry in entries.iter().rev() {
+            if total <= self.max_size {
+                break;
+            }
+            if Some(entry.key.as_str()) == keep {
+                continue;
+            }
+            log::debug!("Evicting cached media: {}", entry.key);
+            std::fs::remove_dir_all(self.dir.join(&entry.key))?;
+            total -= entry.size;
+            freed += entry.size;
+        }
+
+        Ok(freed)
+    }
+
+    /// Remove all entries. Returns the number of entries removed.
+    pub fn clear(&self) -> Result<usize> {
+        let count = self.entries()?.len();
+        if self.dir.exists() {
+            std::fs::remove_dir_all(&self.dir)?;
+        }
+        Ok(count)
+    }
+}
+
+/// A cached transcript together with what produced it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedTranscript {
+    pub model: String,
+    pub options: String,
+    pub transcript: Transcript,
+}
+
+/// A cached transcript as listed by [`TranscriptCache::entries`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptCacheEntry {
+    pub key: String,
+    pub title: String,
+    pub model: String,
+    /// Size in bytes.
+    pub size: u64,
+    pub last_used: SystemTime,
+}
+
+/// Cache of Whisper transcripts, one JSON file per entry.
+///
+/// Transcripts are small, so the cache is not size limited.
+#[derive(Debug, Clone)]
+pub struct TranscriptCache {
+    dir: PathBuf,
+}
+
+impl TranscriptCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The transcript cache in the user's cache directory.
+    pub fn open_default() -> Result<Self> {
+        Ok(Self::new(cache_dir()?.join("transcripts")))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Cache key for transcribing `audio` with a model and decoding options.
+    pub fn key(audio: &Path, model: &str, options: &str) -> Result<String> {
+        Ok(Self::key_for_hash(hash_file(audio)?, model, options))
+    }
+
+    /// Cache key for audio with a known [`hash_file`] hash.
+    pub fn key_for_hash(audio_hash: u64, model: &str, options: &str) -> String {
+        let settings = fnv1a(format!("{}\0{}", model, options).as_bytes());
+        format!("{:016x}-{:016x}", audio_hash, settings)
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    /// Look up a transcript, marking the entry as used.
+    ///
+    /// Unreadable entries are treated as missing.
+    pub fn get(&self, key: &str) -> Option<Transcript> {
+        let path = self.entry_path(key);
+        let json = std::fs::read_to_string(&path).ok()?;
+        let cached: CachedTranscript = match serde_json::from_str(&json) {
+            Ok(cached) => cached,
+            Err(e) => {
+                log::debug!("Ignoring unreadable cached transcript {}: {}", key, e);
+                return None;
+            }
+        };
+        if let Err(e) = touch(&path) {
+            log::debug!("Could not update cache entry time: {}", e);
+        }
+        Some(cached.transcript)
+    }
+
+    /// Store a transcript.
+    pub fn put(
+        &self,
+        key: &str,
+        model: &str,
+        options: &str,
+        transcript: &Transcript,
+    ) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let cached = CachedTranscript {
+            model: model.to_string(),
+            options: options.to_string(),
+            transcript: transcript.clone(),
+        };
+        std::fs::write(self.entry_path(key), serde_json::to_string(&cached)?)?;
+        Ok(())
+    }
+
+    /// List entries, most recently used first.
+    pub fn entries(&self) -> Result<Vec<TranscriptCacheEntry>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut entries = Vec::new();
+        for dir_entry in std::fs::read_dir(&self.dir)? {
+            let path = dir_entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let metadata = std::fs::metadata(&path)?;
+            let cached: Option<CachedTranscript> = std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|json| serde_json::from_str(&json).ok());
+
+            entries.push(TranscriptCacheEntry {
+                key: path
+                    .file_stem()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                title: cached
+                    .as_ref()
+                    .map(|c| c.transcript.metadata.title.clone())
+                    .unwrap_or_default(),
+                model: cached.map(|c| c.model).unwrap_or_default(),
+                size: metadata.len(),
+                last_used: metadata.modified()?,
+            });
+        }
+
+        entries.sort_by_key(|e| std::cmp::Reverse(e.last_used));
+        Ok(entries)
+    }
+
+    /// Remove all entries. Returns the number of entries removed.
+    pub fn clear(&self) -> Result<usize> {
+        let count = self.entries()?.len();
+        if self.dir.exists() {
+            std::fs::remove_dir_all(&self.dir)?;
+        }
+        Ok(count)
+    }
+}
+
+/// Set a file's modification time to now.
+fn touch(path: &Path) -> std::io::Result<()> {
+    std::fs::File::options()
+        .append(true)
+        .open(path)?
+        .set_modified(SystemTime::now())
+}
+
+/// Total size of the files in a directory.
+fn dir_size(dir: &Path) -> Result<u64> {
+    let mut size = 0;
+    for entry in std::fs::read_dir(dir)? {
+        let metadata = entry?.metadata()?;
+        if metadata.is_file() {
+            size += metadata.len();
+        }
+    }
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn info(title: &str) -> VideoInfo {
+        VideoInfo {
+            id: Some(title.to_lowercase()),
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn audio_file(dir: &Path, name: &str, size: usize) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, vec![0u8; size]).unwrap();
+        path
+    }
+
+    /// Backdate an entry so LRU order doesn't depend on timer resolution.
+    fn set_last_used(cache: &MediaCache, key: &str, secs_ago: u64) {
+        std::fs::File::options()
+            .append(true)
+            .open(cache.dir().join(key).join(INFO_FILE))
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(secs_ago))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_put_and_get() {
+        let src = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let cache = MediaCache::new(dir.path(), DEFAULT_MEDIA_CACHE_SIZE);
+
+        assert!(cache.get("yt-abc").is_none());
+
+        let audio = audio_file(src.path(), "a.mp3", 10);
+        let cached = cache.put("yt-abc", &audio, &info("Talk")).unwrap();
+        assert!(cached.starts_with(dir.path()));
+
+        let (path, cached_info) = cache.get("yt-abc").unwrap();
+        assert_eq!(path, cached);
+        assert_eq!(cached_info, info("Talk"));
+    }
+
+    #[test]
+    fn test_put_keeps_extension() {
+        let src = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let cache = MediaCache::new(dir.path(), DEFAULT_MEDIA_CACHE_SIZE);
+
+        let audio = audio_file(src.path(), "stream.webm", 10);
+        let cached = cache.put("yt-abc", &audio, &info("Talk")).unwrap();
+        assert_eq!(cached.file_name().unwrap(), "audio.webm");
+        assert_eq!(cache.get("yt-abc").unwrap().0, cached);
+
+        // Replacing an entry doesn't leave the old stream behind
+        let audio = audio_file(src.path(), "stream.m4a", 10);
+        cache.put("yt-abc", &audio, &info("Talk")).unwrap();
+        assert_eq!(
+            cache.get("yt-abc").unwrap().0.file_name().unwrap(),
+            "audio.m4a"
+        );
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let src = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let info_size = serde_json::to_string(&info("A")).unwrap().len() as u64;
+        let cache = MediaCache::new(dir.path(), 2 * (100 + info_size));
+
+        cache
+            .put("a", &audio_file(src.path(), "a.mp3", 100), &info("A"))
+            .unwrap();
+        cache
+            .put("b", &audio_file(src.path(), "b.mp3", 100), &info("B"))
+            .unwrap();
+        set_last_used(&cache, "a", 20);
+        set_last_used(&cache, "b", 30);
+
+        // Using "b" makes "a" the least recently used
+        cache.get("b").unwrap();
+        cache
+            .put("c", &audio_file(src.path(), "c.mp3", 100), &info("C"))
+            .unwrap();
+
+        let keys: Vec<String> = cache
+            .entries()
+            .unwrap()
+            .into_iter()
+            .map(|e| e.key)
+            .collect();
+        assert_eq!(keys.len(), 2);
+        assert!(keys.contains(&"b".to_string()));
+        assert!(keys.contains(&"c".to_string()));
+    }
+
+    #[test]
+    fn test_never_evicts_new_entry() {
+        let src = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let cache = MediaCache::new(dir.path(), 10);
+
+        cache
+            .put("big", &audio_file(src.path(), "big.mp3", 100), &info("Big"))
+            .unwrap();
+        assert!(cache.get("big").is_some());
+    }
+
+    #[test]
+    fn test_clear() {
+        let src = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let cache = MediaCache::new(dir.path().join("media"), DEFAULT_MEDIA_CACHE_SIZE);
+        assert_eq!(cache.clear().unwrap(), 0);
+
+        cache
+            .put("a", &audio_file(src.path(), "a.mp3", 1), &info("A"))
+            .unwrap();
+        assert_eq!(cache.clear().unwrap(), 1);
+        assert!(cache.entries().unwrap().is_empty());
+    }
+
+    fn transcript(title: &str) -> Transcript {
+        use crate::formats::{Metadata, Segment};
+
+        Transcript::new(
+            vec![Segment::new(0.0, 1.0, "Hello")],
+            Metadata::new(title, "talk.mp3", Some(1.0), "base", None),
+        )
+    }
+
+    #[test]
+    fn test_hash_file_matches_fnv1a() {
+        let dir = tempfile::tempdir().unwrap();
+        let content: Vec<u8> = (0..200_000u32).map(|i| i as u8).collect();
+        let path = dir.path().join("a.mp3");
+        std::fs::write(&path, &content).unwrap();
+
+        assert_eq!(hash_file(&path).unwrap(), fnv1a(&content));
+    }
+
+    #[test]
+    fn test_transcript_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = audio_file(dir.path(), "a.mp3", 10);
+        let a_copy = audio_file(dir.path(), "copy.mp3", 10);
+        let b = dir.path().join("b.mp3");
+        std::fs::write(&b, b"different").unwrap();
+
+        let key = TranscriptCache::key(&a, "base", "greedy").unwrap();
+        // Keyed by content, not by path
+        assert_eq!(
+            key,
+            TranscriptCache::key(&a_copy, "base", "greedy").unwrap()
+        );
+        assert_ne!(key, TranscriptCache::key(&b, "base", "greedy").unwrap());
+        assert_ne!(key, TranscriptCache::key(&a, "small", "greedy").unwrap());
+        assert_ne!(key, TranscriptCache::key(&a, "base", "beam").unwrap());
+        assert_eq!(
+            key,
+            TranscriptCache::key_for_hash(hash_file(&a).unwrap(), "base", "greedy")
+        );
+    }
+
+    #[test]
+    fn test_transcript_put_and_get() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = TranscriptCache::new(dir.path().join("transcripts"));
+
+        assert!(cache.get("k").is_none());
+        cache
+            .put("k", "base", "greedy", &transcript("Talk"))
+            .unwrap();
+        assert_eq!(cache.get("k"), Some(transcript("Talk")));
+
+        let entries = cache.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].key, "k");
+        assert_eq!(entries[0].title, "Talk");
+        assert_eq!(entries[0].model, "base");
+
+        assert_eq!(cache.clear().unwrap(), 1);
+        assert!(cache.get("k").is_none());
+    }
+
+    #[test]
+    fn test_transcript_corrupt_entry_is_a_miss() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = TranscriptCache::new(dir.path());
+        std::fs::write(dir.path().join("k.json"), "{").unwrap();
+
+        assert!(cache.get("k").is_none());
+    }
+
+    #[test]
+    fn test_media_cache_key_is_filesystem_safe() {
+        let key = media_cache_key("https://example.com/a/../b?c=d", "http|a/b");
+        assert!(key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'));
+    }
+}
@@ -4,18 +4,27 @@
 
 //! Command-line argument parsing.
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
+
+use crate::formats::csv::DEFAULT_CSV_COLUMNS;
+use crate::formats::txt::DEFAULT_TXT_WIDTH;
 
 /// Transcribe YouTube videos and local media files to text.
 #[derive(Parser, Debug, Clone)]
 #[command(name = "voxtus")]
 #[command(author, version, about, long_about = None)]
+#[command(subcommand_negates_reqs = true, args_conflicts_with_subcommands = true)]
 pub struct Args {
-    /// YouTube URL or local media file path
-    #[arg(required_unless_present = "list_models")]
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Media URL (YouTube video, playlist or channel, other yt-dlp sites,
+    /// direct file link), podcast feed URL/file or local media file path
+    #[arg(required_unless_present_any = ["list_models", "list_formats", "json_schema"])]
     pub input: Option<String>,
 
-    /// Output format(s), comma-separated: txt,json,srt,vtt
+    /// Output format(s), comma-separated: txt,json,srt,vtt,sbv,ttml,ass,lrc,md,html,csv,tsv
+    /// or template:FILE (see --list-formats)
     #[arg(short, long, default_value = "txt")]
     pub format: String,
 
@@ -23,6 +32,11 @@ pub struct Args {
     #[arg(short, long)]
     pub name: Option<String>,
 
+    /// Output name template: {title}, {id}, {date}, {uploader}, {model}, {lang}, {stem}, {ext},
+    /// and {index}, {playlist}, {show} for playlists and feeds
+    #[arg(long, value_name = "TEMPLATE")]
+    pub output_template: Option<String>,
+
     /// Output directory
     #[arg(short, long)]
     pub output: Option<String>,
@@ -31,10 +45,15 @@ pub struct Args {
     #[arg(short, long, action = clap::ArgAction::Count)]
     pub verbose: u8,
 
-    /// Keep the downloaded/converted audio file
+    /// Keep the downloaded audio file
     #[arg(short, long)]
     pub keep: bool,
 
+    /// Format of the kept audio: original, mp3, flac, wav, opus, m4a, with an
+    /// optional bitrate such as mp3:192k (implies --keep)
+    #[arg(long, value_name = "FORMAT")]
+    pub keep_format: Option<String>,
+
     /// Whisper model to use
     #[arg(long, default_value = "small")]
     pub model: String,
@@ -43,13 +62,170 @@ pub struct Args {
     #[arg(long)]
     pub list_models: bool,
 
-    /// Overwrite existing files without confirmation
+    /// List available output formats and exit
+    #[arg(long)]
+    pub list_formats: bool,
+
+    /// Print the JSON Schema of JSON output and exit
+    #[arg(long)]
+    pub json_schema: bool,
+
+    /// Overwrite existing files without confirmation (same as --on-collision overwrite)
     #[arg(long)]
     pub overwrite: bool,
 
+    /// When an output file exists: prompt, overwrite, skip, suffix
+    #[arg(long, value_name = "POLICY", default_value = "prompt")]
+    pub on_collision: String,
+
     /// Output to stdout only (single format, no files created)
     #[arg(long)]
     pub stdout: bool,
+
+    /// Download backend: auto, yt-dlp, http
+    #[arg(long, default_value = "auto")]
+    pub downloader: String,
+
+    /// Use existing YouTube subtitles: prefer, only, never
+    #[arg(long, default_value = "never")]
+    pub subs: String,
+
+    /// Write one transcript per chapter (YouTube or embedded chapters)
+    #[arg(long)]
+    pub split_by_chapter: bool,
+
+    /// JSON output shape: voxtus, or openai (OpenAI Whisper verbose_json)
+    #[arg(long, value_name = "MODE")]
+    pub json_compat: Option<String>,
+
+    /// TXT layout: timestamps (one line per segment) or plain (wrapped paragraphs)
+    #[arg(long, value_name = "STYLE", default_value = "timestamps")]
+    pub txt_style: String,
+
+    /// Line width of plain TXT output (0 disables wrapping)
+    #[arg(long, value_name = "WIDTH", default_value_t = DEFAULT_TXT_WIDTH)]
+    pub txt_width: usize,
+
+    /// Start Markdown paragraphs with timestamps (linked for YouTube sources)
+    #[arg(long)]
+    pub md_timestamps: bool,
+
+    /// ASS subtitle style as key=value pairs, e.g. font=Arial,size=48,position=top
+    #[arg(long, value_name = "STYLE")]
+    pub ass_style: Option<String>,
+
+    /// Add karaoke tags with word timings to ASS subtitles
+    #[arg(long)]
+    pub ass_karaoke: bool,
+
+    /// CSV/TSV columns, comma-separated: id, start, end, duration, speaker,
+    /// text, confidence
+    #[arg(long, value_name = "COLUMNS", default_value = DEFAULT_CSV_COLUMNS)]
+    pub csv_columns: String,
+
+    /// Add word-level timestamps to LRC lyrics (enhanced LRC)
+    #[arg(long)]
+    pub lrc_words: bool,
+
+    /// Output name template for playlist entries [default: --output-template if
+    /// set, else "{index} - {title}"]
+    #[arg(long, value_name = "TEMPLATE")]
+    pub playlist_template: Option<String>,
+
+    /// Playlist index format(s), comma-separated: json,csv (json is always written)
+    #[arg(long, default_value = "json")]
+    pub index_format: String,
+
+    /// Treat the input as a podcast RSS/Atom feed
+    #[arg(long)]
+    pub feed: bool,
+
+    /// Output name template for feed episodes [default: --output-template if
+    /// set, else "{date} - {title}"]
+    #[arg(long, value_name = "TEMPLATE")]
+    pub episode_template: Option<String>,
+
+    /// Audio preprocessing filter (repeatable): a preset (loudnorm, denoise,
+    /// highpass[:HZ], lowpass[:HZ], speed[:FACTOR], speech) or a raw ffmpeg filter
+    #[arg(long = "audio-filter", value_name = "FILTER")]
+    pub audio_filters: Vec<String>,
+
+    /// Bypass the download and transcript caches
+    #[arg(long)]
+    pub no_cache: bool,
+
+    /// Config file [default: ~/.config/voxtus/config.json]
+    #[arg(long, value_name = "FILE", global = true)]
+    pub config: Option<String>,
+
+    /// Cookies file for yt-dlp (Netscape format)
+    #[arg(long, value_name = "FILE")]
+    pub cookies: Option<String>,
+
+    /// Browser to load cookies from for yt-dlp, e.g. firefox
+    #[arg(long, value_name = "BROWSER")]
+    pub cookies_from_browser: Option<String>,
+
+    /// Proxy URL for yt-dlp
+    #[arg(long, value_name = "URL")]
+    pub proxy: Option<String>,
+
+    /// Maximum yt-dlp download rate, e.g. 2M
+    #[arg(long, value_name = "RATE")]
+    pub limit_rate: Option<String>,
+
+    /// Number of yt-dlp download retries
+    #[arg(long)]
+    pub retries: Option<u32>,
+
+    /// Preferred audio format to download, e.g. m4a, opus
+    #[arg(long)]
+    pub audio_format: Option<String>,
+
+    /// Maximum audio bitrate to download, in kbit/s
+    #[arg(long, value_name = "KBPS")]
+    pub audio_quality: Option<u32>,
+
+    /// Extra argument passed to yt-dlp (repeatable, e.g. --yt-dlp-arg=--geo-bypass)
+    #[arg(long = "yt-dlp-arg", value_name = "ARG", allow_hyphen_values = true)]
+    pub yt_dlp_args: Vec<String>,
+}
+
+/// Subcommands.
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Manage the yt-dlp and ffmpeg binaries
+    Deps {
+        #[command(subcommand)]
+        action: DepsAction,
+    },
+    /// Inspect or clear the download and transcript caches
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+}
+
+/// Actions of the `cache` subcommand.
+#[derive(Subcommand, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheAction {
+    /// List cached downloads and transcripts, most recently used first
+    Ls,
+    /// Remove all cached downloads and transcripts
+    Clear,
+}
+
+/// Actions of the `deps` subcommand.
+#[derive(Subcommand, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepsAction {
+    /// Show which binaries are used and their versions
+    Status,
+    /// Update the bundled yt-dlp to the latest release
+    Update,
+    /// Use yt-dlp and ffmpeg from PATH
+    UseSystem,
+    /// Use the binaries downloaded by voxtus (default)
+    UseBundled,
 }
 
 impl Args {
@@ -82,6 +258,13 @@ mod tests {
         assert!(!args.overwrite);
         assert!(!args.stdout);
         assert_eq!(args.model, "small");
+        assert!(!args.split_by_chapter);
+        assert_eq!(args.subs, "never");
+        assert_eq!(args.downloader, "auto");
+        assert!(args.playlist_template.is_none());
+        assert_eq!(args.index_format, "json");
+        assert!(!args.feed);
+        assert!(args.episode_template.is_none());
     }
 
     #[test]
@@ -102,6 +285,11 @@ mod tests {
             "--stdout",
             "--model",
             "tiny",
+            "--split-by-chapter",
+            "--subs",
+            "prefer",
+            "--downloader",
+            "http",
         ]);
 
         assert_eq!(args.input, Some("test.mp3".to_string()));
@@ -113,6 +301,9 @@ mod tests {
         assert_eq!(args.output, Some("/tmp/output".to_string()));
         assert!(args.stdout);
         assert_eq!(args.model, "tiny");
+        assert!(args.split_by_chapter);
+        assert_eq!(args.subs, "prefer");
+        assert_eq!(args.downloader, "http");
     }
 
     #[test]
@@ -138,10 +329,273 @@ mod tests {
         assert_eq!(args.output, Some("/tmp/short".to_string()));
     }
 
+    #[test]
+    fn test_parse_playlist_flags() {
+        let args = Args::parse_from_iter([
+            "voxtus",
+            "https://www.youtube.com/playlist?list=PL123",
+            "--playlist-template",
+            "{id}",
+            "--index-format",
+            "json,csv",
+        ]);
+
+        assert_eq!(args.playlist_template.as_deref(), Some("{id}"));
+        assert_eq!(args.index_format, "json,csv");
+    }
+
+    #[test]
+    fn test_parse_feed_flags() {
+        let args = Args::parse_from_iter([
+            "voxtus",
+            "https://example.com/podcast",
+            "--feed",
+            "--episode-template",
+            "{show} - {title}",
+        ]);
+
+        assert!(args.feed);
+        assert_eq!(args.episode_template.as_deref(), Some("{show} - {title}"));
+    }
+
+    #[test]
+    fn test_parse_output_template() {
+        let args = Args::parse_from_iter(["voxtus", "video.mp4"]);
+        assert!(args.output_template.is_none());
+
+        let args = Args::parse_from_iter([
+            "voxtus",
+            "video.mp4",
+            "--output-template",
+            "{uploader}/{date} {title}",
+        ]);
+        assert_eq!(
+            args.output_template.as_deref(),
+            Some("{uploader}/{date} {title}")
+        );
+    }
+
+    #[test]
+    fn test_parse_on_collision() {
+        let args = Args::parse_from_iter(["voxtus", "video.mp4"]);
+        assert_eq!(args.on_collision, "prompt");
+
+        let args = Args::parse_from_iter(["voxtus", "video.mp4", "--on-collision", "suffix"]);
+        assert_eq!(args.on_collision, "suffix");
+    }
+
+    #[test]
+    fn test_parse_keep_format() {
+        let args = Args::parse_from_iter(["voxtus", "video.mp4"]);
+        assert!(args.keep_format.is_none());
+
+        let args = Args::parse_from_iter(["voxtus", "video.mp4", "--keep-format", "mp3:192k"]);
+        assert_eq!(args.keep_format.as_deref(), Some("mp3:192k"));
+    }
+
+    #[test]
+    fn test_parse_audio_filters() {
+        let args = Args::parse_from_iter([
+            "voxtus",
+            "lecture.mp4",
+            "--audio-filter",
+            "loudnorm",
+            "--audio-filter",
+            "highpass=f=200",
+        ]);
+        assert_eq!(args.audio_filters, vec!["loudnorm", "highpass=f=200"]);
+    }
+
+    #[test]
+    fn test_parse_no_cache() {
+        let args = Args::parse_from_iter(["voxtus", "video.mp4"]);
+        assert!(!args.no_cache);
+
+        let args = Args::parse_from_iter(["voxtus", "video.mp4", "--no-cache"]);
+        assert!(args.no_cache);
+    }
+
+    #[test]
+    fn test_parse_ass_options() {
+        let args = Args::parse_from_iter(["voxtus", "video.mp4"]);
+        assert!(args.ass_style.is_none());
+        assert!(!args.ass_karaoke);
+
+        let args = Args::parse_from_iter([
+            "voxtus",
+            "video.mp4",
+            "-f",
+            "ass",
+            "--ass-style",
+            "size=48,position=top",
+            "--ass-karaoke",
+        ]);
+        assert_eq!(args.ass_style.as_deref(), Some("size=48,position=top"));
+        assert!(args.ass_karaoke);
+    }
+
+    #[test]
+    fn test_parse_csv_columns() {
+        let args = Args::parse_from_iter(["voxtus", "video.mp4"]);
+        assert_eq!(args.csv_columns, DEFAULT_CSV_COLUMNS);
+
+        let args = Args::parse_from_iter([
+            "voxtus",
+            "video.mp4",
+            "-f",
+            "csv",
+            "--csv-columns",
+            "start,text,confidence",
+        ]);
+        assert_eq!(args.csv_columns, "start,text,confidence");
+    }
+
+    #[test]
+    fn test_parse_json_options() {
+        let args = Args::parse_from_iter(["voxtus", "--json-schema"]);
+        assert!(args.json_schema);
+        assert!(args.input.is_none());
+        assert!(args.json_compat.is_none());
+
+        let args = Args::parse_from_iter([
+            "voxtus",
+            "video.mp4",
+            "-f",
+            "json",
+            "--json-compat",
+            "openai",
+        ]);
+        assert_eq!(args.json_compat.as_deref(), Some("openai"));
+    }
+
+    #[test]
+    fn test_parse_txt_style() {
+        let args = Args::parse_from_iter(["voxtus", "video.mp4"]);
+        assert_eq!(args.txt_style, "timestamps");
+        assert_eq!(args.txt_width, DEFAULT_TXT_WIDTH);
+
+        let args = Args::parse_from_iter([
+            "voxtus",
+            "video.mp4",
+            "--txt-style",
+            "plain",
+            "--txt-width",
+            "100",
+        ]);
+        assert_eq!(args.txt_style, "plain");
+        assert_eq!(args.txt_width, 100);
+    }
+
+    #[test]
+    fn test_parse_lrc_words() {
+        let args = Args::parse_from_iter(["voxtus", "song.mp3"]);
+        assert!(!args.lrc_words);
+
+        let args = Args::parse_from_iter(["voxtus", "song.mp3", "-f", "lrc", "--lrc-words"]);
+        assert!(args.lrc_words);
+    }
+
+    #[test]
+    fn test_parse_md_timestamps() {
+        let args = Args::parse_from_iter(["voxtus", "video.mp4"]);
+        assert!(!args.md_timestamps);
+
+        let args = Args::parse_from_iter(["voxtus", "video.mp4", "-f", "md", "--md-timestamps"]);
+        assert!(args.md_timestamps);
+    }
+
+    #[test]
+    fn test_parse_yt_dlp_flags() {
+        let args = Args::parse_from_iter([
+            "voxtus",
+            "https://www.youtube.com/watch?v=abc",
+            "--cookies",
+            "~/cookies.txt",
+            "--cookies-from-browser",
+            "firefox",
+            "--proxy",
+            "socks5://127.0.0.1:1080",
+            "--limit-rate",
+            "2M",
+            "--retries",
+            "5",
+            "--audio-format",
+            "m4a",
+            "--audio-quality",
+            "128",
+            "--yt-dlp-arg",
+            "--geo-bypass",
+            "--yt-dlp-arg=--no-check-certificates",
+        ]);
+
+        assert_eq!(args.cookies.as_deref(), Some("~/cookies.txt"));
+        assert_eq!(args.cookies_from_browser.as_deref(), Some("firefox"));
+        assert_eq!(args.proxy.as_deref(), Some("socks5://127.0.0.1:1080"));
+        assert_eq!(args.limit_rate.as_deref(), Some("2M"));
+        assert_eq!(args.retries, Some(5));
+        assert_eq!(args.audio_format.as_deref(), Some("m4a"));
+        assert_eq!(args.audio_quality, Some(128));
+        assert_eq!(
+            args.yt_dlp_args,
+            vec!["--geo-bypass", "--no-check-certificates"]
+        );
+    }
+
+    #[test]
+    fn test_parse_deps_subcommand() {
+        let args = Args::parse_from_iter(["voxtus", "deps", "status"]);
+        assert!(args.input.is_none());
+        assert_eq!(
+            args.command,
+            Some(Command::Deps {
+                action: DepsAction::Status
+            })
+        );
+
+        let args =
+            Args::parse_from_iter(["voxtus", "deps", "use-system", "--config", "/tmp/c.json"]);
+        assert_eq!(
+            args.command,
+            Some(Command::Deps {
+                action: DepsAction::UseSystem
+            })
+        );
+        assert_eq!(args.config.as_deref(), Some("/tmp/c.json"));
+
+        let args = Args::parse_from_iter(["voxtus", "video.mp4"]);
+        assert!(args.command.is_none());
+    }
+
+    #[test]
+    fn test_parse_cache_subcommand() {
+        let args = Args::parse_from_iter(["voxtus", "cache", "ls"]);
+        assert_eq!(
+            args.command,
+            Some(Command::Cache {
+                action: CacheAction::Ls
+            })
+        );
+
+        let args = Args::parse_from_iter(["voxtus", "cache", "clear"]);
+        assert_eq!(
+            args.command,
+            Some(Command::Cache {
+                action: CacheAction::Clear
+            })
+        );
+    }
+
     #[test]
     fn test_list_models_without_input() {
         let args = Args::parse_from_iter(["voxtus", "--list-models"]);
         assert!(args.list_models);
         assert!(args.input.is_none());
     }
+
+    #[test]
+    fn test_list_formats_without_input() {
+        let args = Args::parse_from_iter(["voxtus", "--list-formats"]);
+        assert!(args.list_formats);
+        assert!(args.input.is_none());
+    }
 }
@@ -4,43 +4,52 @@
 
 //! Configuration handling.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
 
+use serde::Deserialize;
+
+use crate::audio::{AudioFilters, KeepFormat};
+use crate::cache::DEFAULT_MEDIA_CACHE_SIZE;
 use crate::cli::Args;
+use crate::deps::BinarySource;
+use crate::download::{Downloaders, YtDlpDownloader, YtDlpOptions};
 use crate::error::{Error, Result};
+use crate::feed::DEFAULT_EPISODE_TEMPLATE;
+use crate::formats::ass::AssFormatter;
+use crate::formats::ass::{AssOptions, AssStyle};
+use crate::formats::csv::{CsvColumn, CsvFormatter, TsvFormatter};
+use crate::formats::json::{JsonCompat, JsonFormatter};
+use crate::formats::lrc::LrcFormatter;
+use crate::formats::md::{MarkdownFormatter, MarkdownOptions};
+use crate::formats::txt::{TxtFormatter, TxtOptions, TxtStyle};
+use crate::formats::{Formatter, Formatters};
+use crate::output::{CollisionPolicy, DEFAULT_OUTPUT_TEMPLATE, validate_output_template};
+use crate::playlist::{DEFAULT_ENTRY_TEMPLATE, IndexFormat};
 
-/// Supported output formats.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum OutputFormat {
-    Txt,
-    Json,
-    Srt,
-    Vtt,
+/// When to use existing subtitles instead of transcribing with Whisper.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SubsMode {
+    /// Use creator-uploaded subtitles in the spoken language when available,
+    /// otherwise Whisper.
+    Prefer,
+    /// Only use subtitles (falling back to automatic captions), never Whisper.
+    Only,
+    /// Always transcribe with Whisper.
+    #[default]
+    Never,
 }
 
-impl std::str::FromStr for OutputFormat {
+impl std::str::FromStr for SubsMode {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self> {
         match s.to_lowercase().as_str() {
-            "txt" => Ok(Self::Txt),
-            "json" => Ok(Self::Json),
-            "srt" => Ok(Self::Srt),
-            "vtt" => Ok(Self::Vtt),
-            _ => Err(Error::InvalidFormat(s.to_string())),
-        }
-    }
-}
-
-impl OutputFormat {
-    /// Get the file extension for this format.
-    pub fn extension(&self) -> &'static str {
-        match self {
-            Self::Txt => "txt",
-            Self::Json => "json",
-            Self::Srt => "srt",
-            Self::Vtt => "vtt",
+            "prefer" => Ok(Self::Prefer),
+            "only" => Ok(Self::Only),
+            "never" => Ok(Self::Never),
+            _ => Err(Error::InvalidSubsMode(s.to_string())),
         }
     }
 }
@@ -136,43 +145,308 @@ pub const AVAILABLE_MODELS: &[WhisperModel] = &[
     },
 ];
 
+/// Settings read from the config file.
+///
+/// The file is JSON, read from `--config` or the default location (see
+/// [`default_config_path`]). Command-line flags override its values.
+///
+/// # Examples
+///
+/// ```
+/// use voxtus::config::ConfigFile;
+///
+/// let file = ConfigFile::parse(r#"{"yt_dlp": {"proxy": "socks5://127.0.0.1:1080"}}"#).unwrap();
+/// assert_eq!(file.yt_dlp.proxy.as_deref(), Some("socks5://127.0.0.1:1080"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    /// Where the yt-dlp and ffmpeg binaries come from.
+    pub binaries: BinarySource,
+    pub yt_dlp: YtDlpOptions,
+    pub cache: CacheSettings,
+    /// Default for `--output-template`.
+    pub output_template: Option<String>,
+    /// Default `--audio-filter` presets and raw filters.
+    pub audio_filters: Vec<String>,
+    /// Default `--ass-style` settings.
+    pub ass_style: Option<String>,
+}
+
+/// Cache settings from the config file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheSettings {
+    /// Media cache size limit in MiB; `0` disables the media cache.
+    pub media_max_mb: Option<u64>,
+}
+
+impl CacheSettings {
+    /// Media cache size limit in bytes.
+    pub fn media_max_size(&self) -> u64 {
+        self.media_max_mb
+            .map_or(DEFAULT_MEDIA_CACHE_SIZE, |mb| mb * 1024 * 1024)
+    }
+}
+
+impl ConfigFile {
+    /// Parse config file contents.
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut file: Self =
+            serde_json::from_str(content).map_err(|e| Error::InvalidConfig(e.to_string()))?;
+        file.yt_dlp.cookies = file
+            .yt_dlp
+            .cookies
+            .map(|p| expand_tilde(&p.to_string_lossy()));
+        Ok(file)
+    }
+
+    /// Load the config file at `path`, or from the default location.
+    ///
+    /// An explicitly given file must exist; a missing default file just
+    /// means no settings.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let path = match path {
+            Some(path) if !path.exists() => {
+                return Err(Error::FileNotFound(path.display().to_string()));
+            }
+            Some(path) => path.to_path_buf(),
+            None => match default_config_path() {
+                Some(path) if path.exists() => path,
+                _ => return Ok(Self::default()),
+            },
+        };
+
+        let content = std::fs::read_to_string(&path)?;
+        Self::parse(&content)
+            .map_err(|e| Error::InvalidConfig(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Set a top-level setting in the config file, creating it if needed.
+    ///
+    /// Other settings in the file are left as they are. Returns the path of
+    /// the file written.
+    pub fn set(path: Option<&Path>, key: &str, value: serde_json::Value) -> Result<PathBuf> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => default_config_path().ok_or_else(|| {
+                Error::InvalidConfig("Could not determine config directory".into())
+            })?,
+        };
+
+        let mut settings = if path.exists() {
+            serde_json::from_str(&std::fs::read_to_string(&path)?)
+                .map_err(|e| Error::InvalidConfig(format!("{}: {}", path.display(), e)))?
+        } else {
+            serde_json::Value::Object(Default::default())
+        };
+        let object = settings.as_object_mut().ok_or_else(|| {
+            Error::InvalidConfig(format!("{}: not a JSON object", path.display()))
+        })?;
+        object.insert(key.to_string(), value);
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(&settings)?)?;
+        Ok(path)
+    }
+}
+
+/// Default config file location, e.g. `~/.config/voxtus/config.json`.
+pub fn default_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("voxtus").join("config.json"))
+}
+
 /// Validated configuration for the transcription process.
 #[derive(Debug, Clone)]
 pub struct Config {
     pub input_path: String,
-    pub formats: Vec<OutputFormat>,
+    pub formats: Vec<Arc<dyn Formatter>>,
     pub custom_name: Option<String>,
+    /// Template for output names, used when no custom name is given.
+    pub output_template: String,
     pub output_dir: PathBuf,
     pub verbose_level: u8,
     pub keep_audio: bool,
+    /// How kept audio is saved.
+    pub keep_format: KeepFormat,
+    /// Preprocessing applied to the audio before transcription.
+    pub audio_filters: AudioFilters,
     pub model: String,
-    pub overwrite_files: bool,
+    /// What to do when an output file already exists.
+    pub collision: CollisionPolicy,
     pub stdout_mode: bool,
+    pub split_by_chapter: bool,
+    pub subs: SubsMode,
+    /// Download backend name, or `None` to pick one by URL.
+    pub downloader: Option<String>,
+    /// Output template for playlist entries.
+    pub playlist_template: String,
+    pub index_formats: Vec<IndexFormat>,
+    /// Treat the input as a podcast feed even without a feed-like name.
+    pub feed: bool,
+    /// Output template for feed episodes.
+    pub episode_template: String,
+    /// Options passed through to yt-dlp.
+    pub yt_dlp: YtDlpOptions,
+    pub binaries: BinarySource,
+    /// Media cache size limit in bytes; `0` disables the media cache.
+    pub media_cache_size: u64,
+    /// Bypass the media and transcript caches.
+    pub no_cache: bool,
 }
 
 impl Config {
     /// Create a Config from parsed CLI arguments.
     pub fn from_args(args: &Args) -> Result<Self> {
-        let formats = parse_formats(&args.format, args.stdout)?;
         let model = validate_model(&args.model)?;
         let output_dir = resolve_output_dir(args.output.as_deref())?;
         let custom_name = args.name.as_ref().map(|n| strip_txt_extension(n));
+        let file = ConfigFile::load(args.config.as_deref().map(Path::new))?;
+        // An explicit output template also names playlist entries and episodes
+        let explicit_template = args
+            .output_template
+            .clone()
+            .or_else(|| file.output_template.clone());
+        let batch_template = |template: &Option<String>, default: &str| {
+            template
+                .clone()
+                .or_else(|| explicit_template.clone())
+                .unwrap_or_else(|| default.to_string())
+        };
+        let playlist_template = batch_template(&args.playlist_template, DEFAULT_ENTRY_TEMPLATE);
+        let episode_template = batch_template(&args.episode_template, DEFAULT_EPISODE_TEMPLATE);
+        let output_template = explicit_template
+            .clone()
+            .unwrap_or_else(|| DEFAULT_OUTPUT_TEMPLATE.to_string());
+        for template in [&output_template, &playlist_template, &episode_template] {
+            validate_output_template(template)?;
+        }
+        let mut index_formats = parse_index_formats(&args.index_format)?;
+        // Later runs resume from the JSON index, so it is always written
+        if !index_formats.contains(&IndexFormat::Json) {
+            index_formats.insert(0, IndexFormat::Json);
+        }
+        let csv_columns = parse_csv_columns(&args.csv_columns)?;
+        let subs = SubsMode::from_str(&args.subs)?;
+        let downloader = validate_downloader(&args.downloader)?;
+        if let Some(name) = &downloader
+            && subs == SubsMode::Only
+            && name != YtDlpDownloader::NAME
+        {
+            return Err(Error::SubsNeedYtDlp(name.clone()));
+        }
+        let keep_format = args
+            .keep_format
+            .as_deref()
+            .map(KeepFormat::from_str)
+            .transpose()?
+            .unwrap_or_default();
+        let audio_filters = if args.audio_filters.is_empty() {
+            AudioFilters::parse(&file.audio_filters)?
+        } else {
+            AudioFilters::parse(&args.audio_filters)?
+        };
+        let mut ass_style = AssStyle::default();
+        for spec in [&file.ass_style, &args.ass_style].into_iter().flatten() {
+            ass_style.apply(spec)?;
+        }
+        let formats = configured_formatters(args, ass_style, csv_columns)?
+            .parse(&args.format, args.stdout)?;
+        let collision = if args.overwrite {
+            CollisionPolicy::Overwrite
+        } else {
+            CollisionPolicy::from_str(&args.on_collision)?
+        };
+        let yt_dlp = file.yt_dlp.merge(YtDlpOptions {
+            cookies: args.cookies.as_deref().map(expand_tilde),
+            cookies_from_browser: args.cookies_from_browser.clone(),
+            proxy: args.proxy.clone(),
+            limit_rate: args.limit_rate.clone(),
+            retries: args.retries,
+            audio_format: args.audio_format.clone(),
+            audio_quality: args.audio_quality,
+            extra_args: args.yt_dlp_args.clone(),
+        });
 
         Ok(Self {
             input_path: args.input.clone().unwrap_or_default(),
             formats,
             custom_name,
+            output_template,
             output_dir,
             verbose_level: args.verbose,
-            keep_audio: args.keep,
+            keep_audio: args.keep || args.keep_format.is_some(),
+            keep_format,
+            audio_filters,
             model,
-            overwrite_files: args.overwrite,
+            collision,
             stdout_mode: args.stdout,
+            split_by_chapter: args.split_by_chapter,
+            subs,
+            downloader,
+            playlist_template,
+            index_formats,
+            feed: args.feed,
+            episode_template,
+            yt_dlp,
+            binaries: file.binaries,
+            media_cache_size: file.cache.media_max_size(),
+            no_cache: args.no_cache,
         })
     }
 }
 
-/// Parse comma-separated format string into validated formats.
+/// The built-in formatters, set up with the format options from `args`.
+fn configured_formatters(
+    args: &Args,
+    ass_style: AssStyle,
+    csv_columns: Vec<CsvColumn>,
+) -> Result<Formatters> {
+    let json_compat = args
+        .json_compat
+        .as_deref()
+        .map(JsonCompat::from_str)
+        .transpose()?
+        .unwrap_or_default();
+
+    let mut formatters = Formatters::default();
+    formatters.register(Box::new(TxtFormatter {
+        options: TxtOptions {
+            style: TxtStyle::from_str(&args.txt_style)?,
+            width: args.txt_width,
+        },
+    }));
+    formatters.register(Box::new(JsonFormatter {
+        compat: json_compat,
+    }));
+    formatters.register(Box::new(AssFormatter {
+        options: AssOptions {
+            style: ass_style,
+            karaoke: args.ass_karaoke,
+        },
+    }));
+    formatters.register(Box::new(LrcFormatter {
+        words: args.lrc_words,
+    }));
+    formatters.register(Box::new(MarkdownFormatter {
+        options: MarkdownOptions {
+            timestamps: args.md_timestamps,
+        },
+    }));
+    formatters.register(Box::new(CsvFormatter {
+        columns: csv_columns.clone(),
+    }));
+    formatters.register(Box::new(TsvFormatter {
+        columns: csv_columns,
+    }));
+    Ok(formatters)
+}
+
+/// Parse comma-separated format string into the built-in formatters.
+///
+/// See [`Formatters::parse`] to also accept custom formatters.
 ///
 /// # Examples
 ///
@@ -189,21 +463,54 @@ impl Config {
 /// // Multiple formats not allowed with stdout mode
 /// assert!(parse_formats("txt,json", true).is_err());
 /// ```
-pub fn parse_formats(format_str: &str, stdout_mode: bool) -> Result<Vec<OutputFormat>> {
-    let formats: Result<Vec<OutputFormat>> = format_str
+pub fn parse_formats(format_str: &str, stdout_mode: bool) -> Result<Vec<Arc<dyn Formatter>>> {
+    Formatters::default().parse(format_str, stdout_mode)
+}
+
+/// Parse comma-separated playlist index format string.
+///
+/// # Examples
+///
+/// ```
+/// use voxtus::config::parse_index_formats;
+///
+/// assert_eq!(parse_index_formats("json,csv").unwrap().len(), 2);
+/// assert!(parse_index_formats("xml").is_err());
+/// ```
+pub fn parse_index_formats(format_str: &str) -> Result<Vec<IndexFormat>> {
+    format_str
         .split(',')
         .map(|s| s.trim())
         .filter(|s| !s.is_empty())
-        .map(OutputFormat::from_str)
-        .collect();
+        .map(IndexFormat::from_str)
+        .collect()
+}
 
-    let formats = formats?;
+/// Parse comma-separated CSV/TSV column names.
+///
+/// # Examples
+///
+/// ```
+/// use voxtus::config::parse_csv_columns;
+///
+/// assert_eq!(parse_csv_columns("id, start,text").unwrap().len(), 3);
+/// assert!(parse_csv_columns("id,").is_ok());
+/// assert!(parse_csv_columns("").is_err());
+/// assert!(parse_csv_columns("words").is_err());
+/// ```
+pub fn parse_csv_columns(columns_str: &str) -> Result<Vec<CsvColumn>> {
+    let columns = columns_str
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(CsvColumn::from_str)
+        .collect::<Result<Vec<_>>>()?;
 
-    if stdout_mode && formats.len() > 1 {
-        return Err(Error::MultipleFormatsWithStdout);
+    if columns.is_empty() {
+        return Err(Error::InvalidCsvColumn(columns_str.to_string()));
     }
 
-    Ok(formats)
+    Ok(columns)
 }
 
 /// Validate that the model name is valid.
@@ -233,6 +540,30 @@ pub fn validate_model(model: &str) -> Result<String> {
     }
 }
 
+/// Validate a download backend name against the default backends.
+///
+/// Returns `None` for `auto`, which selects the backend by URL.
+///
+/// # Examples
+///
+/// ```
+/// use voxtus::config::validate_downloader;
+///
+/// assert_eq!(validate_downloader("auto").unwrap(), None);
+/// assert_eq!(validate_downloader("yt-dlp").unwrap(), Some("yt-dlp".to_string()));
+/// assert!(validate_downloader("ftp").is_err());
+/// ```
+pub fn validate_downloader(name: &str) -> Result<Option<String>> {
+    if name.eq_ignore_ascii_case("auto") {
+        return Ok(None);
+    }
+
+    match Downloaders::default().get(name) {
+        Some(downloader) => Ok(Some(downloader.name().to_string())),
+        None => Err(Error::InvalidDownloader(name.to_string())),
+    }
+}
+
 /// Resolve output directory, expanding ~ and creating if needed.
 pub fn resolve_output_dir(output: Option<&str>) -> Result<PathBuf> {
     let path = match output {
@@ -281,6 +612,55 @@ mod tests {
 
     use proptest::prelude::*;
 
+    use crate::formats::{FormatContext, Metadata, Segment, Transcript, Word, ass, csv, json, txt};
+
+    fn sample_transcript() -> Transcript {
+        let mut segment = Segment::new(0.0, 2.5, "Hello there world");
+        segment.words = vec![
+            Word::new(0.0, 0.5, "Hello"),
+            Word::new(0.5, 1.5, "there"),
+            Word::new(1.5, 2.5, "world"),
+        ];
+        let metadata = Metadata::new("Talk", "talk.mp3", Some(2.5), "tiny", None);
+        Transcript::new(
+            vec![segment, Segment::new(2.5, 4.0, "Second line")],
+            metadata,
+        )
+    }
+
+    /// Config for `video.mp4` with `args`, reading `json` as the config file
+    /// instead of the user's own.
+    fn try_config_with(json: &str, args: &[&str]) -> Result<Config> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        std::fs::write(&path, json).unwrap();
+
+        let mut argv = vec![
+            "voxtus",
+            "video.mp4",
+            "--output",
+            dir.path().to_str().unwrap(),
+            "--config",
+            path.to_str().unwrap(),
+        ];
+        argv.extend_from_slice(args);
+        Config::from_args(&Args::parse_from_iter(argv))
+    }
+
+    fn config_with(json: &str, args: &[&str]) -> Config {
+        try_config_with(json, args).unwrap()
+    }
+
+    /// Output of the configured formatter `name` for [`sample_transcript`].
+    fn format_output(config: &Config, name: &str) -> String {
+        let format = config
+            .formats
+            .iter()
+            .find(|format| format.name() == name)
+            .unwrap();
+        format.format(&sample_transcript(), &FormatContext::default())
+    }
+
     proptest! {
         #[test]
         fn prop_valid_models_always_validate(model in prop_oneof![
@@ -362,28 +742,79 @@ mod tests {
         }
     }
 
+    fn names(formats: &[Arc<dyn Formatter>]) -> Vec<&str> {
+        formats.iter().map(|f| f.name()).collect()
+    }
+
     #[test]
     fn test_parse_single_format() {
         let formats = parse_formats("txt", false).unwrap();
-        assert_eq!(formats, vec![OutputFormat::Txt]);
+        assert_eq!(names(&formats), vec!["txt"]);
     }
 
     #[test]
     fn test_parse_multiple_formats() {
         let formats = parse_formats("txt,json", false).unwrap();
-        assert_eq!(formats, vec![OutputFormat::Txt, OutputFormat::Json]);
+        assert_eq!(names(&formats), vec!["txt", "json"]);
+    }
+
+    #[test]
+    fn test_parse_markdown_format() {
+        let formats = parse_formats("md,markdown", false).unwrap();
+        assert_eq!(names(&formats), vec!["md", "md"]);
+    }
+
+    #[test]
+    fn test_parse_ttml_format() {
+        let formats = parse_formats("ttml,DFXP", false).unwrap();
+        assert_eq!(names(&formats), vec!["ttml", "ttml"]);
+    }
+
+    #[test]
+    fn test_parse_csv_formats() {
+        let formats = parse_formats("csv,TSV", false).unwrap();
+        assert_eq!(names(&formats), vec!["csv", "tsv"]);
+    }
+
+    #[test]
+    fn test_parse_csv_columns() {
+        use crate::formats::csv::DEFAULT_CSV_COLUMNS;
+
+        assert_eq!(
+            parse_csv_columns("speaker,text,confidence").unwrap(),
+            vec![CsvColumn::Speaker, CsvColumn::Text, CsvColumn::Confidence]
+        );
+        assert_eq!(
+            parse_csv_columns(DEFAULT_CSV_COLUMNS).unwrap(),
+            vec![
+                CsvColumn::Id,
+                CsvColumn::Start,
+                CsvColumn::End,
+                CsvColumn::Text
+            ]
+        );
+        assert!(matches!(
+            parse_csv_columns(" , "),
+            Err(Error::InvalidCsvColumn(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_lyrics_and_caption_formats() {
+        let formats = parse_formats("LRC,sbv", false).unwrap();
+        assert_eq!(names(&formats), vec!["lrc", "sbv"]);
     }
 
     #[test]
     fn test_parse_formats_with_spaces() {
         let formats = parse_formats("txt, json", false).unwrap();
-        assert_eq!(formats, vec![OutputFormat::Txt, OutputFormat::Json]);
+        assert_eq!(names(&formats), vec!["txt", "json"]);
     }
 
     #[test]
     fn test_parse_formats_case_insensitive() {
         let formats = parse_formats("TXT,JSON", false).unwrap();
-        assert_eq!(formats, vec![OutputFormat::Txt, OutputFormat::Json]);
+        assert_eq!(names(&formats), vec!["txt", "json"]);
     }
 
     #[test]
@@ -401,7 +832,295 @@ mod tests {
     #[test]
     fn test_single_format_with_stdout_allowed() {
         let formats = parse_formats("json", true).unwrap();
-        assert_eq!(formats, vec![OutputFormat::Json]);
+        assert_eq!(names(&formats), vec!["json"]);
+    }
+
+    #[test]
+    fn test_parse_index_formats() {
+        let formats = parse_index_formats("json, csv").unwrap();
+        assert_eq!(formats, vec![IndexFormat::Json, IndexFormat::Csv]);
+        assert!(parse_index_formats("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_subs_mode_from_str() {
+        assert_eq!(SubsMode::from_str("prefer").unwrap(), SubsMode::Prefer);
+        assert_eq!(SubsMode::from_str("ONLY").unwrap(), SubsMode::Only);
+        assert_eq!(SubsMode::from_str("never").unwrap(), SubsMode::Never);
+        assert!(matches!(
+            SubsMode::from_str("always"),
+            Err(Error::InvalidSubsMode(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_downloader() {
+        assert_eq!(validate_downloader("AUTO").unwrap(), None);
+        assert_eq!(
+            validate_downloader("HTTP").unwrap(),
+            Some("http".to_string())
+        );
+        assert!(matches!(
+            validate_downloader("ftp"),
+            Err(Error::InvalidDownloader(_))
+        ));
+    }
+
+    #[test]
+    fn test_config_file_parse() {
+        let file = ConfigFile::parse(
+            r#"{"yt_dlp": {"cookies": "cookies.txt", "retries": 10, "extra_args": ["--geo-bypass"]}}"#,
+        )
+        .unwrap();
+        assert_eq!(file.yt_dlp.cookies, Some(PathBuf::from("cookies.txt")));
+        assert_eq!(file.yt_dlp.retries, Some(10));
+        assert_eq!(file.yt_dlp.extra_args, vec!["--geo-bypass"]);
+
+        assert_eq!(ConfigFile::parse("{}").unwrap(), ConfigFile::default());
+        assert_eq!(
+            ConfigFile::parse(r#"{"cache": {"media_max_mb": 1}}"#)
+                .unwrap()
+                .cache
+                .media_max_size(),
+            1024 * 1024
+        );
+        assert_eq!(
+            ConfigFile::parse(r#"{"output_template": "{id}"}"#)
+                .unwrap()
+                .output_template
+                .as_deref(),
+            Some("{id}")
+        );
+        assert_eq!(
+            CacheSettings::default().media_max_size(),
+            DEFAULT_MEDIA_CACHE_SIZE
+        );
+        assert!(matches!(
+            ConfigFile::parse(r#"{"ytdlp": {}}"#),
+            Err(Error::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_config_file_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        std::fs::write(&path, r#"{"yt_dlp": {"limit_rate": "1M"}}"#).unwrap();
+
+        let file = ConfigFile::load(Some(&path)).unwrap();
+        assert_eq!(file.yt_dlp.limit_rate.as_deref(), Some("1M"));
+        assert!(matches!(
+            ConfigFile::load(Some(&dir.path().join("missing.json"))),
+            Err(Error::FileNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_config_file_set_keeps_other_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("voxtus").join("config.json");
+
+        ConfigFile::set(Some(&path), "binaries", serde_json::json!("system")).unwrap();
+        assert_eq!(
+            ConfigFile::load(Some(&path)).unwrap().binaries,
+            BinarySource::System
+        );
+
+        std::fs::write(&path, r#"{"binaries": "system", "yt_dlp": {"retries": 4}}"#).unwrap();
+        ConfigFile::set(Some(&path), "binaries", serde_json::json!("bundled")).unwrap();
+        let file = ConfigFile::load(Some(&path)).unwrap();
+        assert_eq!(file.binaries, BinarySource::Bundled);
+        assert_eq!(file.yt_dlp.retries, Some(4));
+    }
+
+    #[test]
+    fn test_from_args_cli_overrides_config_file() {
+        let config = config_with(
+            r#"{"yt_dlp": {"proxy": "http://file", "retries": 2, "extra_args": ["--a"]}}"#,
+            &["--proxy", "http://cli", "--yt-dlp-arg=--b"],
+        );
+        assert_eq!(config.yt_dlp.proxy.as_deref(), Some("http://cli"));
+        assert_eq!(config.yt_dlp.retries, Some(2));
+        assert_eq!(config.yt_dlp.extra_args, vec!["--a", "--b"]);
+        assert_eq!(config.output_template, DEFAULT_OUTPUT_TEMPLATE);
+        assert_eq!(config.collision, CollisionPolicy::Prompt);
+    }
+
+    #[test]
+    fn test_from_args_output_options() {
+        let config = |args: &[&str]| try_config_with("{}", args);
+
+        assert_eq!(
+            config(&["--on-collision", "skip"]).unwrap().collision,
+            CollisionPolicy::Skip
+        );
+        assert_eq!(
+            config(&["--overwrite"]).unwrap().collision,
+            CollisionPolicy::Overwrite
+        );
+        assert!(matches!(
+            config(&["--on-collision", "ask"]),
+            Err(Error::InvalidCollisionPolicy(_))
+        ));
+
+        let keep = config(&[]).unwrap();
+        assert!(!keep.keep_audio);
+        assert_eq!(keep.keep_format, KeepFormat::Original);
+        let keep = config(&["--keep-format", "flac"]).unwrap();
+        assert!(keep.keep_audio);
+        assert_eq!(keep.keep_format.extension(), Some("flac"));
+        assert!(matches!(
+            config(&["--keep-format", "mp4"]),
+            Err(Error::InvalidAudioFormat(_))
+        ));
+
+        assert!(config(&[]).unwrap().audio_filters.is_empty());
+        assert_eq!(
+            config(&["--audio-filter", "denoise"])
+                .unwrap()
+                .audio_filters
+                .chain(),
+            "afftdn=nf=-25"
+        );
+        assert!(matches!(
+            config(&["--audio-filter", "echo"]),
+            Err(Error::InvalidAudioFilter(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_args_ass_style() {
+        let config =
+            |args: &[&str]| try_config_with(r#"{"ass_style": "font=Roboto,size=40"}"#, args);
+
+        let transcript = sample_transcript();
+        let expected = |spec: &str, karaoke: bool| {
+            let mut style = AssStyle::default();
+            style.apply(spec).unwrap();
+            let options = AssOptions { style, karaoke };
+            ass::format_transcript(&transcript.segments, &transcript.metadata, &options)
+        };
+
+        let output = format_output(&config(&["--format", "ass"]).unwrap(), "ass");
+        assert_eq!(output, expected("font=Roboto,size=40", false));
+
+        let args = ["--format", "ass", "--ass-style", "size=48", "--ass-karaoke"];
+        let output = format_output(&config(&args).unwrap(), "ass");
+        assert_eq!(output, expected("font=Roboto,size=48", true));
+
+        assert!(matches!(
+            config(&["--ass-style", "position=left"]),
+            Err(Error::InvalidAssStyle(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_args_rejects_invalid_output_template() {
+        assert!(matches!(
+            try_config_with("{}", &["--output-template", "{nope}"]),
+            Err(Error::InvalidTemplate(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_args_rejects_invalid_playlist_template() {
+        for template in ["../{id}", "/tmp/{id}", "{uploader}/{nope}"] {
+            assert!(
+                matches!(
+                    try_config_with("{}", &["--playlist-template", template]),
+                    Err(Error::InvalidTemplate(_))
+                ),
+                "{}",
+                template
+            );
+        }
+    }
+
+    #[test]
+    fn test_batch_templates_default_to_output_template() {
+        let config = |args: &[&str]| config_with("{}", args);
+
+        let defaults = config(&[]);
+        assert_eq!(defaults.playlist_template, DEFAULT_ENTRY_TEMPLATE);
+        assert_eq!(defaults.episode_template, DEFAULT_EPISODE_TEMPLATE);
+
+        let templated = config(&["--output-template", "{playlist}/{index} {title}"]);
+        assert_eq!(templated.playlist_template, "{playlist}/{index} {title}");
+        assert_eq!(templated.episode_template, "{playlist}/{index} {title}");
+
+        let explicit = config(&[
+            "--output-template",
+            "{title}",
+            "--episode-template",
+            "{show}/{date} {title}",
+        ]);
+        assert_eq!(explicit.playlist_template, "{title}");
+        assert_eq!(explicit.episode_template, "{show}/{date} {title}");
+    }
+
+    #[test]
+    fn test_index_formats_always_include_json() {
+        let config = config_with("{}", &["--index-format", "csv"]);
+        assert_eq!(
+            config.index_formats,
+            vec![IndexFormat::Json, IndexFormat::Csv]
+        );
+        let config = config_with("{}", &["--index-format", "csv,json"]);
+        assert_eq!(
+            config.index_formats,
+            vec![IndexFormat::Csv, IndexFormat::Json]
+        );
+    }
+
+    #[test]
+    fn test_subs_only_requires_yt_dlp() {
+        let config = |downloader: &str| {
+            try_config_with("{}", &["--subs", "only", "--downloader", downloader])
+        };
+
+        assert!(matches!(
+            config("http"),
+            Err(Error::SubsNeedYtDlp(name)) if name == "http"
+        ));
+        assert_eq!(config("yt-dlp").unwrap().subs, SubsMode::Only);
+        assert!(config("auto").is_ok());
+    }
+
+    #[test]
+    fn test_config_txt_style() {
+        let config = config_with("{}", &["--txt-style", "plain", "--txt-width", "0"]);
+        let transcript = sample_transcript();
+        assert_eq!(
+            format_output(&config, "txt"),
+            txt::format_plain(&transcript.segments, &transcript.metadata.chapters, 0)
+        );
+
+        assert!(matches!(
+            try_config_with("{}", &["--txt-style", "prose"]),
+            Err(Error::InvalidTxtStyle(_))
+        ));
+    }
+
+    #[test]
+    fn test_config_json_compat() {
+        let transcript = sample_transcript();
+
+        let config = config_with("{}", &["-f", "json"]);
+        assert_eq!(
+            format_output(&config, "json"),
+            json::format_transcript(&transcript.segments, &transcript.metadata)
+        );
+
+        let config = config_with("{}", &["-f", "json", "--json-compat", "openai"]);
+        assert_eq!(
+            format_output(&config, "json"),
+            json::format_openai(&transcript.segments, &transcript.metadata)
+        );
+
+        assert!(matches!(
+            try_config_with("{}", &["--json-compat", "aws"]),
+            Err(Error::InvalidJsonCompat(_))
+        ));
     }
 
     #[test]
@@ -469,9 +1188,34 @@ mod tests {
 
     #[test]
     fn test_output_format_extension() {
-        assert_eq!(OutputFormat::Txt.extension(), "txt");
-        assert_eq!(OutputFormat::Json.extension(), "json");
-        assert_eq!(OutputFormat::Srt.extension(), "srt");
-        assert_eq!(OutputFormat::Vtt.extension(), "vtt");
+        let formats =
+            parse_formats("txt,json,srt,vtt,md,html,ass,ttml,csv,tsv,lrc,sbv", false).unwrap();
+        for format in &formats {
+            assert_eq!(format.extension(), format.name());
+        }
+        assert_eq!(formats.len(), 12);
+    }
+
+    #[test]
+    fn test_default_format_options_match_cli_defaults() {
+        let config = config_with("{}", &["--format", "txt,json,ass,lrc,md,csv,tsv"]);
+
+        // The CLI defaults configure each formatter like its Default
+        let transcript = sample_transcript();
+        let context = FormatContext::default();
+        for format in &config.formats {
+            let default = Formatters::default().get(format.name()).unwrap();
+            assert_eq!(
+                format.format(&transcript, &context),
+                default.format(&transcript, &context),
+                "{}",
+                format.name()
+            );
+        }
+        assert_eq!(
+            csv::DEFAULT_COLUMNS,
+            parse_csv_columns(csv::DEFAULT_CSV_COLUMNS).unwrap()
+        );
+        assert_eq!(txt::TxtOptions::default().width, txt::DEFAULT_TXT_WIDTH);
     }
 }
@@ -0,0 +1,339 @@
+// Voxtus - Transcribe YouTube videos and local media files to text
+// Copyright (C) 2024 Johan Thorén <johan@thoren.xyz>
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Management of the external yt-dlp and ffmpeg binaries.
+//!
+//! By default voxtus downloads its own copies into the libs directory
+//! (bundled). Alternatively the binaries on PATH are used (system). The
+//! choice is persisted in the config file and applies to every ffmpeg,
+//! ffprobe and yt-dlp invocation.
+
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// Where the yt-dlp and ffmpeg binaries come from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BinarySource {
+    /// Copies downloaded by voxtus into the libs directory.
+    #[default]
+    Bundled,
+    /// The binaries found on PATH.
+    System,
+}
+
+impl std::str::FromStr for BinarySource {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "bundled" => Ok(Self::Bundled),
+            "system" => Ok(Self::System),
+            _ => Err(Error::DepsError(format!(
+                "unknown binary source '{}' (expected bundled or system)",
+                s
+            ))),
+        }
+    }
+}
+
+impl BinarySource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Bundled => "bundled",
+            Self::System => "system",
+        }
+    }
+}
+
+/// Global flag selecting the binaries on PATH.
+static USE_SYSTEM: AtomicBool = AtomicBool::new(false);
+
+/// Select where binaries are taken from for the rest of the process.
+pub fn set_source(source: BinarySource) {
+    USE_SYSTEM.store(source == BinarySource::System, Ordering::SeqCst);
+}
+
+/// The currently selected binary source.
+pub fn source() -> BinarySource {
+    if USE_SYSTEM.load(Ordering::SeqCst) {
+        BinarySource::System
+    } else {
+        BinarySource::Bundled
+    }
+}
+
+/// Platform file name of an executable.
+fn exe_name(name: &str) -> String {
+    if cfg!(windows) {
+        format!("{}.exe", name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// Directory where the bundled binaries are stored.
+pub fn libs_dir() -> Result<PathBuf> {
+    let dir = dirs::data_local_dir()
+        .ok_or_else(|| Error::DepsError("Could not determine local data directory".into()))?
+        .join("voxtus")
+        .join("libs");
+
+    if !dir.exists() {
+        std::fs::create_dir_all(&dir)?;
+    }
+
+    Ok(dir)
+}
+
+/// Paths of the bundled yt-dlp and ffmpeg binaries in a libs directory.
+pub fn bundled_paths(libs_dir: &Path) -> (PathBuf, PathBuf) {
+    (
+        libs_dir.join(exe_name("yt-dlp")),
+        libs_dir.join(exe_name("ffmpeg")),
+    )
+}
+
+/// Find an executable in a PATH-style list of directories.
+pub fn find_in_path(name: &str, path_var: &OsStr) -> Option<PathBuf> {
+    let file = exe_name(name);
+    std::env::split_paths(path_var)
+        .map(|dir| dir.join(&file))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Find an executable on PATH.
+pub fn find_on_path(name: &str) -> Option<PathBuf> {
+    find_in_path(name, &std::env::var_os("PATH")?)
+}
+
+/// The ffmpeg binary to run.
+///
+/// The bundled copy is used when selected and installed; otherwise ffmpeg
+/// is looked up on PATH.
+pub fn ffmpeg_path() -> PathBuf {
+    if source() == BinarySource::Bundled
+        && let Ok(dir) = libs_dir()
+    {
+        let (_, ffmpeg) = bundled_paths(&dir);
+        if ffmpeg.is_file() {
+            return ffmpeg;
+        }
+    }
+    find_on_path("ffmpeg").unwrap_or_else(|| PathBuf::from(exe_name("ffmpeg")))
+}
+
+/// The ffprobe binary to run: the one next to ffmpeg, or from PATH.
+pub fn ffprobe_path() -> PathBuf {
+    let sibling = ffmpeg_path().with_file_name(exe_name("ffprobe"));
+    if sibling.is_file() {
+        sibling
+    } else {
+        PathBuf::from(exe_name("ffprobe"))
+    }
+}
+
+/// The yt-dlp binary to run, which may not be installed yet when bundled.
+pub fn yt_dlp_path() -> Result<PathBuf> {
+    match source() {
+        BinarySource::System => {
+            find_on_path("yt-dlp").ok_or_else(|| Error::BinaryNotFound("yt-dlp".to_string()))
+        }
+        BinarySource::Bundled => Ok(bundled_paths(&libs_dir()?).0),
+    }
+}
+
+/// Get the yt-dlp binary, downloading the bundled binaries if needed.
+#[cfg(feature = "youtube")]
+pub async fn ensure_yt_dlp(work_dir: &Path) -> Result<PathBuf> {
+    let path = yt_dlp_path()?;
+    if source() == BinarySource::System {
+        return Ok(path);
+    }
+
+    let libs_dir = libs_dir()?;
+    let (yt_dlp, ffmpeg) = bundled_paths(&libs_dir);
+    if !yt_dlp.exists() || !ffmpeg.exists() {
+        log::info!("Downloading yt-dlp and ffmpeg to {}", libs_dir.display());
+        yt_dlp::Youtube::with_new_binaries(libs_dir, work_dir.to_path_buf())
+            .await
+            .map_err(|e| Error::DepsError(format!("Failed to install binaries: {}", e)))?;
+    }
+
+    Ok(path)
+}
+
+/// Synchronous wrapper for ensure_yt_dlp.
+#[cfg(feature = "youtube")]
+pub fn ensure_yt_dlp_sync(work_dir: &Path) -> Result<PathBuf> {
+    let rt = tokio::runtime::Runtime::new()
+        .map_err(|e| Error::DepsError(format!("Failed to create runtime: {}", e)))?;
+
+    rt.block_on(ensure_yt_dlp(work_dir))
+}
+
+#[cfg(not(feature = "youtube"))]
+pub fn ensure_yt_dlp_sync(_work_dir: &Path) -> Result<PathBuf> {
+    Err(Error::DepsError(
+        "Installing yt-dlp requires the 'youtube' feature".into(),
+    ))
+}
+
+/// Extract the version from `ffmpeg -version` or `yt-dlp --version` output.
+///
+/// # Examples
+///
+/// ```
+/// use voxtus::deps::parse_version;
+///
+/// assert_eq!(parse_version("2024.10.07\n").as_deref(), Some("2024.10.07"));
+/// assert_eq!(
+///     parse_version("ffmpeg version 7.0.1 Copyright (c) 2000-2024").as_deref(),
+///     Some("7.0.1")
+/// );
+/// assert_eq!(parse_version(""), None);
+/// ```
+pub fn parse_version(output: &str) -> Option<String> {
+    let line = output.lines().next()?.trim();
+    let version = match line.split_once(" version ") {
+        Some((_, rest)) => rest.split_whitespace().next()?,
+        None => line,
+    };
+    (!version.is_empty()).then(|| version.to_string())
+}
+
+/// Run a binary's version flag and return the reported version.
+pub fn binary_version(path: &Path, flag: &str) -> Option<String> {
+    let output = Command::new(path).arg(flag).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// An external binary and its installed version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinaryStatus {
+    pub name: &'static str,
+    /// Path of the binary, or `None` when it is not installed.
+    pub path: Option<PathBuf>,
+    pub version: Option<String>,
+}
+
+/// Report the binaries that the current source resolves to.
+pub fn status() -> Vec<BinaryStatus> {
+    let yt_dlp = yt_dlp_path().ok().filter(|p| p.is_file());
+    let ffmpeg = Some(ffmpeg_path()).filter(|p| p.is_file());
+
+    vec![
+        BinaryStatus {
+            name: "yt-dlp",
+            version: yt_dlp
+                .as_deref()
+                .and_then(|p| binary_version(p, "--version")),
+            path: yt_dlp,
+        },
+        BinaryStatus {
+            name: "ffmpeg",
+            version: ffmpeg
+                .as_deref()
+                .and_then(|p| binary_version(p, "-version")),
+            path: ffmpeg,
+        },
+    ]
+}
+
+/// Update the bundled yt-dlp to the latest release.
+///
+/// Installs the bundled binaries first if needed. Binaries on PATH are
+/// left to the system's package manager. Returns the new version.
+pub fn update_yt_dlp() -> Result<String> {
+    if source() == BinarySource::System {
+        return Err(Error::DepsError(
+            "yt-dlp on PATH is managed outside voxtus; update it with your package manager".into(),
+        ));
+    }
+
+    let path = ensure_yt_dlp_sync(&std::env::temp_dir())?;
+    let output = Command::new(&path)
+        .arg("--update")
+        .output()
+        .map_err(|e| Error::DepsError(format!("Failed to run yt-dlp: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::DepsError(format!(
+            "yt-dlp update failed: {}",
+            stderr.lines().last().unwrap_or("unknown error")
+        )));
+    }
+
+    binary_version(&path, "--version")
+        .ok_or_else(|| Error::DepsError("Could not read yt-dlp version".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_source_from_str() {
+        assert_eq!(
+            "system".parse::<BinarySource>().unwrap(),
+            BinarySource::System
+        );
+        assert_eq!(
+            "Bundled".parse::<BinarySource>().unwrap(),
+            BinarySource::Bundled
+        );
+        assert!(matches!(
+            "path".parse::<BinarySource>(),
+            Err(Error::DepsError(_))
+        ));
+    }
+
+    #[test]
+    fn test_binary_source_serde() {
+        assert_eq!(
+            serde_json::to_string(&BinarySource::System).unwrap(),
+            "\"system\""
+        );
+        assert_eq!(
+            serde_json::from_str::<BinarySource>("\"bundled\"").unwrap(),
+            BinarySource::Bundled
+        );
+    }
+
+    #[test]
+    fn test_libs_dir() {
+        let dir = libs_dir().unwrap();
+        assert!(dir.ends_with("voxtus/libs") || dir.ends_with("voxtus\\libs"));
+    }
+
+    #[test]
+    fn test_find_in_path() {
+        let empty = tempfile::tempdir().unwrap();
+        let bin = tempfile::tempdir().unwrap();
+        let tool = bin.path().join(exe_name("tool"));
+        std::fs::write(&tool, "").unwrap();
+
+        let path_var = std::env::join_paths([empty.path(), bin.path()]).unwrap();
+        assert_eq!(find_in_path("tool", &path_var), Some(tool));
+        assert_eq!(find_in_path("missing", &path_var), None);
+    }
+
+    #[test]
+    fn test_parse_version_yt_dlp_multiline() {
+        assert_eq!(
+            parse_version("2025.01.15\nextra\n").as_deref(),
+            Some("2025.01.15")
+        );
+    }
+}
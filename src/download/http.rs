@@ -0,0 +1,275 @@
+// Voxtus - Transcribe YouTube videos and local media files to text
+// Copyright (C) 2024 Johan Thorén <johan@thoren.xyz>
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Plain HTTP download of direct media file links.
+//!
+//! Used for podcast enclosures and other URLs that point straight at an
+//! audio or video file, where running yt-dlp would be wasted effort.
+
+use std::path::{Path, PathBuf};
+
+use super::{Downloader, VideoInfo};
+use crate::error::{Error, Result};
+
+/// File extensions treated as direct media links.
+pub const MEDIA_EXTENSIONS: &[&str] = &[
+    "mp3", "m4a", "aac", "ogg", "oga", "opus", "flac", "wav", "mp4", "m4v", "mov", "mkv", "webm",
+];
+
+/// Downloader for direct links to media files.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HttpDownloader;
+
+impl HttpDownloader {
+    pub const NAME: &'static str = "http";
+}
+
+impl Downloader for HttpDownloader {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn supports(&self, url: &str) -> bool {
+        media_extension(url).is_some()
+    }
+
+    fn download_audio(&self, url: &str, output_dir: &Path) -> Result<(PathBuf, VideoInfo)> {
+        let ext = media_extension(url).unwrap_or("bin");
+        let path = output_dir.join(format!("download.{}", ext));
+        download_file_sync(url, &path)?;
+
+        let title = url_file_stem(url).unwrap_or_else(|| "audio".to_string());
+        let info = crate::audio::probe_media_info(&path, &title).unwrap_or_else(|e| {
+            log::debug!("Could not read media tags: {}", e);
+            VideoInfo {
+                title,
+                ..Default::default()
+            }
+        });
+
+        Ok((path, info))
+    }
+}
+
+/// The path component of a URL, without query string or fragment.
+fn url_path(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let path = rest.find('/').map_or("", |i| &rest[i..]);
+    path.split(['?', '#']).next().unwrap_or(path)
+}
+
+/// Get the media extension of a URL, if it points at a known media file.
+///
+/// # Examples
+///
+/// ```
+/// use voxtus::download::http::media_extension;
+///
+/// assert_eq!(media_extension("https://cdn.example.com/ep1.MP3?x=1"), Some("mp3"));
+/// assert_eq!(media_extension("https://example.com/watch?v=abc.mp3"), None);
+/// ```
+pub fn media_extension(url: &str) -> Option<&'static str> {
+    let file = url_path(url).rsplit('/').next()?;
+    let (_, ext) = file.rsplit_once('.')?;
+    MEDIA_EXTENSIONS
+        .iter()
+        .find(|m| m.eq_ignore_ascii_case(ext))
+        .copied()
+}
+
+/// Decode `%XX` escapes in a URL component.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                decoded.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                decoded.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// File name of a URL without its extension, used as a fallback title.
+pub fn url_file_stem(url: &str) -> Option<String> {
+    let file = url_path(url).rsplit('/').next()?;
+    let stem = file.rsplit_once('.').map_or(file, |(stem, _)| stem);
+    let stem = percent_decode(stem);
+    (!stem.is_empty()).then_some(stem)
+}
+
+/// Download a URL to a file, streaming the body to disk.
+#[cfg(feature = "http")]
+pub async fn download_file(url: &str, path: &Path) -> Result<()> {
+    use tokio::io::AsyncWriteExt;
+
+    let mut response = reqwest::get(url)
+        .await
+        .map_err(|e| Error::DownloadFailed(format!("Failed to download {}: {}", url, e)))?;
+
+    if !response.status().is_success() {
+        return Err(Error::DownloadFailed(format!(
+            "Failed to download {}: HTTP {}",
+            url,
+            response.status()
+        )));
+    }
+
+    let mut file = tokio::fs::File::create(path).await?;
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| Error::DownloadFailed(format!("Failed to read response: {}", e)))?
+    {
+        file.write_all(&chunk).await?;
+    }
+    file.flush().await?;
+
+    Ok(())
+}
+
+/// Synchronous wrapper for download_file.
+#[cfg(feature = "http")]
+pub fn download_file_sync(url: &str, path: &Path) -> Result<()> {
+    let rt = tokio::runtime::Runtime::new()
+        .map_err(|e| Error::DownloadFailed(format!("Failed to create runtime: {}", e)))?;
+
+    rt.block_on(download_file(url, path))
+}
+
+#[cfg(not(feature = "http"))]
+pub fn download_file_sync(_url: &str, _path: &Path) -> Result<()> {
+    Err(Error::DownloadFailed(
+        "HTTP download requires the 'http' feature".into(),
+    ))
+}
+
+/// Download a URL as text, e.g. a podcast feed.
+#[cfg(feature = "http")]
+pub async fn download_text(url: &str) -> Result<String> {
+    let response = reqwest::get(url)
+        .await
+        .map_err(|e| Error::DownloadFailed(format!("Failed to download {}: {}", url, e)))?;
+
+    if !response.status().is_success() {
+        return Err(Error::DownloadFailed(format!(
+            "Failed to download {}: HTTP {}",
+            url,
+            response.status()
+        )));
+    }
+
+    response
+        .text()
+        .await
+        .map_err(|e| Error::DownloadFailed(format!("Failed to read response: {}", e)))
+}
+
+/// Synchronous wrapper for download_text.
+#[cfg(feature = "http")]
+pub fn download_text_sync(url: &str) -> Result<String> {
+    let rt = tokio::runtime::Runtime::new()
+        .map_err(|e| Error::DownloadFailed(format!("Failed to create runtime: {}", e)))?;
+
+    rt.block_on(download_text(url))
+}
+
+#[cfg(not(feature = "http"))]
+pub fn download_text_sync(_url: &str) -> Result<String> {
+    Err(Error::DownloadFailed(
+        "HTTP download requires the 'http' feature".into(),
+    ))
+}
+
+/// A one-shot local HTTP server for tests.
+#[cfg(all(test, feature = "http"))]
+pub(crate) mod test_server {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread::JoinHandle;
+
+    /// Serve `body` to a single request, returning the server's base URL.
+    pub fn serve_once(body: &'static [u8]) -> (String, JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request).unwrap();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            )
+            .unwrap();
+            stream.write_all(body).unwrap();
+        });
+        (format!("http://{}", addr), server)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_media_extension() {
+        assert_eq!(media_extension("https://a.com/show/ep1.mp3"), Some("mp3"));
+        assert_eq!(media_extension("http://a.com/x.opus#t=10"), Some("opus"));
+        assert_eq!(
+            media_extension("https://a.com/x.m4a?token=a.b"),
+            Some("m4a")
+        );
+        assert_eq!(media_extension("https://a.com/feed.xml"), None);
+        assert_eq!(media_extension("https://a.com/"), None);
+        assert_eq!(media_extension("https://vimeo.com/12345"), None);
+        assert_eq!(media_extension("https://example.mp3"), None);
+    }
+
+    #[test]
+    fn test_url_file_stem() {
+        assert_eq!(
+            url_file_stem("https://a.com/show/Episode%2042.mp3?x=1"),
+            Some("Episode 42".to_string())
+        );
+        assert_eq!(url_file_stem("https://a.com/"), None);
+    }
+
+    #[test]
+    fn test_percent_decode_invalid_escape() {
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("a%zzb"), "a%zzb");
+        assert_eq!(percent_decode("caf%C3%A9"), "café");
+    }
+
+    #[test]
+    fn test_supports() {
+        assert!(HttpDownloader.supports("https://cdn.example.com/ep.mp3"));
+        assert!(!HttpDownloader.supports("https://www.youtube.com/watch?v=abc"));
+    }
+
+    #[test]
+    #[cfg(feature = "http")]
+    fn test_download_file_from_local_server() {
+        let (url, server) = test_server::serve_once(b"ID3fake-audio");
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ep.mp3");
+        download_file_sync(&format!("{}/ep.mp3", url), &path).unwrap();
+        server.join().unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), b"ID3fake-audio");
+    }
+}
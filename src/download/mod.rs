@@ -3,7 +3,220 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 //! Download functionality for remote media.
+//!
+//! Remote URLs are handled by a [`Downloader`] backend picked from a
+//! [`Downloaders`] registry: direct media links go over plain HTTP, and
+//! everything else goes through yt-dlp, which supports YouTube, Vimeo,
+//! SoundCloud, Twitch and many more sites.
 
+pub mod http;
 pub mod youtube;
 
-pub use youtube::{VideoInfo, download_audio_sync};
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+
+pub use http::HttpDownloader;
+pub use youtube::{
+    PlaylistEntry, PlaylistInfo, SubtitleTrack, VideoInfo, YtDlpDownloader, YtDlpOptions,
+    download_subtitles_sync, fetch_playlist_sync, fetch_video_info_sync, is_playlist_url,
+    select_subtitle_track,
+};
+
+/// A backend that can fetch the audio for a URL.
+pub trait Downloader: Send + Sync {
+    /// Short name used to select the backend, e.g. `yt-dlp`.
+    fn name(&self) -> &'static str;
+
+    /// Whether this backend can handle the URL.
+    fn supports(&self, url: &str) -> bool;
+
+    /// Download the audio for the URL into `output_dir`.
+    ///
+    /// Returns the path of the downloaded file (in any format ffmpeg can
+    /// read) and whatever metadata the backend knows about it.
+    fn download_audio(&self, url: &str, output_dir: &Path) -> Result<(PathBuf, VideoInfo)>;
+
+    /// Download the audio for a URL whose metadata was already fetched,
+    /// such as while looking for subtitles.
+    ///
+    /// Backends that fetch metadata separately can skip doing it again; the
+    /// default ignores `info` and calls [`Downloader::download_audio`].
+    fn download_audio_with_info(
+        &self,
+        url: &str,
+        output_dir: &Path,
+        info: VideoInfo,
+    ) -> Result<(PathBuf, VideoInfo)> {
+        let _ = info;
+        self.download_audio(url, output_dir)
+    }
+}
+
+/// Ordered registry of downloader backends.
+///
+/// For automatic selection, the first backend that supports a URL wins.
+pub struct Downloaders {
+    backends: Vec<Box<dyn Downloader>>,
+}
+
+impl Default for Downloaders {
+    /// Direct HTTP for media file links, then yt-dlp for everything else.
+    fn default() -> Self {
+        Self {
+            backends: vec![
+                Box::new(HttpDownloader),
+                Box::new(YtDlpDownloader::default()),
+            ],
+        }
+    }
+}
+
+impl Downloaders {
+    /// The default backends, with options passed through to yt-dlp.
+    pub fn with_yt_dlp_options(options: YtDlpOptions) -> Self {
+        Self {
+            backends: vec![
+                Box::new(HttpDownloader),
+                Box::new(YtDlpDownloader { options }),
+            ],
+        }
+    }
+
+    /// Create an empty registry.
+    pub fn empty() -> Self {
+        Self {
+            backends: Vec::new(),
+        }
+    }
+
+    /// Add a backend, taking precedence over the ones already registered.
+    pub fn register(&mut self, downloader: Box<dyn Downloader>) {
+        self.backends.insert(0, downloader);
+    }
+
+    /// Names of the registered backends, in selection order.
+    pub fn names(&self) -> Vec<&'static str> {
+        self.backends.iter().map(|d| d.name()).collect()
+    }
+
+    /// Look up a backend by name.
+    pub fn get(&self, name: &str) -> Option<&dyn Downloader> {
+        self.backends
+            .iter()
+            .find(|d| d.name().eq_ignore_ascii_case(name))
+            .map(|d| d.as_ref())
+    }
+
+    /// Find the first backend that supports the URL.
+    pub fn for_url(&self, url: &str) -> Option<&dyn Downloader> {
+        self.backends
+            .iter()
+            .find(|d| d.supports(url))
+            .map(|d| d.as_ref())
+    }
+
+    /// Pick the backend for a URL, by name if one is given.
+    pub fn select(&self, url: &str, name: Option<&str>) -> Result<&dyn Downloader> {
+        match name {
+            Some(name) => self
+                .get(name)
+                .ok_or_else(|| Error::InvalidDownloader(name.to_string())),
+            None => self
+                .for_url(url)
+                .ok_or_else(|| Error::InvalidUrl(url.to_string())),
+        }
+    }
+}
+
+/// Download audio from a URL with the first backend that supports it.
+pub fn download_audio_sync(url: &str, output_dir: &Path) -> Result<(PathBuf, VideoInfo)> {
+    Downloaders::default()
+        .select(url, None)?
+        .download_audio(url, output_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeDownloader;
+
+    impl Downloader for FakeDownloader {
+        fn name(&self) -> &'static str {
+            "fake"
+        }
+
+        fn supports(&self, url: &str) -> bool {
+            url.starts_with("https://fake.example/")
+        }
+
+        fn download_audio(&self, _url: &str, output_dir: &Path) -> Result<(PathBuf, VideoInfo)> {
+            let info = VideoInfo {
+                title: "Fake".to_string(),
+                ..Default::default()
+            };
+            Ok((output_dir.join("fake.mp3"), info))
+        }
+    }
+
+    #[test]
+    fn test_default_selection_by_url() {
+        let downloaders = Downloaders::default();
+        assert_eq!(downloaders.names(), vec!["http", "yt-dlp"]);
+
+        let select = |url| downloaders.select(url, None).unwrap().name();
+        assert_eq!(select("https://cdn.example.com/podcast/ep1.mp3"), "http");
+        assert_eq!(select("https://www.youtube.com/watch?v=abc"), "yt-dlp");
+        assert_eq!(select("https://vimeo.com/12345"), "yt-dlp");
+        assert_eq!(select("https://soundcloud.com/artist/track"), "yt-dlp");
+    }
+
+    #[test]
+    fn test_select_by_name() {
+        let downloaders = Downloaders::default();
+        let url = "https://cdn.example.com/ep1.mp3";
+        assert_eq!(
+            downloaders.select(url, Some("YT-DLP")).unwrap().name(),
+            "yt-dlp"
+        );
+        assert!(matches!(
+            downloaders.select(url, Some("ftp")),
+            Err(Error::InvalidDownloader(_))
+        ));
+    }
+
+    #[test]
+    fn test_register_takes_precedence() {
+        let mut downloaders = Downloaders::default();
+        downloaders.register(Box::new(FakeDownloader));
+
+        let url = "https://fake.example/track.mp3";
+        let downloader = downloaders.select(url, None).unwrap();
+        assert_eq!(downloader.name(), "fake");
+
+        let dir = tempfile::tempdir().unwrap();
+        let (path, info) = downloader.download_audio(url, dir.path()).unwrap();
+        assert_eq!(path, dir.path().join("fake.mp3"));
+        assert_eq!(info.title, "Fake");
+
+        // Backends without a separate metadata step ignore known info
+        let known = VideoInfo {
+            title: "Known".to_string(),
+            ..Default::default()
+        };
+        let (_, info) = downloader
+            .download_audio_with_info(url, dir.path(), known)
+            .unwrap();
+        assert_eq!(info.title, "Fake");
+    }
+
+    #[test]
+    fn test_empty_registry_rejects_urls() {
+        let downloaders = Downloaders::empty();
+        assert!(matches!(
+            downloaders.select("https://example.com/a.mp3", None),
+            Err(Error::InvalidUrl(_))
+        ));
+    }
+}
@@ -3,101 +3,770 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 //! YouTube download functionality using yt-dlp.
+//!
+//! Despite the module name, everything here works for any site yt-dlp
+//! supports; only playlist and channel detection is YouTube specific.
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
+use serde::{Deserialize, Serialize};
+
+use super::Downloader;
 use crate::error::{Error, Result};
+use crate::formats::Chapter;
 
 /// Video metadata from yt-dlp.
-#[derive(Debug, Clone)]
+///
+/// For local files the same structure is filled from ffprobe tags, so
+/// everything but the title is optional.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct VideoInfo {
+    pub id: Option<String>,
     pub title: String,
+    pub uploader: Option<String>,
+    pub channel: Option<String>,
+    /// Upload date as `YYYY-MM-DD`.
+    pub upload_date: Option<String>,
+    pub description: Option<String>,
+    /// Duration in seconds.
+    pub duration: Option<f64>,
+    pub tags: Vec<String>,
+    pub chapters: Vec<Chapter>,
+    /// Spoken language of the video, when the site reports it.
+    pub language: Option<String>,
+    /// Languages with creator-uploaded subtitles.
+    pub subtitles: Vec<String>,
+    /// Languages with automatically generated captions.
+    pub automatic_captions: Vec<String>,
 }
 
-/// Directory where yt-dlp and ffmpeg binaries are stored.
-#[cfg(feature = "youtube")]
-fn get_libs_dir() -> Result<PathBuf> {
-    let dir = dirs::data_local_dir()
-        .ok_or_else(|| Error::DownloadFailed("Could not determine local data directory".into()))?
-        .join("voxtus")
-        .join("libs");
+/// A subtitle track to fetch instead of transcribing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubtitleTrack {
+    pub language: String,
+    /// Whether this is an automatically generated caption track.
+    pub automatic: bool,
+}
+
+/// Raw `--dump-single-json` output for a single video from yt-dlp.
+#[derive(Debug, Deserialize)]
+struct RawVideoInfo {
+    id: Option<String>,
+    title: Option<String>,
+    uploader: Option<String>,
+    channel: Option<String>,
+    upload_date: Option<String>,
+    description: Option<String>,
+    duration: Option<f64>,
+    tags: Option<Vec<String>>,
+    chapters: Option<Vec<RawChapter>>,
+    language: Option<String>,
+    subtitles: Option<HashMap<String, serde_json::Value>>,
+    automatic_captions: Option<HashMap<String, serde_json::Value>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawChapter {
+    start_time: f64,
+    end_time: f64,
+    title: Option<String>,
+}
+
+/// Options passed through to every yt-dlp invocation.
+///
+/// Read from the `yt_dlp` section of the config file and overridden by
+/// command-line flags.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct YtDlpOptions {
+    /// Netscape-format cookies file (`--cookies`).
+    pub cookies: Option<PathBuf>,
+    /// Browser to import cookies from, e.g. `firefox` (`--cookies-from-browser`).
+    pub cookies_from_browser: Option<String>,
+    /// Proxy URL (`--proxy`).
+    pub proxy: Option<String>,
+    /// Maximum download rate, e.g. `2M` (`--limit-rate`).
+    pub limit_rate: Option<String>,
+    /// Number of retries (`--retries`).
+    pub retries: Option<u32>,
+    /// Preferred audio container/codec, e.g. `m4a` or `opus`.
+    pub audio_format: Option<String>,
+    /// Maximum audio bitrate in kbit/s.
+    pub audio_quality: Option<u32>,
+    /// Extra arguments appended verbatim.
+    pub extra_args: Vec<String>,
+}
+
+impl YtDlpOptions {
+    /// Overlay `other` on top of these options.
+    ///
+    /// Values set in `other` win; extra arguments from both are kept.
+    pub fn merge(self, other: YtDlpOptions) -> Self {
+        Self {
+            cookies: other.cookies.or(self.cookies),
+            cookies_from_browser: other.cookies_from_browser.or(self.cookies_from_browser),
+            proxy: other.proxy.or(self.proxy),
+            limit_rate: other.limit_rate.or(self.limit_rate),
+            retries: other.retries.or(self.retries),
+            audio_format: other.audio_format.or(self.audio_format),
+            audio_quality: other.audio_quality.or(self.audio_quality),
+            extra_args: self
+                .extra_args
+                .into_iter()
+                .chain(other.extra_args)
+                .collect(),
+        }
+    }
+
+    /// Build the yt-dlp format selector for the audio download.
+    ///
+    /// Falls back to any audio stream, then to the best combined stream, so
+    /// a preference never makes a download fail.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use voxtus::download::YtDlpOptions;
+    ///
+    /// assert_eq!(YtDlpOptions::default().format_selector(), "bestaudio/best");
+    ///
+    /// let options = YtDlpOptions {
+    ///     audio_format: Some("m4a".to_string()),
+    ///     audio_quality: Some(128),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(
+    ///     options.format_selector(),
+    ///     "bestaudio[ext=m4a][abr<=128]/bestaudio[ext=m4a]/bestaudio/best"
+    /// );
+    /// ```
+    pub fn format_selector(&self) -> String {
+        let ext = self
+            .audio_format
+            .as_ref()
+            .map(|f| format!("[ext={}]", f))
+            .unwrap_or_default();
+        let abr = self
+            .audio_quality
+            .map(|q| format!("[abr<={}]", q))
+            .unwrap_or_default();
 
-    if !dir.exists() {
-        std::fs::create_dir_all(&dir)?;
+        let mut choices = Vec::new();
+        if !abr.is_empty() {
+            choices.push(format!("bestaudio{}{}", ext, abr));
+        }
+        if !ext.is_empty() {
+            choices.push(format!("bestaudio{}", ext));
+        }
+        choices.push("bestaudio/best".to_string());
+        choices.join("/")
     }
 
-    Ok(dir)
+    /// Command-line arguments for the network and authentication options.
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(cookies) = &self.cookies {
+            args.extend([
+                "--cookies".to_string(),
+                cookies.to_string_lossy().to_string(),
+            ]);
+        }
+        if let Some(browser) = &self.cookies_from_browser {
+            args.extend(["--cookies-from-browser".to_string(), browser.clone()]);
+        }
+        if let Some(proxy) = &self.proxy {
+            args.extend(["--proxy".to_string(), proxy.clone()]);
+        }
+        if let Some(rate) = &self.limit_rate {
+            args.extend(["--limit-rate".to_string(), rate.clone()]);
+        }
+        if let Some(retries) = self.retries {
+            args.extend(["--retries".to_string(), retries.to_string()]);
+        }
+        args.extend(self.extra_args.iter().cloned());
+        args
+    }
 }
 
-/// Download audio from URL. Returns m4a path and video info.
-#[cfg(feature = "youtube")]
-pub async fn download_audio(url: &str, output_dir: &Path) -> Result<(PathBuf, VideoInfo)> {
-    use yt_dlp::Youtube;
+/// Downloader backend that uses yt-dlp.
+#[derive(Debug, Clone, Default)]
+pub struct YtDlpDownloader {
+    pub options: YtDlpOptions,
+}
+
+impl YtDlpDownloader {
+    pub const NAME: &'static str = "yt-dlp";
+}
+
+impl Downloader for YtDlpDownloader {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    /// yt-dlp has extractors for most video and audio sites, and a generic
+    /// extractor for pages with embedded media, so any web URL is accepted.
+    fn supports(&self, url: &str) -> bool {
+        crate::config::is_url(url)
+    }
+
+    fn download_audio(&self, url: &str, output_dir: &Path) -> Result<(PathBuf, VideoInfo)> {
+        download_audio_sync(url, output_dir, &self.options, None)
+    }
+
+    fn download_audio_with_info(
+        &self,
+        url: &str,
+        output_dir: &Path,
+        info: VideoInfo,
+    ) -> Result<(PathBuf, VideoInfo)> {
+        download_audio_sync(url, output_dir, &self.options, Some(info))
+    }
+}
+
+/// A single video in a playlist or channel listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlaylistEntry {
+    /// 1-based position in the playlist.
+    pub index: usize,
+    pub id: String,
+    pub title: String,
+    pub url: String,
+}
+
+/// Playlist or channel metadata with its flattened entries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlaylistInfo {
+    pub id: String,
+    pub title: String,
+    pub entries: Vec<PlaylistEntry>,
+}
+
+/// Raw `--flat-playlist --dump-single-json` output from yt-dlp.
+#[derive(Debug, Deserialize)]
+struct RawPlaylist {
+    id: Option<String>,
+    title: Option<String>,
+    #[serde(default)]
+    entries: Vec<RawPlaylistEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawPlaylistEntry {
+    id: Option<String>,
+    title: Option<String>,
+    url: Option<String>,
+}
 
-    let libs_dir = get_libs_dir()?;
-    let yt_dlp_path = libs_dir.join(if cfg!(windows) {
-        "yt-dlp.exe"
+/// Check if a URL points at a YouTube playlist or channel rather than a single video.
+///
+/// Watch URLs that merely carry a `list=` parameter are treated as single videos.
+///
+/// # Examples
+///
+/// ```
+/// use voxtus::download::youtube::is_playlist_url;
+///
+/// assert!(is_playlist_url("https://www.youtube.com/playlist?list=PL123"));
+/// assert!(is_playlist_url("https://www.youtube.com/@somechannel"));
+/// assert!(!is_playlist_url("https://www.youtube.com/watch?v=abc&list=PL123"));
+/// ```
+pub fn is_playlist_url(url: &str) -> bool {
+    let Some(rest) = url.split_once("://").map(|(_, rest)| rest) else {
+        return false;
+    };
+    let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+    if host != "youtube.com" && !host.ends_with(".youtube.com") {
+        return false;
+    }
+
+    path.starts_with("playlist")
+        || path.starts_with('@')
+        || path.starts_with("channel/")
+        || path.starts_with("c/")
+        || path.starts_with("user/")
+}
+
+/// Extract the video ID from a YouTube video URL without a network request.
+///
+/// # Examples
+///
+/// ```
+/// use voxtus::download::youtube::youtube_video_id;
+///
+/// assert_eq!(youtube_video_id("https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=1").as_deref(), Some("dQw4w9WgXcQ"));
+/// assert_eq!(youtube_video_id("https://youtu.be/dQw4w9WgXcQ").as_deref(), Some("dQw4w9WgXcQ"));
+/// assert_eq!(youtube_video_id("https://vimeo.com/12345"), None);
+/// ```
+pub fn youtube_video_id(url: &str) -> Option<String> {
+    let rest = url.split_once("://")?.1;
+    let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let (path, query) = path.split_once('?').unwrap_or((path, ""));
+    let path = path.split('#').next().unwrap_or(path);
+
+    let id = if host == "youtu.be" {
+        path.split('/').next()
+    } else if host == "youtube.com" || host.ends_with(".youtube.com") {
+        match path.split_once('/') {
+            Some(("shorts" | "live" | "embed" | "v", id)) => id.split('/').next(),
+            _ if path == "watch" => query
+                .split(['&', '#'])
+                .find_map(|param| param.strip_prefix("v=")),
+            _ => None,
+        }
     } else {
-        "yt-dlp"
-    });
-    let ffmpeg_path = libs_dir.join(if cfg!(windows) {
-        "ffmpeg.exe"
+        None
+    }?;
+
+    let valid = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    valid.then(|| id.to_string())
+}
+
+/// Point bare channel URLs at the channel's video tab.
+///
+/// yt-dlp lists the channel tabs (videos, shorts, live) as nested playlists
+/// for a bare channel URL, so we select the videos tab explicitly.
+pub fn normalize_channel_url(url: &str) -> String {
+    let trimmed = url.trim_end_matches('/');
+    let Some((_, path)) = trimmed.split_once("youtube.com/") else {
+        return url.to_string();
+    };
+
+    let segments: Vec<&str> = path.split('/').collect();
+    let is_bare_channel = match segments.as_slice() {
+        [handle] => handle.starts_with('@'),
+        [kind, _] => matches!(*kind, "channel" | "c" | "user"),
+        _ => false,
+    };
+
+    if is_bare_channel {
+        format!("{}/videos", trimmed)
     } else {
-        "ffmpeg"
-    });
-
-    // Initialize YouTube client, downloading binaries if needed
-    let youtube: Youtube = if yt_dlp_path.exists() && ffmpeg_path.exists() {
-        let libs = yt_dlp::client::deps::Libraries::new(yt_dlp_path, ffmpeg_path);
-        Youtube::new(libs, output_dir.to_path_buf())
-            .await
-            .map_err(|e| Error::DownloadFailed(e.to_string()))?
+        url.to_string()
+    }
+}
+
+/// Convert a yt-dlp `YYYYMMDD` date to `YYYY-MM-DD`.
+///
+/// Dates in any other shape are returned unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use voxtus::download::youtube::format_upload_date;
+///
+/// assert_eq!(format_upload_date("20240131"), "2024-01-31");
+/// assert_eq!(format_upload_date("2024-01-31"), "2024-01-31");
+/// ```
+pub fn format_upload_date(date: &str) -> String {
+    if date.len() == 8 && date.bytes().all(|b| b.is_ascii_digit()) {
+        format!("{}-{}-{}", &date[..4], &date[4..6], &date[6..])
     } else {
-        Youtube::with_new_binaries(libs_dir, output_dir.to_path_buf())
-            .await
-            .map_err(|e| Error::DownloadFailed(e.to_string()))?
-    };
+        date.to_string()
+    }
+}
+
+/// Sorted subtitle languages, without YouTube's live chat pseudo-track.
+fn subtitle_languages(tracks: Option<HashMap<String, serde_json::Value>>) -> Vec<String> {
+    let mut languages: Vec<String> = tracks
+        .unwrap_or_default()
+        .into_keys()
+        .filter(|l| l != "live_chat")
+        .collect();
+    languages.sort();
+    languages
+}
+
+/// Parse yt-dlp single video JSON into a [`VideoInfo`].
+pub fn parse_video_json(json: &str) -> Result<VideoInfo> {
+    let raw: RawVideoInfo = serde_json::from_str(json)?;
+
+    let chapters = raw
+        .chapters
+        .unwrap_or_default()
+        .into_iter()
+        .enumerate()
+        .map(|(i, c)| {
+            let title = c.title.unwrap_or_else(|| format!("Chapter {}", i + 1));
+            Chapter::new(c.start_time, c.end_time, title)
+        })
+        .collect();
+
+    Ok(VideoInfo {
+        title: raw
+            .title
+            .or_else(|| raw.id.clone())
+            .unwrap_or_else(|| "video".to_string()),
+        id: raw.id,
+        uploader: raw.uploader,
+        channel: raw.channel,
+        upload_date: raw.upload_date.as_deref().map(format_upload_date),
+        description: raw.description.filter(|d| !d.trim().is_empty()),
+        duration: raw.duration,
+        tags: raw.tags.unwrap_or_default(),
+        chapters,
+        language: raw.language,
+        subtitles: subtitle_languages(raw.subtitles),
+        automatic_captions: subtitle_languages(raw.automatic_captions),
+    })
+}
+
+/// Pick the best subtitle track for a video.
+///
+/// Creator-uploaded subtitles in the video's own language come first. When
+/// the spoken language is unknown, or with `fallback` set (for `--subs
+/// only`), English and then any other language follow; otherwise a
+/// translation is never picked over transcribing. Automatic captions are
+/// only considered with `fallback` set and no manual subtitles, and only in
+/// the spoken language (`<lang>-orig` or `<lang>`), since the rest are
+/// machine translations.
+pub fn select_subtitle_track(info: &VideoInfo, fallback: bool) -> Option<SubtitleTrack> {
+    let matches =
+        |lang: &str, wanted: &str| lang == wanted || lang.split('-').next() == Some(wanted);
+
+    let spoken = info.language.as_deref();
+    let mut manual = spoken.and_then(|s| info.subtitles.iter().find(|l| matches(l, s)));
+    if spoken.is_none() || fallback {
+        manual = manual
+            .or_else(|| info.subtitles.iter().find(|l| matches(l, "en")))
+            .or_else(|| info.subtitles.first());
+    }
+    if let Some(language) = manual {
+        return Some(SubtitleTrack {
+            language: language.clone(),
+            automatic: false,
+        });
+    }
+
+    if !fallback {
+        return None;
+    }
+
+    let spoken = spoken.unwrap_or("en");
+    let original = format!("{}-orig", spoken);
+    info.automatic_captions
+        .iter()
+        .find(|l| **l == original)
+        .or_else(|| info.automatic_captions.iter().find(|l| *l == spoken))
+        .map(|language| SubtitleTrack {
+            language: language.clone(),
+            automatic: true,
+        })
+}
 
-    // Fetch video info
-    let video = youtube
-        .fetch_video_infos(url.to_string())
+/// Parse yt-dlp flat playlist JSON into a [`PlaylistInfo`].
+pub fn parse_playlist_json(json: &str) -> Result<PlaylistInfo> {
+    let raw: RawPlaylist = serde_json::from_str(json)?;
+
+    let entries = raw
+        .entries
+        .into_iter()
+        .filter_map(|e| {
+            let id = e.id?;
+            let url = e
+                .url
+                .filter(|u| u.starts_with("http"))
+                .unwrap_or_else(|| format!("https://www.youtube.com/watch?v={}", id));
+            Some((id, e.title, url))
+        })
+        .enumerate()
+        .map(|(i, (id, title, url))| PlaylistEntry {
+            index: i + 1,
+            title: title.unwrap_or_else(|| id.clone()),
+            id,
+            url,
+        })
+        .collect();
+
+    let id = raw.id.unwrap_or_default();
+    Ok(PlaylistInfo {
+        title: raw.title.unwrap_or_else(|| id.clone()),
+        id,
+        entries,
+    })
+}
+
+/// Run yt-dlp, installing it first if needed, and return its standard output.
+///
+/// The passthrough options go before `args`, which end with the URL.
+#[cfg(feature = "youtube")]
+async fn run_yt_dlp(work_dir: &Path, args: &[&str], options: &YtDlpOptions) -> Result<String> {
+    let yt_dlp_path = crate::deps::ensure_yt_dlp(work_dir).await?;
+
+    let mut command = tokio::process::Command::new(yt_dlp_path);
+    // Use the same ffmpeg as the rest of voxtus
+    let ffmpeg = crate::deps::ffmpeg_path();
+    if ffmpeg.is_absolute() {
+        command.arg("--ffmpeg-location").arg(ffmpeg);
+    }
+
+    let output = command
+        .args(options.to_args())
+        .args(args)
+        .output()
         .await
-        .map_err(|e| Error::DownloadFailed(format!("Failed to fetch video info: {}", e)))?;
+        .map_err(|e| Error::DownloadFailed(format!("Failed to run yt-dlp: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::DownloadFailed(
+            stderr.lines().last().unwrap_or("unknown error").to_string(),
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Fetch video metadata without downloading any media.
+#[cfg(feature = "youtube")]
+pub async fn fetch_video_info(
+    url: &str,
+    work_dir: &Path,
+    options: &YtDlpOptions,
+) -> Result<VideoInfo> {
+    let json = run_yt_dlp(
+        work_dir,
+        &["--dump-single-json", "--no-playlist", "--no-warnings", url],
+        options,
+    )
+    .await
+    .map_err(|e| Error::DownloadFailed(format!("Failed to fetch video info: {}", e)))?;
+    parse_video_json(&json)
+}
 
-    let info = VideoInfo {
-        title: video.title.clone(),
+/// Download a subtitle track as VTT. Returns the path of the subtitle file.
+#[cfg(feature = "youtube")]
+pub async fn download_subtitles(
+    url: &str,
+    track: &SubtitleTrack,
+    output_dir: &Path,
+    options: &YtDlpOptions,
+) -> Result<PathBuf> {
+    let template = output_dir.join("subtitles.%(ext)s");
+    let template = template.to_string_lossy();
+    let write_flag = if track.automatic {
+        "--write-auto-subs"
+    } else {
+        "--write-subs"
     };
 
-    // Download audio stream as m4a (native YouTube format)
-    // We'll convert to mp3 later using our own ffmpeg
-    let audio_path = youtube
-        .download_audio_stream_from_url(url.to_string(), "audio.m4a")
-        .await
-        .map_err(|e| Error::DownloadFailed(format!("Failed to download audio: {}", e)))?;
+    run_yt_dlp(
+        output_dir,
+        &[
+            "--skip-download",
+            "--no-playlist",
+            "--no-warnings",
+            write_flag,
+            "--sub-langs",
+            &track.language,
+            "--sub-format",
+            "vtt",
+            "-o",
+            &template,
+            url,
+        ],
+        options,
+    )
+    .await
+    .map_err(|e| Error::DownloadFailed(format!("Failed to download subtitles: {}", e)))?;
+
+    // yt-dlp names the file subtitles.<lang>.vtt
+    let path = output_dir.join(format!("subtitles.{}.vtt", track.language));
+    if !path.exists() {
+        return Err(Error::DownloadFailed(format!(
+            "No VTT subtitles for language '{}'",
+            track.language
+        )));
+    }
+
+    Ok(path)
+}
+
+/// Download audio from URL. Returns the audio path and video info.
+///
+/// The video info is fetched first, unless it is passed in.
+#[cfg(feature = "youtube")]
+pub async fn download_audio(
+    url: &str,
+    output_dir: &Path,
+    options: &YtDlpOptions,
+    info: Option<VideoInfo>,
+) -> Result<(PathBuf, VideoInfo)> {
+    let info = match info {
+        Some(info) => info,
+        None => fetch_video_info(url, output_dir, options).await?,
+    };
+
+    // Download the audio stream as is (m4a or webm on YouTube); the stream
+    // is used as downloaded, with no conversion step
+    let template = output_dir.join("audio.%(ext)s");
+    let template = template.to_string_lossy();
+    let selector = options.format_selector();
+    let stdout = run_yt_dlp(
+        output_dir,
+        &[
+            "--no-playlist",
+            "--no-warnings",
+            "--no-simulate",
+            "--print",
+            "after_move:filepath",
+            "-f",
+            &selector,
+            "-o",
+            &template,
+            url,
+        ],
+        options,
+    )
+    .await
+    .map_err(|e| Error::DownloadFailed(format!("Failed to download audio: {}", e)))?;
+
+    let audio_path = stdout
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .filter(|path| path.exists())
+        .ok_or_else(|| Error::DownloadFailed("yt-dlp did not report a downloaded file".into()))?;
 
     Ok((audio_path, info))
 }
 
 #[cfg(not(feature = "youtube"))]
-pub async fn download_audio(_url: &str, _output_dir: &Path) -> Result<(PathBuf, VideoInfo)> {
+pub async fn download_audio(
+    _url: &str,
+    _output_dir: &Path,
+    _options: &YtDlpOptions,
+    _info: Option<VideoInfo>,
+) -> Result<(PathBuf, VideoInfo)> {
     Err(Error::DownloadFailed(
-        "YouTube download requires the 'youtube' feature".into(),
+        "Downloading with yt-dlp requires the 'youtube' feature".into(),
     ))
 }
 
-/// Synchronous wrapper for download_audio.
+/// Run a download future to completion on a fresh runtime.
 #[cfg(feature = "youtube")]
-pub fn download_audio_sync(url: &str, output_dir: &Path) -> Result<(PathBuf, VideoInfo)> {
+fn block_on<T>(future: impl std::future::Future<Output = Result<T>>) -> Result<T> {
     let rt = tokio::runtime::Runtime::new()
         .map_err(|e| Error::DownloadFailed(format!("Failed to create runtime: {}", e)))?;
 
-    rt.block_on(download_audio(url, output_dir))
+    rt.block_on(future)
+}
+
+/// Synchronous wrapper for download_audio.
+#[cfg(feature = "youtube")]
+pub fn download_audio_sync(
+    url: &str,
+    output_dir: &Path,
+    options: &YtDlpOptions,
+    info: Option<VideoInfo>,
+) -> Result<(PathBuf, VideoInfo)> {
+    block_on(download_audio(url, output_dir, options, info))
+}
+
+#[cfg(not(feature = "youtube"))]
+pub fn download_audio_sync(
+    _url: &str,
+    _output_dir: &Path,
+    _options: &YtDlpOptions,
+    _info: Option<VideoInfo>,
+) -> Result<(PathBuf, VideoInfo)> {
+    Err(Error::DownloadFailed(
+        "Downloading with yt-dlp requires the 'youtube' feature".into(),
+    ))
+}
+
+/// Synchronous wrapper for fetch_video_info.
+#[cfg(feature = "youtube")]
+pub fn fetch_video_info_sync(
+    url: &str,
+    work_dir: &Path,
+    options: &YtDlpOptions,
+) -> Result<VideoInfo> {
+    block_on(fetch_video_info(url, work_dir, options))
+}
+
+#[cfg(not(feature = "youtube"))]
+pub fn fetch_video_info_sync(
+    _url: &str,
+    _work_dir: &Path,
+    _options: &YtDlpOptions,
+) -> Result<VideoInfo> {
+    Err(Error::DownloadFailed(
+        "Downloading with yt-dlp requires the 'youtube' feature".into(),
+    ))
+}
+
+/// Synchronous wrapper for download_subtitles.
+#[cfg(feature = "youtube")]
+pub fn download_subtitles_sync(
+    url: &str,
+    track: &SubtitleTrack,
+    output_dir: &Path,
+    options: &YtDlpOptions,
+) -> Result<PathBuf> {
+    block_on(download_subtitles(url, track, output_dir, options))
 }
 
 #[cfg(not(feature = "youtube"))]
-pub fn download_audio_sync(_url: &str, _output_dir: &Path) -> Result<(PathBuf, VideoInfo)> {
+pub fn download_subtitles_sync(
+    _url: &str,
+    _track: &SubtitleTrack,
+    _output_dir: &Path,
+    _options: &YtDlpOptions,
+) -> Result<PathBuf> {
     Err(Error::DownloadFailed(
-        "YouTube download requires the 'youtube' feature".into(),
+        "Downloading with yt-dlp requires the 'youtube' feature".into(),
+    ))
+}
+
+/// Enumerate the videos of a playlist or channel without downloading them.
+#[cfg(feature = "youtube")]
+pub async fn fetch_playlist(
+    url: &str,
+    work_dir: &Path,
+    options: &YtDlpOptions,
+) -> Result<PlaylistInfo> {
+    let url = normalize_channel_url(url);
+    let json = run_yt_dlp(
+        work_dir,
+        &[
+            "--flat-playlist",
+            "--dump-single-json",
+            "--no-warnings",
+            &url,
+        ],
+        options,
+    )
+    .await
+    .map_err(|e| Error::DownloadFailed(format!("Failed to list playlist: {}", e)))?;
+
+    parse_playlist_json(&json)
+}
+
+/// Synchronous wrapper for fetch_playlist.
+#[cfg(feature = "youtube")]
+pub fn fetch_playlist_sync(
+    url: &str,
+    work_dir: &Path,
+    options: &YtDlpOptions,
+) -> Result<PlaylistInfo> {
+    block_on(fetch_playlist(url, work_dir, options))
+}
+
+#[cfg(not(feature = "youtube"))]
+pub fn fetch_playlist_sync(
+    _url: &str,
+    _work_dir: &Path,
+    _options: &YtDlpOptions,
+) -> Result<PlaylistInfo> {
+    Err(Error::DownloadFailed(
+        "Downloading with yt-dlp requires the 'youtube' feature".into(),
     ))
 }
 
@@ -106,9 +775,299 @@ mod tests {
     use super::*;
 
     #[test]
-    #[cfg(feature = "youtube")]
-    fn test_get_libs_dir() {
-        let dir = get_libs_dir().unwrap();
-        assert!(dir.ends_with("voxtus/libs") || dir.ends_with("voxtus\\libs"));
+    fn test_yt_dlp_supports_any_web_url() {
+        let downloader = YtDlpDownloader::default();
+        assert!(downloader.supports("https://vimeo.com/12345"));
+        assert!(downloader.supports("http://www.twitch.tv/videos/1"));
+        assert!(!downloader.supports("/local/file.mp3"));
+    }
+
+    #[test]
+    fn test_yt_dlp_options_to_args() {
+        assert!(YtDlpOptions::default().to_args().is_empty());
+
+        let options = YtDlpOptions {
+            cookies: Some(PathBuf::from("/tmp/cookies.txt")),
+            cookies_from_browser: Some("firefox".to_string()),
+            proxy: Some("socks5://127.0.0.1:1080".to_string()),
+            limit_rate: Some("2M".to_string()),
+            retries: Some(5),
+            audio_format: Some("opus".to_string()),
+            audio_quality: None,
+            extra_args: vec!["--geo-bypass".to_string()],
+        };
+        assert_eq!(
+            options.to_args(),
+            vec![
+                "--cookies",
+                "/tmp/cookies.txt",
+                "--cookies-from-browser",
+                "firefox",
+                "--proxy",
+                "socks5://127.0.0.1:1080",
+                "--limit-rate",
+                "2M",
+                "--retries",
+                "5",
+                "--geo-bypass",
+            ]
+        );
+        assert_eq!(
+            options.format_selector(),
+            "bestaudio[ext=opus]/bestaudio/best"
+        );
+    }
+
+    #[test]
+    fn test_yt_dlp_options_merge() {
+        let file = YtDlpOptions {
+            proxy: Some("http://file-proxy".to_string()),
+            retries: Some(3),
+            extra_args: vec!["--a".to_string()],
+            ..Default::default()
+        };
+        let cli = YtDlpOptions {
+            proxy: Some("http://cli-proxy".to_string()),
+            extra_args: vec!["--b".to_string()],
+            ..Default::default()
+        };
+
+        let merged = file.merge(cli);
+        assert_eq!(merged.proxy.as_deref(), Some("http://cli-proxy"));
+        assert_eq!(merged.retries, Some(3));
+        assert_eq!(merged.extra_args, vec!["--a", "--b"]);
+    }
+
+    #[test]
+    fn test_yt_dlp_options_deserialize() {
+        let options: YtDlpOptions =
+            serde_json::from_str(r#"{"limit_rate": "500K", "audio_quality": 96}"#).unwrap();
+        assert_eq!(options.limit_rate.as_deref(), Some("500K"));
+        assert_eq!(options.audio_quality, Some(96));
+        assert!(serde_json::from_str::<YtDlpOptions>(r#"{"proxi": "x"}"#).is_err());
+    }
+
+    #[test]
+    fn test_youtube_video_id() {
+        let id = |url| youtube_video_id(url);
+        assert_eq!(
+            id("https://m.youtube.com/watch?feature=share&v=abc_-1").as_deref(),
+            Some("abc_-1")
+        );
+        assert_eq!(
+            id("https://www.youtube.com/shorts/abc123").as_deref(),
+            Some("abc123")
+        );
+        assert_eq!(
+            id("https://www.youtube.com/live/abc123?si=x").as_deref(),
+            Some("abc123")
+        );
+        assert_eq!(
+            id("https://youtu.be/abc123?t=42").as_deref(),
+            Some("abc123")
+        );
+        assert_eq!(id("https://www.youtube.com/playlist?list=PL1"), None);
+        assert_eq!(id("https://www.youtube.com/watch?v=../etc"), None);
+        assert_eq!(id("https://notyoutube.com/watch?v=abc"), None);
+    }
+
+    #[test]
+    fn test_is_playlist_url() {
+        assert!(is_playlist_url(
+            "https://www.youtube.com/playlist?list=PLabc"
+        ));
+        assert!(is_playlist_url("https://youtube.com/@channel/videos"));
+        assert!(is_playlist_url("https://www.youtube.com/channel/UC123"));
+        assert!(is_playlist_url("https://www.youtube.com/c/Name"));
+        assert!(is_playlist_url("https://www.youtube.com/user/name"));
+        assert!(!is_playlist_url("https://www.youtube.com/watch?v=abc"));
+        assert!(!is_playlist_url(
+            "https://www.youtube.com/watch?v=abc&list=PLabc"
+        ));
+        assert!(!is_playlist_url("https://youtu.be/abc"));
+        assert!(!is_playlist_url("https://example.com/playlist"));
+        assert!(!is_playlist_url(
+            "https://notyoutube.com/playlist?list=PLabc"
+        ));
+        assert!(!is_playlist_url("https://evil-youtube.com/@channel"));
+        assert!(!is_playlist_url("playlist.mp3"));
+    }
+
+    #[test]
+    fn test_normalize_channel_url() {
+        assert_eq!(
+            normalize_channel_url("https://www.youtube.com/@channel"),
+            "https://www.youtube.com/@channel/videos"
+        );
+        assert_eq!(
+            normalize_channel_url("https://www.youtube.com/channel/UC123/"),
+            "https://www.youtube.com/channel/UC123/videos"
+        );
+        assert_eq!(
+            normalize_channel_url("https://www.youtube.com/@channel/shorts"),
+            "https://www.youtube.com/@channel/shorts"
+        );
+        assert_eq!(
+            normalize_channel_url("https://www.youtube.com/playlist?list=PLabc"),
+            "https://www.youtube.com/playlist?list=PLabc"
+        );
+    }
+
+    #[test]
+    fn test_parse_playlist_json() {
+        let json = r#"{
+            "id": "PLabc",
+            "title": "My Playlist",
+            "_type": "playlist",
+            "entries": [
+                {"id": "vid1", "title": "First", "url": "https://www.youtube.com/watch?v=vid1"},
+                {"id": "vid2", "title": null, "url": "vid2"},
+                {"title": "No id"}
+            ]
+        }"#;
+
+        let playlist = parse_playlist_json(json).unwrap();
+        assert_eq!(playlist.id, "PLabc");
+        assert_eq!(playlist.title, "My Playlist");
+        assert_eq!(playlist.entries.len(), 2);
+
+        assert_eq!(playlist.entries[0].index, 1);
+        assert_eq!(playlist.entries[0].title, "First");
+        assert_eq!(
+            playlist.entries[0].url,
+            "https://www.youtube.com/watch?v=vid1"
+        );
+
+        // Missing title falls back to id, relative url is rebuilt
+        assert_eq!(playlist.entries[1].index, 2);
+        assert_eq!(playlist.entries[1].title, "vid2");
+        assert_eq!(
+            playlist.entries[1].url,
+            "https://www.youtube.com/watch?v=vid2"
+        );
+    }
+
+    #[test]
+    fn test_format_upload_date() {
+        assert_eq!(format_upload_date("19991231"), "1999-12-31");
+        assert_eq!(format_upload_date("2024"), "2024");
+        assert_eq!(format_upload_date("2024013x"), "2024013x");
+    }
+
+    #[test]
+    fn test_parse_video_json() {
+        let json = r#"{
+            "id": "dQw4w9WgXcQ",
+            "title": "Some Talk",
+            "uploader": "Conference",
+            "channel": "Conference Channel",
+            "upload_date": "20240131",
+            "description": "About the talk",
+            "duration": 212.0,
+            "tags": ["rust", "audio"],
+            "chapters": [
+                {"start_time": 0.0, "end_time": 60.0, "title": "Intro"},
+                {"start_time": 60.0, "end_time": 212.0, "title": null}
+            ],
+            "formats": []
+        }"#;
+
+        let info = parse_video_json(json).unwrap();
+        assert_eq!(info.id.as_deref(), Some("dQw4w9WgXcQ"));
+        assert_eq!(info.title, "Some Talk");
+        assert_eq!(info.uploader.as_deref(), Some("Conference"));
+        assert_eq!(info.channel.as_deref(), Some("Conference Channel"));
+        assert_eq!(info.upload_date.as_deref(), Some("2024-01-31"));
+        assert_eq!(info.description.as_deref(), Some("About the talk"));
+        assert_eq!(info.duration, Some(212.0));
+        assert_eq!(info.tags, vec!["rust", "audio"]);
+        assert_eq!(
+            info.chapters,
+            vec![
+                Chapter::new(0.0, 60.0, "Intro"),
+                Chapter::new(60.0, 212.0, "Chapter 2"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_video_json_subtitles() {
+        let json = r#"{
+            "id": "abc",
+            "language": "sv",
+            "subtitles": {"sv": [], "en-GB": [], "live_chat": []},
+            "automatic_captions": {"sv-orig": [], "sv": [], "de": []}
+        }"#;
+
+        let info = parse_video_json(json).unwrap();
+        assert_eq!(info.language.as_deref(), Some("sv"));
+        assert_eq!(info.subtitles, vec!["en-GB", "sv"]);
+        assert_eq!(info.automatic_captions, vec!["de", "sv", "sv-orig"]);
+    }
+
+    fn info_with_subs(language: Option<&str>, manual: &[&str], auto: &[&str]) -> VideoInfo {
+        VideoInfo {
+            language: language.map(str::to_string),
+            subtitles: manual.iter().map(|s| s.to_string()).collect(),
+            automatic_captions: auto.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_select_subtitle_track_prefers_spoken_language() {
+        let info = info_with_subs(Some("sv"), &["de", "en", "sv-SE"], &["sv-orig"]);
+        let track = select_subtitle_track(&info, true).unwrap();
+        assert_eq!(track.language, "sv-SE");
+        assert!(!track.automatic);
+    }
+
+    #[test]
+    fn test_select_subtitle_track_falls_back_to_english() {
+        let info = info_with_subs(None, &["de", "en-US"], &[]);
+        assert_eq!(
+            select_subtitle_track(&info, false).unwrap().language,
+            "en-US"
+        );
+
+        let info = info_with_subs(Some("fi"), &["de"], &[]);
+        assert_eq!(select_subtitle_track(&info, true).unwrap().language, "de");
+    }
+
+    #[test]
+    fn test_select_subtitle_track_skips_translations_unless_fallback() {
+        // A German video with only translated subtitles is transcribed
+        let info = info_with_subs(Some("de"), &["en", "ja"], &[]);
+        assert!(select_subtitle_track(&info, false).is_none());
+        assert_eq!(select_subtitle_track(&info, true).unwrap().language, "en");
+    }
+
+    #[test]
+    fn test_select_subtitle_track_automatic() {
+        let info = info_with_subs(Some("sv"), &[], &["de", "sv", "sv-orig"]);
+        assert!(select_subtitle_track(&info, false).is_none());
+
+        let track = select_subtitle_track(&info, true).unwrap();
+        assert_eq!(track.language, "sv-orig");
+        assert!(track.automatic);
+
+        // Translated captions are never picked
+        let info = info_with_subs(Some("sv"), &[], &["de"]);
+        assert!(select_subtitle_track(&info, true).is_none());
+    }
+
+    #[test]
+    fn test_parse_video_json_minimal() {
+        let info = parse_video_json(r#"{"id": "abc", "tags": null, "description": ""}"#).unwrap();
+        assert_eq!(info.title, "abc");
+        assert!(info.tags.is_empty());
+        assert!(info.chapters.is_empty());
+        assert!(info.description.is_none());
+        assert!(info.upload_date.is_none());
+    }
+
+    #[test]
+    fn test_parse_playlist_json_invalid() {
+        assert!(parse_playlist_json("not json").is_err());
     }
 }
@@ -21,9 +21,12 @@ pub enum Error {
     #[error("File not found: {0}")]
     FileNotFound(String),
 
-    #[error("Invalid URL: {0}")]
+    #[error("No downloader supports URL: {0}")]
     InvalidUrl(String),
 
+    #[error("Unknown downloader: {0}")]
+    InvalidDownloader(String),
+
     #[error("Download failed: {0}")]
     DownloadFailed(String),
 
@@ -36,9 +39,70 @@ pub enum Error {
     #[error("FFmpeg not found. Please install ffmpeg.")]
     FfmpegNotFound,
 
+    #[error("{0} not found on PATH")]
+    BinaryNotFound(String),
+
+    #[error("Dependency error: {0}")]
+    DepsError(String),
+
+    #[error("Cache error: {0}")]
+    CacheError(String),
+
+    #[error("Invalid config file: {0}")]
+    InvalidConfig(String),
+
+    #[error(
+        "Invalid audio format: {0} (expected original, mp3, flac, wav, opus or m4a, with an optional :BITRATE for lossy codecs)"
+    )]
+    InvalidAudioFormat(String),
+
+    #[error("Invalid audio filter: {0}")]
+    InvalidAudioFilter(String),
+
+    #[error("Invalid JSON compatibility mode: {0} (expected voxtus or openai)")]
+    InvalidJsonCompat(String),
+
+    #[error("Invalid TXT style: {0} (expected timestamps or plain)")]
+    InvalidTxtStyle(String),
+
+    #[error("Invalid ASS style: {0}")]
+    InvalidAssStyle(String),
+
+    #[error(
+        "Invalid CSV column: {0} (expected id, start, end, duration, speaker, text or confidence)"
+    )]
+    InvalidCsvColumn(String),
+
+    #[error("Invalid collision policy: {0} (expected prompt, overwrite, skip or suffix)")]
+    InvalidCollisionPolicy(String),
+
+    #[error("Invalid format template: {0}")]
+    InvalidFormatTemplate(String),
+
+    #[error("Invalid output template: {0}")]
+    InvalidTemplate(String),
+
     #[error("Invalid model: {0}")]
     InvalidModel(String),
 
+    #[error("Invalid subtitle mode: {0} (expected prefer, only or never)")]
+    InvalidSubsMode(String),
+
+    #[error("No subtitles available for {0}")]
+    NoSubtitles(String),
+
+    #[error("--subs only needs the yt-dlp downloader, not {0}")]
+    SubsNeedYtDlp(String),
+
+    #[error("{0} playlist entries failed")]
+    PlaylistEntriesFailed(usize),
+
+    #[error("Invalid feed: {0}")]
+    InvalidFeed(String),
+
+    #[error("{0} feed episodes failed")]
+    FeedEpisodesFailed(usize),
+
     #[error("User aborted")]
     UserAborted,
 
@@ -0,0 +1,596 @@
+// Voxtus - Transcribe YouTube videos and local media files to text
+// Copyright (C) 2024 Johan Thorén <johan@thoren.xyz>
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Podcast feed ingestion.
+//!
+//! RSS and Atom feeds are parsed into episodes with enclosure URLs. A
+//! per-feed state file records the GUIDs of episodes already transcribed,
+//! so scheduled runs only pick up new episodes.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::is_url;
+use crate::download::VideoInfo;
+use crate::error::{Error, Result};
+
+/// Default template for naming feed episode outputs.
+pub const DEFAULT_EPISODE_TEMPLATE: &str = "{date} - {title}";
+
+/// File extensions treated as feeds.
+const FEED_EXTENSIONS: &[&str] = &["rss", "atom", "xml"];
+
+/// A feed episode with downloadable media.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Episode {
+    /// Episode GUID (RSS `guid`, Atom `id`), or the enclosure URL if missing.
+    pub guid: String,
+    pub title: String,
+    /// Publish date as YYYY-MM-DD.
+    pub published: Option<String>,
+    pub enclosure_url: String,
+    pub author: Option<String>,
+    pub description: Option<String>,
+}
+
+/// A parsed podcast feed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Feed {
+    /// Show name.
+    pub title: String,
+    pub author: Option<String>,
+    /// Episodes in feed order (usually newest first).
+    pub episodes: Vec<Episode>,
+}
+
+impl Episode {
+    /// Metadata for the transcript of this episode.
+    pub fn video_info(&self, feed: &Feed) -> VideoInfo {
+        VideoInfo {
+            id: Some(self.guid.clone()),
+            title: self.title.clone(),
+            uploader: self.author.clone().or_else(|| feed.author.clone()),
+            channel: Some(feed.title.clone()),
+            upload_date: self.published.clone(),
+            description: self.description.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Check whether an input looks like a feed URL or feed file.
+///
+/// # Examples
+///
+/// ```
+/// use voxtus::feed::is_feed_input;
+///
+/// assert!(is_feed_input("https://example.com/podcast.rss"));
+/// assert!(is_feed_input("https://example.com/show/feed/"));
+/// assert!(is_feed_input("shows/weekly.xml"));
+/// assert!(!is_feed_input("https://example.com/episode.mp3"));
+/// ```
+pub fn is_feed_input(input: &str) -> bool {
+    let path = input.split(['?', '#']).next().unwrap_or(input);
+    let Some(file) = path.trim_end_matches('/').rsplit('/').next() else {
+        return false;
+    };
+    let file = file.to_lowercase();
+
+    let has_feed_extension = file
+        .rsplit_once('.')
+        .is_some_and(|(_, ext)| FEED_EXTENSIONS.contains(&ext));
+    let is_feed_path = is_url(input) && matches!(file.as_str(), "feed" | "rss" | "atom");
+
+    has_feed_extension || is_feed_path
+}
+
+/// Read a feed from a URL or local file and parse it.
+pub fn load_feed(input: &str) -> Result<Feed> {
+    let xml = if is_url(input) {
+        crate::download::http::download_text_sync(input)?
+    } else if Path::new(input).exists() {
+        std::fs::read_to_string(input)?
+    } else {
+        return Err(Error::FileNotFound(input.to_string()));
+    };
+
+    parse_feed(&xml)
+}
+
+/// Parse an RSS 2.0, RSS 1.0 or Atom feed.
+///
+/// Entries without an audio or video enclosure are left out.
+pub fn parse_feed(xml: &str) -> Result<Feed> {
+    let options = roxmltree::ParsingOptions {
+        allow_dtd: true,
+        ..Default::default()
+    };
+    let document = roxmltree::Document::parse_with_options(xml, options)
+        .map_err(|e| Error::InvalidFeed(e.to_string()))?;
+    let root = document.root_element();
+
+    if is(root, None, "rss") || is(root, Some(RDF_NS), "RDF") {
+        let channel = child(root, None, "channel")
+            .ok_or_else(|| Error::InvalidFeed("missing <channel>".to_string()))?;
+        // RSS 1.0 puts items next to the channel instead of inside it
+        let items = children(channel, None, "item").chain(children(root, None, "item"));
+        Ok(Feed {
+            title: child_text(channel, None, "title").unwrap_or_default(),
+            author: child_text(channel, Some(ITUNES_NS), "author")
+                .or_else(|| child_text(channel, None, "managingEditor")),
+            episodes: items.filter_map(parse_rss_item).collect(),
+        })
+    } else if is(root, None, "feed") {
+        Ok(Feed {
+            title: child_text(root, None, "title").unwrap_or_default(),
+            author: child(root, None, "author").and_then(|a| child_text(a, None, "name")),
+            episodes: children(root, None, "entry")
+                .filter_map(parse_atom_entry)
+                .collect(),
+        })
+    } else {
+        Err(Error::InvalidFeed(format!(
+            "unsupported root element <{}>",
+            root.tag_name().name()
+        )))
+    }
+}
+
+fn parse_rss_item(item: Node) -> Option<Episode> {
+    let enclosure_url = child(item, None, "enclosure")
+        .or_else(|| child(item, Some(MEDIA_NS), "content"))
+        .and_then(|e| e.attribute("url"))
+        .map(str::to_string)?;
+
+    Some(Episode {
+        guid: child_text(item, None, "guid").unwrap_or_else(|| enclosure_url.clone()),
+        title: child_text(item, None, "title").unwrap_or_else(|| enclosure_url.clone()),
+        published: child_text(item, None, "pubDate")
+            .or_else(|| child_text(item, Some(DC_NS), "date"))
+            .and_then(|d| format_publish_date(&d)),
+        author: child_text(item, Some(ITUNES_NS), "author")
+            .or_else(|| child_text(item, Some(DC_NS), "creator")),
+        description: child_text(item, None, "description")
+            .or_else(|| child_text(item, Some(ITUNES_NS), "summary"))
+            .map(|d| strip_html(&d))
+            .filter(|d| !d.is_empty()),
+        enclosure_url,
+    })
+}
+
+fn parse_atom_entry(entry: Node) -> Option<Episode> {
+    let enclosure_url = children(entry, None, "link")
+        .find(|l| l.attribute("rel") == Some("enclosure"))
+        .and_then(|l| l.attribute("href"))
+        .map(str::to_string)?;
+
+    Some(Episode {
+        guid: child_text(entry, None, "id").unwrap_or_else(|| enclosure_url.clone()),
+        title: child_text(entry, None, "title").unwrap_or_else(|| enclosure_url.clone()),
+        published: child_text(entry, None, "published")
+            .or_else(|| child_text(entry, None, "updated"))
+            .and_then(|d| format_publish_date(&d)),
+        author: child(entry, None, "author").and_then(|a| child_text(a, None, "name")),
+        description: child_text(entry, None, "summary")
+            .or_else(|| child_text(entry, None, "content"))
+            .map(|d| strip_html(&d))
+            .filter(|d| !d.is_empty()),
+        enclosure_url,
+    })
+}
+
+/// Convert an RFC 2822 (RSS) or RFC 3339 (Atom) date to YYYY-MM-DD.
+///
+/// # Examples
+///
+/// ```
+/// use voxtus::feed::format_publish_date;
+///
+/// assert_eq!(format_publish_date("Tue, 30 Jan 2024 10:00:00 GMT").as_deref(), Some("2024-01-30"));
+/// assert_eq!(format_publish_date("2024-01-30T10:00:00+01:00").as_deref(), Some("2024-01-30"));
+/// assert_eq!(format_publish_date("last week"), None);
+/// ```
+pub fn format_publish_date(date: &str) -> Option<String> {
+    let date = date.trim();
+    chrono::DateTime::parse_from_rfc2822(date)
+        .or_else(|_| chrono::DateTime::parse_from_rfc3339(date))
+        .map(|d| d.format("%Y-%m-%d").to_string())
+        .ok()
+        .or_else(|| {
+            let day = date.get(..10)?;
+            chrono::NaiveDate::parse_from_str(day, "%Y-%m-%d")
+                .ok()
+                .map(|d| d.to_string())
+        })
+}
+
+/// A transcribed episode and the files written for it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessedEpisode {
+    pub guid: String,
+    pub title: String,
+    pub published: Option<String>,
+    /// Output file names, relative to the output directory.
+    pub outputs: Vec<String>,
+}
+
+/// Per-feed record of the episodes already transcribed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeedState {
+    pub title: String,
+    pub source: String,
+    pub episodes: Vec<ProcessedEpisode>,
+}
+
+impl FeedState {
+    pub fn new(feed: &Feed, source: impl Into<String>) -> Self {
+        Self {
+            title: feed.title.clone(),
+            source: source.into(),
+            episodes: Vec::new(),
+        }
+    }
+
+    /// Load a state file written by a previous run, if one exists.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+
+    /// Check whether an episode has already been transcribed.
+    pub fn is_processed(&self, guid: &str) -> bool {
+        self.episodes.iter().any(|e| e.guid == guid)
+    }
+
+    /// Record a transcribed episode, replacing any previous record.
+    pub fn mark_processed(&mut self, episode: ProcessedEpisode) {
+        self.episodes.retain(|e| e.guid != episode.guid);
+        self.episodes.push(episode);
+    }
+
+    /// Format the state as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+/// Remove HTML tags from a show note and collapse whitespace.
+fn strip_html(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => {
+                in_tag = true;
+                text.push(' ');
+            }
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    decode_entities(&text)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+type Node<'a> = roxmltree::Node<'a, 'a>;
+
+/// Namespaces of the feed extensions that are read.
+const ITUNES_NS: &str = "http://www.itunes.com/dtds/podcast-1.0.dtd";
+const MEDIA_NS: &str = "http://search.yahoo.com/mrss/";
+const DC_NS: &str = "http://purl.org/dc/elements/1.1/";
+const RDF_NS: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+
+/// Namespaces of the feed formats themselves; RSS 2.0 has none.
+const FEED_NS: &[&str] = &["http://www.w3.org/2005/Atom", "http://purl.org/rss/1.0/"];
+
+/// Whether `node` is the element `name` in the namespace `ns`, or in the
+/// feed's own vocabulary when `ns` is `None`.
+fn is(node: Node, ns: Option<&str>, name: &str) -> bool {
+    let tag = node.tag_name();
+    node.is_element()
+        && tag.name() == name
+        && match ns {
+            Some(ns) => tag.namespace() == Some(ns),
+            None => tag.namespace().is_none_or(|uri| FEED_NS.contains(&uri)),
+        }
+}
+
+fn child<'a>(node: Node<'a>, ns: Option<&str>, name: &str) -> Option<Node<'a>> {
+    node.children().find(|c| is(*c, ns, name))
+}
+
+fn children<'a>(
+    node: Node<'a>,
+    ns: Option<&'a str>,
+    name: &'a str,
+) -> impl Iterator<Item = Node<'a>> {
+    node.children().filter(move |c| is(*c, ns, name))
+}
+
+/// Trimmed text of a child element, if present and non-empty.
+fn child_text(node: Node, ns: Option<&str>, name: &str) -> Option<String> {
+    let text: String = child(node, ns, name)?
+        .children()
+        .filter_map(|c| c.is_text().then(|| c.text()).flatten())
+        .collect();
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Decode the HTML entities common in show notes and numeric character
+/// references.
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest
+            .find(';')
+            .filter(|&end| end <= 10)
+            .and_then(|end| Some((decode_entity(&rest[1..end])?, end)));
+        match entity {
+            Some((c, end)) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
+}
+
+fn decode_entity(name: &str) -> Option<char> {
+    match name {
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "amp" => Some('&'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        _ => {
+            let code = match name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => name.strip_prefix('#')?.parse().ok()?,
+            };
+            char::from_u32(code)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RSS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd">
+  <channel>
+    <title>Rust &amp; Friends</title>
+    <itunes:author>Ferris</itunes:author>
+    <!-- newest first -->
+    <item>
+      <title>Episode 2: Async</title>
+      <guid isPermaLink="false">ep-2</guid>
+      <pubDate>Tue, 06 Feb 2024 08:00:00 +0000</pubDate>
+      <description><![CDATA[<p>All about <b>async</b>.</p>]]></description>
+      <enclosure url="https://cdn.example.com/ep2.mp3?a=1&amp;b=2" length="1" type="audio/mpeg"/>
+    </item>
+    <item>
+      <title>Trailer</title>
+      <description>No audio here</description>
+    </item>
+    <item>
+      <title>Episode 1</title>
+      <pubDate>Tue, 30 Jan 2024 08:00:00 GMT</pubDate>
+      <itunes:author>Guest Host</itunes:author>
+      <enclosure url='https://cdn.example.com/ep1.mp3' type="audio/mpeg" />
+    </item>
+  </channel>
+</rss>"#;
+
+    const ATOM: &str = r#"<?xml version="1.0"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title type="text">Atom Show</title>
+  <author><name>Host</name></author>
+  <entry>
+    <id>urn:uuid:1</id>
+    <title>First</title>
+    <published>2024-03-01T12:00:00Z</published>
+    <summary>Show &lt;b&gt;notes&lt;/b&gt;</summary>
+    <link rel="alternate" href="https://example.com/first"/>
+    <link rel="enclosure" type="audio/mpeg" href="https://example.com/first.m4a"/>
+  </entry>
+</feed>"#;
+
+    #[test]
+    fn test_parse_rss() {
+        let feed = parse_feed(RSS).unwrap();
+        assert_eq!(feed.title, "Rust & Friends");
+        assert_eq!(feed.author.as_deref(), Some("Ferris"));
+        assert_eq!(feed.episodes.len(), 2);
+
+        let ep2 = &feed.episodes[0];
+        assert_eq!(ep2.guid, "ep-2");
+        assert_eq!(ep2.title, "Episode 2: Async");
+        assert_eq!(ep2.published.as_deref(), Some("2024-02-06"));
+        assert_eq!(ep2.enclosure_url, "https://cdn.example.com/ep2.mp3?a=1&b=2");
+        assert_eq!(ep2.description.as_deref(), Some("All about async ."));
+
+        // Without a guid the enclosure URL identifies the episode
+        let ep1 = &feed.episodes[1];
+        assert_eq!(ep1.guid, "https://cdn.example.com/ep1.mp3");
+        assert_eq!(ep1.author.as_deref(), Some("Guest Host"));
+    }
+
+    #[test]
+    fn test_parse_atom() {
+        let feed = parse_feed(ATOM).unwrap();
+        assert_eq!(feed.title, "Atom Show");
+        assert_eq!(feed.author.as_deref(), Some("Host"));
+        assert_eq!(
+            feed.episodes,
+            vec![Episode {
+                guid: "urn:uuid:1".to_string(),
+                title: "First".to_string(),
+                published: Some("2024-03-01".to_string()),
+                enclosure_url: "https://example.com/first.m4a".to_string(),
+                author: None,
+                description: Some("Show notes".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_feed_rejects_invalid_documents() {
+        assert!(matches!(
+            parse_feed("<html><body></body></html>"),
+            Err(Error::InvalidFeed(_))
+        ));
+        assert!(matches!(
+            parse_feed("<rss><channel></rss>"),
+            Err(Error::InvalidFeed(_))
+        ));
+        assert!(matches!(parse_feed(""), Err(Error::InvalidFeed(_))));
+    }
+
+    #[test]
+    fn test_parse_feed_with_doctype() {
+        let xml = r#"<?xml version="1.0"?>
+<!DOCTYPE rss [
+  <!ENTITY show "Rust and Friends">
+  <!ELEMENT rss (channel)>
+]>
+<rss version="2.0"><channel><title>&show;</title></channel></rss>"#;
+        assert_eq!(parse_feed(xml).unwrap().title, "Rust and Friends");
+
+        // Entities that are never declared are an error, not kept as text
+        assert!(matches!(
+            parse_feed("<rss><channel><title>&show;</title></channel></rss>"),
+            Err(Error::InvalidFeed(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_feed_matches_namespaces_not_prefixes() {
+        let xml = r#"<rss xmlns:i="http://www.itunes.com/dtds/podcast-1.0.dtd"
+     xmlns:itunes="urn:other"><channel>
+  <itunes:author>Wrong</itunes:author>
+  <i:author>Ferris</i:author>
+</channel></rss>"#;
+        assert_eq!(parse_feed(xml).unwrap().author.as_deref(), Some("Ferris"));
+    }
+
+    #[test]
+    fn test_parse_rss1() {
+        let xml = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+         xmlns="http://purl.org/rss/1.0/" xmlns:dc="http://purl.org/dc/elements/1.1/">
+  <channel><title>RDF Show</title></channel>
+  <item>
+    <title>One</title>
+    <dc:date>2024-02-01T00:00:00Z</dc:date>
+    <enclosure url="https://example.com/one.mp3"/>
+  </item>
+</rdf:RDF>"#;
+        let feed = parse_feed(xml).unwrap();
+        assert_eq!(feed.title, "RDF Show");
+        assert_eq!(feed.episodes.len(), 1);
+        assert_eq!(feed.episodes[0].published.as_deref(), Some("2024-02-01"));
+    }
+
+    #[test]
+    fn test_episode_video_info() {
+        let feed = parse_feed(RSS).unwrap();
+
+        let info = feed.episodes[0].video_info(&feed);
+        assert_eq!(info.id.as_deref(), Some("ep-2"));
+        assert_eq!(info.title, "Episode 2: Async");
+        assert_eq!(info.channel.as_deref(), Some("Rust & Friends"));
+        assert_eq!(info.uploader.as_deref(), Some("Ferris"));
+        assert_eq!(info.upload_date.as_deref(), Some("2024-02-06"));
+
+        let info = feed.episodes[1].video_info(&feed);
+        assert_eq!(info.uploader.as_deref(), Some("Guest Host"));
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(decode_entities("a &lt;b&gt; &#233;&#x41;"), "a <b> éA");
+        assert_eq!(decode_entities("AT&T; & more"), "AT&T; & more");
+    }
+
+    #[test]
+    fn test_is_feed_input() {
+        assert!(is_feed_input("https://example.com/podcast.XML?key=1"));
+        assert!(is_feed_input("https://example.com/rss"));
+        assert!(!is_feed_input("feed"));
+        assert!(!is_feed_input("https://www.youtube.com/watch?v=abc"));
+        assert!(!is_feed_input("recording.mp3"));
+    }
+
+    #[test]
+    fn test_feed_state_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("show.feed.json");
+        let feed = parse_feed(RSS).unwrap();
+
+        let mut state = FeedState::new(&feed, "feed.rss");
+        state.mark_processed(ProcessedEpisode {
+            guid: "ep-2".to_string(),
+            title: "Episode 2: Async".to_string(),
+            published: Some("2024-02-06".to_string()),
+            outputs: vec!["2024-02-06 - Episode 2: Async.txt".to_string()],
+        });
+        std::fs::write(&path, state.to_json()).unwrap();
+
+        let loaded = FeedState::load(&path).unwrap().unwrap();
+        assert_eq!(loaded, state);
+        assert!(loaded.is_processed("ep-2"));
+        assert!(!loaded.is_processed("https://cdn.example.com/ep1.mp3"));
+        assert!(
+            FeedState::load(&dir.path().join("none.json"))
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_load_feed_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("show.rss");
+        std::fs::write(&path, ATOM).unwrap();
+
+        let feed = load_feed(path.to_str().unwrap()).unwrap();
+        assert_eq!(feed.title, "Atom Show");
+        assert!(matches!(
+            load_feed("/nonexistent/show.rss"),
+            Err(Error::FileNotFound(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "http")]
+    fn test_load_feed_from_local_server() {
+        let (url, server) = crate::download::http::test_server::serve_once(RSS.as_bytes());
+        let feed = load_feed(&url).unwrap();
+        server.join().unwrap();
+
+        assert_eq!(feed.title, "Rust & Friends");
+        assert_eq!(feed.episodes.len(), 2);
+    }
+}
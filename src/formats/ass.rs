@@ -0,0 +1,469 @@
+// Voxtus - Transcribe YouTube videos and local media files to text
+// Copyright (C) 2024 Johan Thorén <johan@thoren.xyz>
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! ASS (Advanced SubStation Alpha) format output.
+//!
+//! Styled subtitle format for burning subtitles into video. Uses
+//! centiseconds: `H:MM:SS.cc`. Colors are written as `&HAABBGGRR`.
+
+use super::{FormatContext, Formatter, Metadata, Segment, Transcript};
+use crate::error::{Error, Result};
+
+/// Speaker colors as `&HAABBGGRR`, in order of appearance.
+const SPEAKER_COLORS: &[&str] = &[
+    "&H0000FFFF", // yellow
+    "&H00FFFF00", // cyan
+    "&H0080FF80", // green
+    "&H00FF80FF", // pink
+    "&H000080FF", // orange
+    "&H00FFA060", // light blue
+];
+
+/// Style of the subtitles.
+///
+/// Set with `key=value` pairs, see [`AssStyle::apply`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssStyle {
+    pub font: String,
+    pub size: u32,
+    /// Text color, `&HAABBGGRR`.
+    pub color: String,
+    /// Color of words not yet sung in karaoke, `&HAABBGGRR`.
+    pub karaoke_color: String,
+    pub outline_color: String,
+    /// Shadow or box color, `&HAABBGGRR`.
+    pub back_color: String,
+    pub bold: bool,
+    /// Outline width in pixels.
+    pub outline: f64,
+    /// Shadow depth in pixels.
+    pub shadow: f64,
+    /// Numpad position: 1-3 bottom, 4-6 middle, 7-9 top.
+    pub alignment: u8,
+    /// Vertical margin in pixels.
+    pub margin: u32,
+}
+
+impl Default for AssStyle {
+    fn default() -> Self {
+        Self {
+            font: "Arial".to_string(),
+            size: 54,
+            color: "&H00FFFFFF".to_string(),
+            karaoke_color: "&H0000FFFF".to_string(),
+            outline_color: "&H00000000".to_string(),
+            back_color: "&H80000000".to_string(),
+            bold: false,
+            outline: 2.5,
+            shadow: 0.0,
+            alignment: 2,
+            margin: 50,
+        }
+    }
+}
+
+impl AssStyle {
+    /// Apply comma-separated `key=value` settings on top of this style.
+    ///
+    /// Keys are `font`, `size`, `color`, `karaoke-color`, `outline-color`,
+    /// `back-color`, `bold`, `outline`, `shadow`, `position` (`top`,
+    /// `middle`, `bottom` or a numpad digit) and `margin`. Colors are
+    /// `#RRGGBB`, `#RRGGBBAA` or ASS `&HAABBGGRR`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use voxtus::formats::ass::AssStyle;
+    ///
+    /// let mut style = AssStyle::default();
+    /// style.apply("font=Roboto,size=40,color=#FFCC00,position=top").unwrap();
+    /// assert_eq!(style.font, "Roboto");
+    /// assert_eq!(style.color, "&H0000CCFF");
+    /// assert_eq!(style.alignment, 8);
+    ///
+    /// assert!(style.apply("size=big").is_err());
+    /// ```
+    pub fn apply(&mut self, spec: &str) -> Result<()> {
+        for setting in spec.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let invalid = || Error::InvalidAssStyle(setting.to_string());
+            let (key, value) = setting.split_once('=').ok_or_else(invalid)?;
+            let value = value.trim();
+            match key.trim().to_lowercase().as_str() {
+                "font" if !value.is_empty() => self.font = value.to_string(),
+                "size" => self.size = value.parse().map_err(|_| invalid())?,
+                "color" => self.color = parse_color(value).ok_or_else(invalid)?,
+                "karaoke-color" => self.karaoke_color = parse_color(value).ok_or_else(invalid)?,
+                "outline-color" => self.outline_color = parse_color(value).ok_or_else(invalid)?,
+                "back-color" => self.back_color = parse_color(value).ok_or_else(invalid)?,
+                "bold" => self.bold = value.parse().map_err(|_| invalid())?,
+                "outline" => self.outline = parse_width(value).ok_or_else(invalid)?,
+                "shadow" => self.shadow = parse_width(value).ok_or_else(invalid)?,
+                "position" => {
+                    self.alignment = match value.to_lowercase().as_str() {
+                        "bottom" => 2,
+                        "middle" => 5,
+                        "top" => 8,
+                        digit => digit
+                            .parse()
+                            .ok()
+                            .filter(|d| (1..=9).contains(d))
+                            .ok_or_else(invalid)?,
+                    }
+                }
+                "margin" => self.margin = value.parse().map_err(|_| invalid())?,
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(())
+    }
+
+    /// Format the style as a `Style:` line with the given name and color.
+    fn format_line(&self, name: &str, color: &str) -> String {
+        format!(
+            "Style: {},{},{},{},{},{},{},{},0,0,0,100,100,0,0,1,{},{},{},60,60,{},1",
+            name,
+            self.font,
+            self.size,
+            color,
+            self.karaoke_color,
+            self.outline_color,
+            self.back_color,
+            if self.bold { -1 } else { 0 },
+            self.outline,
+            self.shadow,
+            self.alignment,
+            self.margin
+        )
+    }
+}
+
+/// Options for ASS output.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AssOptions {
+    pub style: AssStyle,
+    /// Add `\k` karaoke tags from word timings, when available.
+    pub karaoke: bool,
+}
+
+/// Parse a `#RRGGBB`, `#RRGGBBAA` or `&HAABBGGRR` color into ASS form.
+///
+/// The alpha of `#RRGGBBAA` is opacity, which ASS inverts to transparency.
+fn parse_color(value: &str) -> Option<String> {
+    let is_hex = |s: &str| s.chars().all(|c| c.is_ascii_hexdigit());
+
+    if let Some(hex) = value.strip_prefix('#') {
+        if !is_hex(hex) || !(hex.len() == 6 || hex.len() == 8) {
+            return None;
+        }
+        let (r, g, b) = (&hex[0..2], &hex[2..4], &hex[4..6]);
+        let alpha = match hex.get(6..8) {
+            Some(a) => 255 - u8::from_str_radix(a, 16).ok()?,
+            None => 0,
+        };
+        return Some(format!("&H{:02X}{}{}{}", alpha, b, g, r).to_uppercase());
+    }
+
+    let hex = value
+        .strip_prefix("&H")
+        .or_else(|| value.strip_prefix("&h"))?;
+    let hex = hex.strip_suffix('&').unwrap_or(hex);
+    if !is_hex(hex) || hex.is_empty() || hex.len() > 8 {
+        return None;
+    }
+    Some(format!("&H{:0>8}", hex.to_uppercase()))
+}
+
+/// Parse a non-negative width.
+fn parse_width(value: &str) -> Option<f64> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|w| w.is_finite() && *w >= 0.0)
+}
+
+/// Format seconds as ASS timestamp (H:MM:SS.cc).
+///
+/// # Example
+/// ```
+/// use voxtus::formats::ass::format_timestamp;
+///
+/// assert_eq!(format_timestamp(0.0), "0:00:00.00");
+/// assert_eq!(format_timestamp(65.5), "0:01:05.50");
+/// assert_eq!(format_timestamp(3661.123), "1:01:01.12");
+/// ```
+pub fn format_timestamp(seconds: f64) -> String {
+    let total_centis = (seconds.max(0.0) * 100.0).round() as u64;
+    let centis = total_centis % 100;
+    let total_seconds = total_centis / 100;
+    let hours = total_seconds / 3600;
+    let minutes = (total_seconds % 3600) / 60;
+    let secs = total_seconds % 60;
+
+    format!("{}:{:02}:{:02}.{:02}", hours, minutes, secs, centis)
+}
+
+/// Make text safe for a dialogue line.
+///
+/// Braces would start override blocks and newlines must be `\N`.
+fn escape(text: &str) -> String {
+    text.trim()
+        .replace('{', "(")
+        .replace('}', ")")
+        .replace("\r\n", "\\N")
+        .replace('\n', "\\N")
+}
+
+/// Make a speaker label usable as a style name.
+fn style_name(speaker: &str) -> String {
+    let name = speaker.replace(',', " ");
+    let name = name.trim();
+    if name.is_empty() || name == "Default" {
+        format!("Speaker {}", name).trim().to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Format the segment text with a `\k` tag per word.
+///
+/// Pauses before words become empty `\k` tags, so each word lights up when
+/// it is spoken.
+///
+/// # Example
+/// ```
+/// use voxtus::formats::{Segment, Word, ass::karaoke_text};
+///
+/// let mut segment = Segment::new(1.0, 2.0, "Hi there");
+/// segment.words = vec![Word::new(1.0, 1.3, "Hi"), Word::new(1.5, 2.0, "there")];
+/// assert_eq!(karaoke_text(&segment), r"{\k30}Hi {\k20}{\k50}there");
+/// ```
+pub fn karaoke_text(segment: &Segment) -> String {
+    let mut text = String::new();
+    let mut cursor = segment.start;
+    for (i, word) in segment.words.iter().enumerate() {
+        if i > 0 {
+            text.push(' ');
+        }
+        let start = word.start.max(cursor);
+        let gap = ((start - cursor) * 100.0).round() as u64;
+        if gap > 0 {
+            text.push_str(&format!("{{\\k{}}}", gap));
+        }
+        let end = word.end.max(start);
+        let duration = ((end - start) * 100.0).round() as u64;
+        text.push_str(&format!("{{\\k{}}}{}", duration, escape(&word.text)));
+        cursor = end;
+    }
+    text
+}
+
+/// Format a single segment as a `Dialogue:` line.
+pub fn format_segment(segment: &Segment, options: &AssOptions) -> String {
+    let (style, name) = match &segment.speaker {
+        Some(speaker) => (style_name(speaker), speaker.replace(',', " ")),
+        None => ("Default".to_string(), String::new()),
+    };
+    let text = if options.karaoke && !segment.words.is_empty() {
+        karaoke_text(segment)
+    } else {
+        escape(&segment.text)
+    };
+
+    format!(
+        "Dialogue: 0,{},{},{},{},0,0,0,,{}",
+        format_timestamp(segment.start),
+        format_timestamp(segment.end),
+        style,
+        name,
+        text
+    )
+}
+
+/// Format segments and metadata as ASS output.
+///
+/// A style is added for each speaker, in its own color.
+pub fn format_transcript(
+    segments: &[Segment],
+    metadata: &Metadata,
+    options: &AssOptions,
+) -> String {
+    let style = &options.style;
+    let mut styles = vec![style.format_line("Default", &style.color)];
+    let mut speakers: Vec<String> = Vec::new();
+    for speaker in segments.iter().filter_map(|s| s.speaker.as_deref()) {
+        let name = style_name(speaker);
+        if !speakers.contains(&name) {
+            let color = SPEAKER_COLORS[speakers.len() % SPEAKER_COLORS.len()];
+            styles.push(style.format_line(&name, color));
+            speakers.push(name);
+        }
+    }
+
+    let mut lines = vec![
+        "[Script Info]".to_string(),
+        "; Script generated by voxtus".to_string(),
+        format!("Title: {}", metadata.title.replace('\n', " ")),
+        "ScriptType: v4.00+".to_string(),
+        "WrapStyle: 0".to_string(),
+        "ScaledBorderAndShadow: yes".to_string(),
+        "PlayResX: 1920".to_string(),
+        "PlayResY: 1080".to_string(),
+        String::new(),
+        "[V4+ Styles]".to_string(),
+        "Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, \
+         BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, \
+         BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding"
+            .to_string(),
+    ];
+    lines.extend(styles);
+    lines.push(String::new());
+    lines.push("[Events]".to_string());
+    lines.push(
+        "Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text"
+            .to_string(),
+    );
+    lines.extend(segments.iter().map(|s| format_segment(s, options)));
+
+    lines.join("\n")
+}
+
+/// ASS output as a [`Formatter`].
+#[derive(Debug, Clone, Default)]
+pub struct AssFormatter {
+    pub options: AssOptions,
+}
+
+impl Formatter for AssFormatter {
+    fn name(&self) -> &str {
+        "ass"
+    }
+
+    fn extension(&self) -> &str {
+        "ass"
+    }
+
+    fn mime_type(&self) -> &str {
+        "text/x-ssa"
+    }
+
+    fn description(&self) -> &str {
+        "Styled ASS subtitles, optionally with karaoke"
+    }
+
+    fn format(&self, transcript: &Transcript, _context: &FormatContext) -> String {
+        format_transcript(&transcript.segments, &transcript.metadata, &self.options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::Word;
+
+    fn sample_metadata() -> Metadata {
+        Metadata::new("Test Video", "test.mp3", Some(4.0), "tiny", None)
+    }
+
+    #[test]
+    fn test_format_transcript_sections() {
+        let segments = vec![
+            Segment::new(0.0, 2.0, "Hello"),
+            Segment::new(2.0, 4.5, "World"),
+        ];
+        let ass = format_transcript(&segments, &sample_metadata(), &AssOptions::default());
+
+        assert!(ass.starts_with("[Script Info]\n"));
+        assert!(ass.contains("\nTitle: Test Video\n"));
+        assert!(ass.contains(
+            "\nStyle: Default,Arial,54,&H00FFFFFF,&H0000FFFF,&H00000000,&H80000000,\
+             0,0,0,0,100,100,0,0,1,2.5,0,2,60,60,50,1\n"
+        ));
+        assert!(ass.ends_with(
+            "Dialogue: 0,0:00:00.00,0:00:02.00,Default,,0,0,0,,Hello\n\
+             Dialogue: 0,0:00:02.00,0:00:04.50,Default,,0,0,0,,World"
+        ));
+    }
+
+    #[test]
+    fn test_dialogue_escapes_text() {
+        let segment = Segment::new(0.0, 1.0, " {\\b1}bold\nline ");
+        let line = format_segment(&segment, &AssOptions::default());
+        assert!(line.ends_with(",,(\\b1)bold\\Nline"));
+    }
+
+    #[test]
+    fn test_speaker_styles() {
+        let mut segments = vec![
+            Segment::new(0.0, 1.0, "Hi"),
+            Segment::new(1.0, 2.0, "Hello"),
+            Segment::new(2.0, 3.0, "Bye"),
+        ];
+        segments[0].speaker = Some("Alice".into());
+        segments[1].speaker = Some("Bob, Jr".into());
+        segments[2].speaker = Some("Alice".into());
+
+        let ass = format_transcript(&segments, &sample_metadata(), &AssOptions::default());
+        assert_eq!(ass.matches("\nStyle: ").count(), 3);
+        assert!(ass.contains("\nStyle: Alice,Arial,54,&H0000FFFF,"));
+        assert!(ass.contains("\nStyle: Bob  Jr,Arial,54,&H00FFFF00,"));
+        assert!(ass.contains(",Alice,Alice,0,0,0,,Hi\n"));
+        assert!(ass.contains(",Bob  Jr,Bob  Jr,0,0,0,,Hello\n"));
+    }
+
+    #[test]
+    fn test_karaoke() {
+        let mut segment = Segment::new(0.0, 1.0, "Hi there");
+        segment.words = vec![Word::new(0.0, 0.4, "Hi"), Word::new(0.4, 1.0, "there")];
+
+        let options = AssOptions {
+            karaoke: true,
+            ..Default::default()
+        };
+        assert!(format_segment(&segment, &options).ends_with(",,{\\k40}Hi {\\k60}there"));
+        assert!(format_segment(&segment, &AssOptions::default()).ends_with(",,Hi there"));
+    }
+
+    #[test]
+    fn test_karaoke_without_words_uses_text() {
+        let options = AssOptions {
+            karaoke: true,
+            ..Default::default()
+        };
+        let segment = Segment::new(0.0, 1.0, "Hi there");
+        assert!(format_segment(&segment, &options).ends_with(",,Hi there"));
+    }
+
+    #[test]
+    fn test_apply_style() {
+        let mut style = AssStyle::default();
+        style
+            .apply("size=36, bold=true, outline=0, shadow=1.5, position=7, margin=20")
+            .unwrap();
+        assert_eq!(style.size, 36);
+        assert!(style.bold);
+        assert_eq!(style.outline, 0.0);
+        assert_eq!(style.shadow, 1.5);
+        assert_eq!(style.alignment, 7);
+        assert_eq!(style.margin, 20);
+        assert_eq!(style.font, "Arial");
+    }
+
+    #[test]
+    fn test_apply_style_invalid() {
+        let mut style = AssStyle::default();
+        for spec in ["color=red", "position=10", "outline=-1", "weight=1", "font"] {
+            assert!(style.apply(spec).is_err(), "{}", spec);
+        }
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#FF8000").as_deref(), Some("&H000080FF"));
+        assert_eq!(parse_color("#ff800080").as_deref(), Some("&H7F0080FF"));
+        assert_eq!(parse_color("&H00FFFFFF").as_deref(), Some("&H00FFFFFF"));
+        assert_eq!(parse_color("&HFFFF&").as_deref(), Some("&H0000FFFF"));
+        assert_eq!(parse_color("#FFF"), None);
+        assert_eq!(parse_color("white"), None);
+    }
+}
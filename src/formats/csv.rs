@@ -0,0 +1,340 @@
+// Voxtus - Transcribe YouTube videos and local media files to text
+// Copyright (C) 2024 Johan Thorén <johan@thoren.xyz>
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! CSV and TSV format output.
+//!
+//! One row per segment with a header row, for spreadsheets and data
+//! analysis. CSV follows RFC 4180: CRLF line endings, and fields quoted when
+//! they contain a comma, quote or line break. TSV has no quoting, so tabs and
+//! line breaks in fields are replaced by spaces.
+
+use super::{FormatContext, Formatter, Segment, Transcript};
+use crate::error::{Error, Result};
+
+/// Default columns for `--csv-columns`.
+pub const DEFAULT_CSV_COLUMNS: &str = "id,start,end,text";
+
+/// The columns of [`DEFAULT_CSV_COLUMNS`].
+pub const DEFAULT_COLUMNS: &[CsvColumn] = &[
+    CsvColumn::Id,
+    CsvColumn::Start,
+    CsvColumn::End,
+    CsvColumn::Text,
+];
+
+/// A column of CSV and TSV output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvColumn {
+    /// Segment number, starting at 1.
+    Id,
+    /// Start time in seconds.
+    Start,
+    /// End time in seconds.
+    End,
+    /// Duration in seconds.
+    Duration,
+    /// Speaker label, empty when unknown.
+    Speaker,
+    Text,
+    /// Average token probability (0-1), empty when unknown.
+    Confidence,
+}
+
+impl std::str::FromStr for CsvColumn {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "id" => Ok(Self::Id),
+            "start" => Ok(Self::Start),
+            "end" => Ok(Self::End),
+            "duration" => Ok(Self::Duration),
+            "speaker" => Ok(Self::Speaker),
+            "text" => Ok(Self::Text),
+            "confidence" | "avg_confidence" => Ok(Self::Confidence),
+            _ => Err(Error::InvalidCsvColumn(s.to_string())),
+        }
+    }
+}
+
+impl CsvColumn {
+    /// Name of the column in the header row.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Id => "id",
+            Self::Start => "start",
+            Self::End => "end",
+            Self::Duration => "duration",
+            Self::Speaker => "speaker",
+            Self::Text => "text",
+            Self::Confidence => "confidence",
+        }
+    }
+
+    /// Unquoted value of the column for a segment.
+    ///
+    /// # Example
+    /// ```
+    /// use voxtus::formats::{Segment, csv::CsvColumn};
+    ///
+    /// let segment = Segment::new(1.5, 4.0, " Hello ");
+    /// assert_eq!(CsvColumn::Duration.value(&segment, 0), "2.500");
+    /// assert_eq!(CsvColumn::Text.value(&segment, 0), "Hello");
+    /// assert_eq!(CsvColumn::Confidence.value(&segment, 0), "");
+    /// ```
+    pub fn value(&self, segment: &Segment, index: usize) -> String {
+        match self {
+            Self::Id => (index + 1).to_string(),
+            Self::Start => format!("{:.3}", segment.start),
+            Self::End => format!("{:.3}", segment.end),
+            Self::Duration => format!("{:.3}", (segment.end - segment.start).max(0.0)),
+            Self::Speaker => segment.speaker.clone().unwrap_or_default(),
+            Self::Text => segment.text.trim().to_string(),
+            Self::Confidence => segment
+                .confidence
+                .map(|c| format!("{:.3}", c))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Quote a CSV field if it contains a delimiter, quote or line break.
+///
+/// # Examples
+///
+/// ```
+/// use voxtus::formats::csv::csv_field;
+///
+/// assert_eq!(csv_field("plain"), "plain");
+/// assert_eq!(csv_field("a,b"), "\"a,b\"");
+/// assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+/// ```
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Make a value safe for a TSV field.
+fn tsv_field(value: &str) -> String {
+    value.replace("\r\n", " ").replace(['\t', '\n', '\r'], " ")
+}
+
+/// Format segments as CSV with a header row.
+///
+/// # Example
+/// ```
+/// use voxtus::formats::{Segment, csv::{CsvColumn, format_csv}};
+///
+/// let segments = vec![Segment::new(0.0, 1.0, "Hi, there")];
+/// assert_eq!(
+///     format_csv(&segments, &[CsvColumn::Id, CsvColumn::Text]),
+///     "id,text\r\n1,\"Hi, there\""
+/// );
+/// ```
+pub fn format_csv(segments: &[Segment], columns: &[CsvColumn]) -> String {
+    let mut rows = vec![
+        columns
+            .iter()
+            .map(|c| c.name())
+            .collect::<Vec<_>>()
+            .join(","),
+    ];
+    for (i, segment) in segments.iter().enumerate() {
+        rows.push(
+            columns
+                .iter()
+                .map(|c| csv_field(&c.value(segment, i)))
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+    }
+    rows.join("\r\n")
+}
+
+/// Format segments as TSV with a header row.
+///
+/// # Example
+/// ```
+/// use voxtus::formats::{Segment, csv::{CsvColumn, format_tsv}};
+///
+/// let segments = vec![Segment::new(0.0, 1.0, "Hi\tthere")];
+/// assert_eq!(
+///     format_tsv(&segments, &[CsvColumn::Start, CsvColumn::Text]),
+///     "start\ttext\n0.000\tHi there"
+/// );
+/// ```
+pub fn format_tsv(segments: &[Segment], columns: &[CsvColumn]) -> String {
+    let mut rows = vec![
+        columns
+            .iter()
+            .map(|c| c.name())
+            .collect::<Vec<_>>()
+            .join("\t"),
+    ];
+    for (i, segment) in segments.iter().enumerate() {
+        rows.push(
+            columns
+                .iter()
+                .map(|c| tsv_field(&c.value(segment, i)))
+                .collect::<Vec<_>>()
+                .join("\t"),
+        );
+    }
+    rows.join("\n")
+}
+
+/// CSV output as a [`Formatter`].
+#[derive(Debug, Clone)]
+pub struct CsvFormatter {
+    pub columns: Vec<CsvColumn>,
+}
+
+impl Default for CsvFormatter {
+    fn default() -> Self {
+        Self {
+            columns: DEFAULT_COLUMNS.to_vec(),
+        }
+    }
+}
+
+impl Formatter for CsvFormatter {
+    fn name(&self) -> &str {
+        "csv"
+    }
+
+    fn extension(&self) -> &str {
+        "csv"
+    }
+
+    fn mime_type(&self) -> &str {
+        "text/csv"
+    }
+
+    fn description(&self) -> &str {
+        "One row per segment, for spreadsheets"
+    }
+
+    fn format(&self, transcript: &Transcript, _context: &FormatContext) -> String {
+        format_csv(&transcript.segments, &self.columns)
+    }
+}
+
+/// TSV output as a [`Formatter`].
+#[derive(Debug, Clone)]
+pub struct TsvFormatter {
+    pub columns: Vec<CsvColumn>,
+}
+
+impl Default for TsvFormatter {
+    fn default() -> Self {
+        Self {
+            columns: DEFAULT_COLUMNS.to_vec(),
+        }
+    }
+}
+
+impl Formatter for TsvFormatter {
+    fn name(&self) -> &str {
+        "tsv"
+    }
+
+    fn extension(&self) -> &str {
+        "tsv"
+    }
+
+    fn mime_type(&self) -> &str {
+        "text/tab-separated-values"
+    }
+
+    fn description(&self) -> &str {
+        "Tab-separated rows, one per segment"
+    }
+
+    fn format(&self, transcript: &Transcript, _context: &FormatContext) -> String {
+        format_tsv(&transcript.segments, &self.columns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field_escapes_newlines() {
+        assert_eq!(csv_field("a\nb"), "\"a\nb\"");
+        assert_eq!(csv_field("a\r\nb"), "\"a\r\nb\"");
+    }
+
+    const ALL: &[CsvColumn] = &[
+        CsvColumn::Id,
+        CsvColumn::Start,
+        CsvColumn::End,
+        CsvColumn::Duration,
+        CsvColumn::Speaker,
+        CsvColumn::Text,
+        CsvColumn::Confidence,
+    ];
+
+    fn sample_segments() -> Vec<Segment> {
+        let mut segments = vec![
+            Segment::new(0.0, 2.5, "Hello, \"world\""),
+            Segment::new(2.5, 4.0, "Line one\nline two"),
+        ];
+        segments[0].speaker = Some("Alice".to_string());
+        segments[0].confidence = Some(0.91234);
+        segments
+    }
+
+    #[test]
+    fn test_format_csv_all_columns() {
+        let csv = format_csv(&sample_segments(), ALL);
+        assert_eq!(
+            csv,
+            "id,start,end,duration,speaker,text,confidence\r\n\
+             1,0.000,2.500,2.500,Alice,\"Hello, \"\"world\"\"\",0.912\r\n\
+             2,2.500,4.000,1.500,,\"Line one\nline two\","
+        );
+    }
+
+    #[test]
+    fn test_format_csv_no_segments() {
+        assert_eq!(format_csv(&[], &[CsvColumn::Text]), "text");
+    }
+
+    #[test]
+    fn test_format_tsv_all_columns() {
+        let tsv = format_tsv(&sample_segments(), ALL);
+        assert_eq!(
+            tsv,
+            "id\tstart\tend\tduration\tspeaker\ttext\tconfidence\n\
+             1\t0.000\t2.500\t2.500\tAlice\tHello, \"world\"\t0.912\n\
+             2\t2.500\t4.000\t1.500\t\tLine one line two\t"
+        );
+    }
+
+    #[test]
+    fn test_column_order_is_kept() {
+        let csv = format_csv(&sample_segments(), &[CsvColumn::Text, CsvColumn::Id]);
+        assert!(csv.starts_with("text,id\r\n\"Hello, \"\"world\"\"\",1\r\n"));
+    }
+
+    #[test]
+    fn test_parse_column() {
+        assert_eq!("ID".parse::<CsvColumn>().unwrap(), CsvColumn::Id);
+        assert_eq!(
+            "avg_confidence".parse::<CsvColumn>().unwrap(),
+            CsvColumn::Confidence
+        );
+        assert!(matches!(
+            "words".parse::<CsvColumn>(),
+            Err(Error::InvalidCsvColumn(_))
+        ));
+        for column in ALL {
+            assert_eq!(column.name().parse::<CsvColumn>().unwrap(), *column);
+        }
+    }
+}
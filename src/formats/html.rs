@@ -0,0 +1,436 @@
+// Voxtus - Transcribe YouTube videos and local media files to text
+// Copyright (C) 2024 Johan Thorén <johan@thoren.xyz>
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! HTML format output.
+//!
+//! A single standalone page for reading a transcript in a browser: metadata,
+//! a search box, speaker coloring and timestamps that seek an embedded
+//! player. Styles and scripts are inline, so the file works offline apart
+//! from the YouTube embed.
+
+use super::md::{format_timestamp, timestamp_url};
+use super::{FormatContext, Formatter, Metadata, Segment, Transcript, chapter_for};
+use crate::download::youtube::youtube_video_id;
+
+/// Extensions played with a `<video>` element rather than `<audio>`.
+const VIDEO_EXTENSIONS: &[&str] = &["mp4", "m4v", "mkv", "mov", "webm"];
+
+/// Number of distinct speaker colors before they repeat.
+const SPEAKER_COLORS: usize = 8;
+
+const STYLE: &str = r#"
+body { font-family: system-ui, sans-serif; line-height: 1.5; max-width: 50rem; margin: 0 auto; padding: 1rem; color: #222; background: #fff; }
+header { position: sticky; top: 0; background: #fff; padding-bottom: 0.5rem; border-bottom: 1px solid #ddd; }
+h1 { font-size: 1.5rem; margin: 0.5rem 0; }
+dl { display: grid; grid-template-columns: max-content 1fr; gap: 0 1rem; margin: 0 0 0.5rem; font-size: 0.9rem; }
+dt { font-weight: bold; }
+dd { margin: 0; overflow-wrap: anywhere; }
+#player { width: 100%; margin: 0.5rem 0; }
+iframe#player { aspect-ratio: 16 / 9; border: 0; }
+#search { width: 70%; padding: 0.3rem; font-size: 1rem; }
+#count { margin-left: 0.5rem; color: #666; }
+h2 { font-size: 1.2rem; margin-top: 1.5rem; }
+.segment { margin: 0.3rem 0; padding: 0.1rem 0.5rem; border-left: 4px solid transparent; }
+.segment.current { background: #fff6d5; }
+.time { font-family: monospace; color: #666; text-decoration: none; margin-right: 0.5rem; }
+.time:hover { text-decoration: underline; }
+.speaker { font-weight: bold; margin-right: 0.3rem; }
+.speaker-0 { border-color: #1f77b4; } .speaker-0 .speaker { color: #1f77b4; }
+.speaker-1 { border-color: #d62728; } .speaker-1 .speaker { color: #d62728; }
+.speaker-2 { border-color: #2ca02c; } .speaker-2 .speaker { color: #2ca02c; }
+.speaker-3 { border-color: #9467bd; } .speaker-3 .speaker { color: #9467bd; }
+.speaker-4 { border-color: #ff7f0e; } .speaker-4 .speaker { color: #ff7f0e; }
+.speaker-5 { border-color: #17becf; } .speaker-5 .speaker { color: #17becf; }
+.speaker-6 { border-color: #8c564b; } .speaker-6 .speaker { color: #8c564b; }
+.speaker-7 { border-color: #e377c2; } .speaker-7 .speaker { color: #e377c2; }
+"#;
+
+const SCRIPT: &str = r#"
+const player = document.getElementById('player');
+const segments = Array.from(document.querySelectorAll('.segment'));
+
+function seek(t) {
+  if (!player) return;
+  if (player.tagName === 'IFRAME') {
+    const send = (func, args) => player.contentWindow.postMessage(
+      JSON.stringify({ event: 'command', func: func, args: args }), '*');
+    send('seekTo', [t, true]);
+    send('playVideo', []);
+  } else {
+    player.currentTime = t;
+    player.play();
+  }
+}
+
+document.querySelectorAll('.time').forEach((link) => {
+  link.addEventListener('click', (event) => {
+    if (!player) return;
+    event.preventDefault();
+    seek(parseFloat(link.closest('.segment').dataset.start));
+  });
+});
+
+const search = document.getElementById('search');
+const count = document.getElementById('count');
+search.addEventListener('input', () => {
+  const query = search.value.trim().toLowerCase();
+  let shown = 0;
+  segments.forEach((segment) => {
+    const match = !query || segment.textContent.toLowerCase().includes(query);
+    segment.hidden = !match;
+    if (match) shown++;
+  });
+  count.textContent = query ? shown + ' / ' + segments.length : '';
+});
+
+if (player && player.tagName !== 'IFRAME') {
+  player.addEventListener('timeupdate', () => {
+    const t = player.currentTime;
+    segments.forEach((segment) => {
+      const start = parseFloat(segment.dataset.start);
+      const end = parseFloat(segment.dataset.end);
+      segment.classList.toggle('current', t >= start && t < end);
+    });
+  });
+}
+"#;
+
+/// Escape text for use in HTML content and attribute values.
+///
+/// # Example
+/// ```
+/// use voxtus::formats::html::escape;
+///
+/// assert_eq!(escape("<b>\"Tom\" & 'Jerry'</b>"), "&lt;b&gt;&quot;Tom&quot; &amp; &#39;Jerry&#39;&lt;/b&gt;");
+/// ```
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Percent-encode a relative file path for use as a URL.
+fn encode_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// Format the player: the media file when given, otherwise the YouTube
+/// embed for YouTube sources.
+///
+/// Returns `None` when there is nothing to play.
+pub fn format_player(metadata: &Metadata, media: Option<&str>) -> Option<String> {
+    if let Some(media) = media {
+        let extension = media.rsplit_once('.').map(|(_, e)| e.to_lowercase());
+        let element = match extension {
+            Some(e) if VIDEO_EXTENSIONS.contains(&e.as_str()) => "video",
+            _ => "audio",
+        };
+        return Some(format!(
+            "<{element} id=\"player\" controls preload=\"metadata\" src=\"{}\"></{element}>",
+            escape(&encode_path(media))
+        ));
+    }
+
+    let id = youtube_video_id(&metadata.source)?;
+    Some(format!(
+        "<iframe id=\"player\" src=\"https://www.youtube.com/embed/{}?enablejsapi=1\" \
+         allow=\"autoplay; encrypted-media\" allowfullscreen></iframe>",
+        escape(&id)
+    ))
+}
+
+/// Format metadata as a definition list.
+pub fn format_metadata(metadata: &Metadata) -> String {
+    let mut rows = Vec::new();
+
+    let source =
+        if metadata.source.starts_with("http://") || metadata.source.starts_with("https://") {
+            format!("<a href=\"{0}\">{0}</a>", escape(&metadata.source))
+        } else {
+            escape(&metadata.source)
+        };
+    rows.push(("Source", source));
+
+    if let Some(uploader) = metadata.uploader.as_ref().or(metadata.channel.as_ref()) {
+        rows.push(("Uploader", escape(uploader)));
+    }
+    if let Some(date) = &metadata.upload_date {
+        rows.push(("Date", escape(date)));
+    }
+    if let Some(duration) = metadata.duration {
+        rows.push(("Duration", format_timestamp(duration)));
+    }
+    if let Some(language) = &metadata.language {
+        rows.push(("Language", escape(language)));
+    }
+    rows.push(("Model", escape(&metadata.model)));
+
+    let rows: String = rows
+        .iter()
+        .map(|(label, value)| format!("<dt>{}</dt><dd>{}</dd>", label, value))
+        .collect();
+    format!("<dl>{}</dl>", rows)
+}
+
+/// Format a single segment as a paragraph.
+///
+/// `speaker` is the index of the segment's speaker in order of appearance,
+/// used for its color.
+///
+/// # Example
+/// ```
+/// use voxtus::formats::{Segment, html::format_segment};
+///
+/// let segment = Segment::new(65.0, 67.5, "Hello & welcome");
+/// assert_eq!(
+///     format_segment(&segment, None, "#"),
+///     "<p class=\"segment\" data-start=\"65.000\" data-end=\"67.500\">\
+///      <a class=\"time\" href=\"#\">01:05</a>\
+///      <span class=\"text\">Hello &amp; welcome</span></p>"
+/// );
+/// ```
+pub fn format_segment(segment: &Segment, speaker: Option<usize>, href: &str) -> String {
+    let class = match speaker {
+        Some(i) => format!("segment speaker-{}", i % SPEAKER_COLORS),
+        None => "segment".to_string(),
+    };
+    let label = match &segment.speaker {
+        Some(name) => format!("<span class=\"speaker\">{}</span>", escape(name)),
+        None => String::new(),
+    };
+
+    format!(
+        "<p class=\"{}\" data-start=\"{:.3}\" data-end=\"{:.3}\">\
+         <a class=\"time\" href=\"{}\">{}</a>{}<span class=\"text\">{}</span></p>",
+        class,
+        segment.start,
+        segment.end,
+        escape(href),
+        format_timestamp(segment.start),
+        label,
+        escape(segment.text.trim())
+    )
+}
+
+/// Format segments and metadata as a standalone HTML page.
+///
+/// `media` is the path of an audio or video file relative to the page.
+/// Without it, YouTube sources get an embedded player instead.
+pub fn format_transcript(segments: &[Segment], metadata: &Metadata, media: Option<&str>) -> String {
+    let chapters = &metadata.chapters;
+    let mut speakers: Vec<&str> = Vec::new();
+    let mut body = Vec::new();
+    let mut current = None;
+
+    for segment in segments {
+        let chapter = chapter_for(chapters, segment);
+        if chapter != current {
+            if let Some(i) = chapter {
+                body.push(format!("<h2>{}</h2>", escape(&chapters[i].title)));
+            }
+            current = chapter;
+        }
+
+        let speaker = segment.speaker.as_deref().map(|name| {
+            speakers.iter().position(|s| *s == name).unwrap_or_else(|| {
+                speakers.push(name);
+                speakers.len() - 1
+            })
+        });
+        let href = match media {
+            Some(_) => "#".to_string(),
+            None => timestamp_url(&metadata.source, segment.start).unwrap_or_else(|| "#".into()),
+        };
+        body.push(format_segment(segment, speaker, &href));
+    }
+
+    let language = metadata.language.as_deref().unwrap_or("und");
+    let player = format_player(metadata, media).unwrap_or_default();
+
+    format!(
+        "<!DOCTYPE html>\n\
+         <html lang=\"{lang}\">\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <meta name=\"generator\" content=\"voxtus\">\n\
+         <title>{title}</title>\n\
+         <style>{style}</style>\n\
+         </head>\n\
+         <body>\n\
+         <header>\n\
+         <h1>{title}</h1>\n\
+         {metadata}\n\
+         {player}\n\
+         <input id=\"search\" type=\"search\" placeholder=\"Search transcript\"><span id=\"count\"></span>\n\
+         </header>\n\
+         <main>\n\
+         {body}\n\
+         </main>\n\
+         <script>{script}</script>\n\
+         </body>\n\
+         </html>",
+        lang = escape(language),
+        title = escape(&metadata.title),
+        style = STYLE,
+        metadata = format_metadata(metadata),
+        player = player,
+        body = body.join("\n"),
+        script = SCRIPT,
+    )
+}
+
+/// HTML output as a [`Formatter`].
+pub struct HtmlFormatter;
+
+impl Formatter for HtmlFormatter {
+    fn name(&self) -> &str {
+        "html"
+    }
+
+    fn extension(&self) -> &str {
+        "html"
+    }
+
+    fn mime_type(&self) -> &str {
+        "text/html"
+    }
+
+    fn description(&self) -> &str {
+        "Standalone HTML viewer with search and playback"
+    }
+
+    fn format(&self, transcript: &Transcript, context: &FormatContext) -> String {
+        format_transcript(
+            &transcript.segments,
+            &transcript.metadata,
+            context.media.as_deref(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::Chapter;
+
+    fn sample_segments() -> Vec<Segment> {
+        vec![
+            Segment::new(0.0, 2.0, "Hello world"),
+            Segment::new(2.0, 4.0, "This is a test"),
+        ]
+    }
+
+    fn sample_metadata(source: &str) -> Metadata {
+        Metadata::new("Test <Video>", source, Some(4.0), "tiny", Some("en".into()))
+    }
+
+    #[test]
+    fn test_format_transcript_is_standalone() {
+        let html = format_transcript(&sample_segments(), &sample_metadata("talk.mp3"), None);
+        assert!(html.starts_with("<!DOCTYPE html>\n<html lang=\"en\">"));
+        assert!(html.ends_with("</html>"));
+        assert!(html.contains("<title>Test &lt;Video&gt;</title>"));
+        assert!(html.contains("<input id=\"search\""));
+        assert!(!html.contains("<script src"));
+        assert!(!html.contains("<link"));
+    }
+
+    #[test]
+    fn test_format_transcript_escapes_text() {
+        let segments = vec![Segment::new(0.0, 1.0, "<script>alert(1)</script>")];
+        let html = format_transcript(&segments, &sample_metadata("talk.mp3"), None);
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert_eq!(html.matches("<script>").count(), 1);
+    }
+
+    #[test]
+    fn test_player_for_kept_audio() {
+        let metadata = sample_metadata("https://youtu.be/dQw4w9WgXcQ");
+        let player = format_player(&metadata, Some("My Talk #1.m4a")).unwrap();
+        assert_eq!(
+            player,
+            "<audio id=\"player\" controls preload=\"metadata\" src=\"My%20Talk%20%231.m4a\"></audio>"
+        );
+
+        let player = format_player(&metadata, Some("talk.webm")).unwrap();
+        assert!(player.starts_with("<video "));
+    }
+
+    #[test]
+    fn test_player_for_youtube() {
+        let html = format_transcript(
+            &sample_segments(),
+            &sample_metadata("https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            None,
+        );
+        assert!(html.contains("src=\"https://www.youtube.com/embed/dQw4w9WgXcQ?enablejsapi=1\""));
+        assert!(html.contains("href=\"https://www.youtube.com/watch?v=dQw4w9WgXcQ&amp;t=2s\""));
+    }
+
+    #[test]
+    fn test_no_player_for_local_file() {
+        assert!(format_player(&sample_metadata("talk.mp3"), None).is_none());
+        let html = format_transcript(&sample_segments(), &sample_metadata("talk.mp3"), None);
+        assert!(!html.contains("id=\"player\""));
+    }
+
+    #[test]
+    fn test_speaker_colors() {
+        let mut segments = vec![
+            Segment::new(0.0, 1.0, "Hi"),
+            Segment::new(1.0, 2.0, "Hello"),
+            Segment::new(2.0, 3.0, "Bye"),
+        ];
+        segments[0].speaker = Some("Alice".into());
+        segments[1].speaker = Some("Bob".into());
+        segments[2].speaker = Some("Alice".into());
+
+        let html = format_transcript(&segments, &sample_metadata("talk.mp3"), None);
+        assert_eq!(html.matches("class=\"segment speaker-0\"").count(), 2);
+        assert_eq!(html.matches("class=\"segment speaker-1\"").count(), 1);
+        assert!(html.contains("<span class=\"speaker\">Bob</span>"));
+    }
+
+    #[test]
+    fn test_chapter_headings() {
+        let mut metadata = sample_metadata("talk.mp3");
+        metadata.chapters = vec![
+            Chapter::new(0.0, 2.0, "Intro"),
+            Chapter::new(2.0, 4.0, "Q&A"),
+        ];
+        let html = format_transcript(&sample_segments(), &metadata, None);
+        assert!(html.contains("<h2>Intro</h2>"));
+        assert!(html.contains("<h2>Q&amp;A</h2>"));
+    }
+
+    #[test]
+    fn test_format_metadata() {
+        let mut metadata = sample_metadata("https://example.com/a?b=1&c=2");
+        metadata.upload_date = Some("2024-05-01".into());
+        let html = format_metadata(&metadata);
+        assert!(html.contains(
+            "<dd><a href=\"https://example.com/a?b=1&amp;c=2\">https://example.com/a?b=1&amp;c=2</a></dd>"
+        ));
+        assert!(html.contains("<dt>Date</dt><dd>2024-05-01</dd>"));
+        assert!(html.contains("<dt>Duration</dt><dd>00:04</dd>"));
+    }
+}
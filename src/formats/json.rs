@@ -4,40 +4,147 @@
 
 //! JSON format output.
 //!
-//! Structured JSON format with transcript segments and metadata.
+//! Structured JSON format with transcript segments and metadata. The output
+//! carries a `schema_version` and is described by [`json_schema`].
+//!
+//! With [`JsonCompat::OpenAi`] the output has the shape of the OpenAI
+//! Whisper API `verbose_json` response instead, for tools that expect it.
 
+use schemars::JsonSchema;
+use schemars::generate::SchemaSettings;
 use serde::Serialize;
+use serde_json::Value;
+
+use super::{
+    Chapter, FormatContext, Formatter, Metadata, Segment, Transcript, TranscriptSource, chapter_for,
+};
+use crate::error::{Error, Result};
+
+/// Version of the JSON output schema.
+///
+/// The minor version is bumped when fields are added, and the major version
+/// when fields change meaning or are removed. The schema allows fields it
+/// doesn't declare, so output with added fields validates against the schema
+/// of an older minor version.
+pub const JSON_SCHEMA_VERSION: &str = "1.0";
+
+/// Shape of JSON output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsonCompat {
+    /// Voxtus JSON, described by [`json_schema`].
+    #[default]
+    Voxtus,
+    /// OpenAI Whisper API `verbose_json`.
+    OpenAi,
+}
 
-use super::{Metadata, Segment};
+impl std::str::FromStr for JsonCompat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "voxtus" | "none" => Ok(Self::Voxtus),
+            "openai" => Ok(Self::OpenAi),
+            _ => Err(Error::InvalidJsonCompat(s.to_string())),
+        }
+    }
+}
 
 /// A segment in JSON output format.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
+#[schemars(rename = "segment")]
 struct JsonSegment {
+    #[schemars(range(min = 1))]
     id: usize,
+    #[schemars(range(min = 0.0))]
     start: f64,
+    #[schemars(range(min = 0.0))]
     end: f64,
     text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    speaker: Option<String>,
+    /// ID of the chapter the segment belongs to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 1))]
+    chapter: Option<usize>,
+}
+
+/// A chapter in JSON output format.
+#[derive(Debug, Serialize, JsonSchema)]
+#[schemars(rename = "chapter")]
+struct JsonChapter {
+    #[schemars(range(min = 1))]
+    id: usize,
+    #[schemars(range(min = 0.0))]
+    start: f64,
+    #[schemars(range(min = 0.0))]
+    end: f64,
+    title: String,
+    /// IDs of the segments in this chapter.
+    #[schemars(inner(range(min = 1)))]
+    segments: Vec<usize>,
+}
+
+/// What made the JSON output, and how the transcript was made.
+#[derive(Debug, Serialize, JsonSchema)]
+#[schemars(rename = "generator")]
+struct JsonGenerator {
+    name: &'static str,
+    version: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(extend("format" = "date-time"))]
+    created_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    decoding_options: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(regex(pattern = r"^fnv1a64:[0-9a-f]{16}$"))]
+    audio_hash: Option<String>,
 }
 
 /// JSON output structure.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
+#[schemars(title = "Voxtus transcript", description = "JSON output of voxtus")]
 struct JsonOutput {
+    #[schemars(extend("const" = JSON_SCHEMA_VERSION))]
+    schema_version: &'static str,
+    generator: JsonGenerator,
     transcript: Vec<JsonSegment>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    chapters: Vec<JsonChapter>,
     metadata: JsonMetadata,
 }
 
 /// Metadata in JSON output format.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
+#[schemars(rename = "metadata")]
 struct JsonMetadata {
     title: String,
     source: String,
+    #[schemars(range(min = 0.0))]
     duration: Option<f64>,
     model: String,
-    language: String,
+    /// Language code, `null` when unknown.
+    language: Option<String>,
+    transcript_source: TranscriptSource,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uploader: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    channel: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(regex(pattern = r"^\d{4}-\d{2}-\d{2}$"))]
+    upload_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    audio_filters: Option<String>,
 }
 
 /// Convert segments to JSON segment format.
-fn to_json_segments(segments: &[Segment]) -> Vec<JsonSegment> {
+fn to_json_segments(segments: &[Segment], chapters: &[Chapter]) -> Vec<JsonSegment> {
     segments
         .iter()
         .enumerate()
@@ -46,6 +153,27 @@ fn to_json_segments(segments: &[Segment]) -> Vec<JsonSegment> {
             start: s.start,
             end: s.end,
             text: s.text.clone(),
+            speaker: s.speaker.clone(),
+            chapter: chapter_for(chapters, s).map(|c| c + 1),
+        })
+        .collect()
+}
+
+/// Convert chapters to JSON chapter format, listing their segment IDs.
+fn to_json_chapters(chapters: &[Chapter], segments: &[JsonSegment]) -> Vec<JsonChapter> {
+    chapters
+        .iter()
+        .enumerate()
+        .map(|(i, c)| JsonChapter {
+            id: i + 1,
+            start: c.start,
+            end: c.end,
+            title: c.title.clone(),
+            segments: segments
+                .iter()
+                .filter(|s| s.chapter == Some(i + 1))
+                .map(|s| s.id)
+                .collect(),
         })
         .collect()
 }
@@ -57,10 +185,15 @@ fn to_json_metadata(metadata: &Metadata) -> JsonMetadata {
         source: metadata.source.clone(),
         duration: metadata.duration,
         model: metadata.model.clone(),
-        language: metadata
-            .language
-            .clone()
-            .unwrap_or_else(|| "en".to_string()),
+        language: metadata.language.clone(),
+        transcript_source: metadata.transcript_source,
+        id: metadata.id.clone(),
+        uploader: metadata.uploader.clone(),
+        channel: metadata.channel.clone(),
+        upload_date: metadata.upload_date.clone(),
+        description: metadata.description.clone(),
+        tags: metadata.tags.clone(),
+        audio_filters: metadata.audio_filters.clone(),
     }
 }
 
@@ -76,18 +209,296 @@ fn to_json_metadata(metadata: &Metadata) -> JsonMetadata {
 /// assert!(json.contains("\"transcript\""));
 /// ```
 pub fn format_transcript(segments: &[Segment], metadata: &Metadata) -> String {
+    let transcript = to_json_segments(segments, &metadata.chapters);
     let output = JsonOutput {
-        transcript: to_json_segments(segments),
+        schema_version: JSON_SCHEMA_VERSION,
+        generator: JsonGenerator {
+            name: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+            created_at: metadata.created_at.clone(),
+            decoding_options: metadata.decoding_options.clone(),
+            audio_hash: metadata.audio_hash.clone(),
+        },
+        chapters: to_json_chapters(&metadata.chapters, &transcript),
+        transcript,
         metadata: to_json_metadata(metadata),
     };
 
     serde_json::to_string_pretty(&output).unwrap_or_else(|_| "{}".to_string())
 }
 
+/// JSON Schema (draft 2020-12) of the JSON output.
+///
+/// Derived from the output types above; `schema/transcript.schema.json`
+/// in the repository is this schema, and `voxtus --json-schema` prints it.
+///
+/// # Example
+/// ```
+/// use voxtus::formats::json::{JSON_SCHEMA_VERSION, json_schema};
+///
+/// let schema = json_schema();
+/// assert_eq!(schema["properties"]["schema_version"]["const"], JSON_SCHEMA_VERSION);
+/// ```
+pub fn json_schema() -> Value {
+    // Settings for serialization, so that fields skipped when empty are
+    // optional and `Option` fields that are always written are required
+    SchemaSettings::draft2020_12()
+        .for_serialize()
+        .into_generator()
+        .into_root_schema_for::<JsonOutput>()
+        .to_value()
+}
+
+/// Language names of OpenAI Whisper, by language code.
+pub const WHISPER_LANGUAGES: &[(&str, &str)] = &[
+    ("en", "english"),
+    ("zh", "chinese"),
+    ("de", "german"),
+    ("es", "spanish"),
+    ("ru", "russian"),
+    ("ko", "korean"),
+    ("fr", "french"),
+    ("ja", "japanese"),
+    ("pt", "portuguese"),
+    ("tr", "turkish"),
+    ("pl", "polish"),
+    ("ca", "catalan"),
+    ("nl", "dutch"),
+    ("ar", "arabic"),
+    ("sv", "swedish"),
+    ("it", "italian"),
+    ("id", "indonesian"),
+    ("hi", "hindi"),
+    ("fi", "finnish"),
+    ("vi", "vietnamese"),
+    ("he", "hebrew"),
+    ("uk", "ukrainian"),
+    ("el", "greek"),
+    ("ms", "malay"),
+    ("cs", "czech"),
+    ("ro", "romanian"),
+    ("da", "danish"),
+    ("hu", "hungarian"),
+    ("ta", "tamil"),
+    ("no", "norwegian"),
+    ("th", "thai"),
+    ("ur", "urdu"),
+    ("hr", "croatian"),
+    ("bg", "bulgarian"),
+    ("lt", "lithuanian"),
+    ("la", "latin"),
+    ("mi", "maori"),
+    ("ml", "malayalam"),
+    ("cy", "welsh"),
+    ("sk", "slovak"),
+    ("te", "telugu"),
+    ("fa", "persian"),
+    ("lv", "latvian"),
+    ("bn", "bengali"),
+    ("sr", "serbian"),
+    ("az", "azerbaijani"),
+    ("sl", "slovenian"),
+    ("kn", "kannada"),
+    ("et", "estonian"),
+    ("mk", "macedonian"),
+    ("br", "breton"),
+    ("eu", "basque"),
+    ("is", "icelandic"),
+    ("hy", "armenian"),
+    ("ne", "nepali"),
+    ("mn", "mongolian"),
+    ("bs", "bosnian"),
+    ("kk", "kazakh"),
+    ("sq", "albanian"),
+    ("sw", "swahili"),
+    ("gl", "galician"),
+    ("mr", "marathi"),
+    ("pa", "punjabi"),
+    ("si", "sinhala"),
+    ("km", "khmer"),
+    ("sn", "shona"),
+    ("yo", "yoruba"),
+    ("so", "somali"),
+    ("af", "afrikaans"),
+    ("oc", "occitan"),
+    ("ka", "georgian"),
+    ("be", "belarusian"),
+    ("tg", "tajik"),
+    ("sd", "sindhi"),
+    ("gu", "gujarati"),
+    ("am", "amharic"),
+    ("yi", "yiddish"),
+    ("lo", "lao"),
+    ("uz", "uzbek"),
+    ("fo", "faroese"),
+    ("ht", "haitian creole"),
+    ("ps", "pashto"),
+    ("tk", "turkmen"),
+    ("nn", "nynorsk"),
+    ("mt", "maltese"),
+    ("sa", "sanskrit"),
+    ("lb", "luxembourgish"),
+    ("my", "myanmar"),
+    ("bo", "tibetan"),
+    ("tl", "tagalog"),
+    ("mg", "malagasy"),
+    ("as", "assamese"),
+    ("tt", "tatar"),
+    ("haw", "hawaiian"),
+    ("ln", "lingala"),
+    ("ha", "hausa"),
+    ("ba", "bashkir"),
+    ("jw", "javanese"),
+    ("su", "sundanese"),
+    ("yue", "cantonese"),
+];
+
+/// A segment of OpenAI `verbose_json` output.
+#[derive(Debug, Serialize)]
+struct OpenAiSegment {
+    id: usize,
+    seek: u64,
+    start: f64,
+    end: f64,
+    text: String,
+    tokens: Vec<u32>,
+    temperature: f64,
+    avg_logprob: f64,
+    compression_ratio: f64,
+    no_speech_prob: f64,
+}
+
+/// A word of OpenAI `verbose_json` output.
+#[derive(Debug, Serialize)]
+struct OpenAiWord {
+    word: String,
+    start: f64,
+    end: f64,
+}
+
+/// OpenAI `verbose_json` output structure.
+#[derive(Debug, Serialize)]
+struct OpenAiOutput {
+    task: &'static str,
+    language: Option<String>,
+    duration: f64,
+    text: String,
+    segments: Vec<OpenAiSegment>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    words: Vec<OpenAiWord>,
+}
+
+/// Format segments and metadata as OpenAI Whisper API `verbose_json`.
+///
+/// The language is the Whisper language name, e.g. `english`. Fields
+/// voxtus does not track get neutral values: `seek` is 0, `tokens` is
+/// empty, `compression_ratio` is 1 and `no_speech_prob` is 0. Decoding is
+/// greedy, so `temperature` is 0, and `avg_logprob` is the log of the
+/// segment confidence when it is known. Word timings become a top-level
+/// `words` list, as with `timestamp_granularities=word`.
+///
+/// # Example
+/// ```
+/// use voxtus::formats::{Segment, Metadata, json::format_openai};
+///
+/// let segments = vec![Segment::new(0.0, 2.0, " Hello there ")];
+/// let metadata = Metadata::new("Test", "test.mp3", None, "tiny", Some("en".to_string()));
+/// let json: serde_json::Value = serde_json::from_str(&format_openai(&segments, &metadata)).unwrap();
+/// assert_eq!(json["language"], "english");
+/// assert_eq!(json["duration"], 2.0);
+/// assert_eq!(json["text"], "Hello there");
+/// ```
+pub fn format_openai(segments: &[Segment], metadata: &Metadata) -> String {
+    let language = metadata.language.as_deref().map(|code| {
+        WHISPER_LANGUAGES
+            .iter()
+            .find(|(c, _)| c.eq_ignore_ascii_case(code))
+            .map_or(code, |(_, name)| name)
+            .to_string()
+    });
+    let duration = metadata
+        .duration
+        .or_else(|| segments.last().map(|s| s.end))
+        .unwrap_or(0.0);
+    let text = segments
+        .iter()
+        .map(|s| s.text.trim())
+        .filter(|t| !t.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let output = OpenAiOutput {
+        task: "transcribe",
+        language,
+        duration,
+        text,
+        segments: segments
+            .iter()
+            .enumerate()
+            .map(|(i, s)| OpenAiSegment {
+                id: i,
+                seek: 0,
+                start: s.start,
+                end: s.end,
+                text: s.text.trim().to_string(),
+                tokens: Vec::new(),
+                temperature: 0.0,
+                avg_logprob: s.confidence.map_or(0.0, |c| c.max(f64::MIN_POSITIVE).ln()),
+                compression_ratio: 1.0,
+                no_speech_prob: 0.0,
+            })
+            .collect(),
+        words: segments
+            .iter()
+            .flat_map(|s| &s.words)
+            .map(|w| OpenAiWord {
+                word: w.text.trim().to_string(),
+                start: w.start,
+                end: w.end,
+            })
+            .collect(),
+    };
+
+    serde_json::to_string_pretty(&output).unwrap_or_else(|_| "{}".to_string())
+}
+
+/// JSON output as a [`Formatter`].
+#[derive(Debug, Clone, Default)]
+pub struct JsonFormatter {
+    pub compat: JsonCompat,
+}
+
+impl Formatter for JsonFormatter {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn extension(&self) -> &str {
+        "json"
+    }
+
+    fn mime_type(&self) -> &str {
+        "application/json"
+    }
+
+    fn description(&self) -> &str {
+        "Segments and metadata as versioned JSON, or OpenAI verbose_json"
+    }
+
+    fn format(&self, transcript: &Transcript, _context: &FormatContext) -> String {
+        match self.compat {
+            JsonCompat::Voxtus => format_transcript(&transcript.segments, &transcript.metadata),
+            JsonCompat::OpenAi => format_openai(&transcript.segments, &transcript.metadata),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use serde_json::json;
+
     fn sample_segments() -> Vec<Segment> {
         vec![
             Segment::new(0.0, 2.0, "Segment 1 text"),
@@ -151,6 +562,41 @@ mod tests {
         assert_eq!(metadata.get("duration").unwrap().as_f64().unwrap(), 6.0);
         assert_eq!(metadata.get("model").unwrap().as_str().unwrap(), "base");
         assert_eq!(metadata.get("language").unwrap().as_str().unwrap(), "en");
+        assert_eq!(metadata["transcript_source"], "whisper");
+    }
+
+    #[test]
+    fn test_json_transcript_source() {
+        let mut metadata = sample_metadata();
+        metadata.transcript_source = TranscriptSource::AutoCaptions;
+
+        let json = format_transcript(&sample_segments(), &metadata);
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["metadata"]["transcript_source"], "auto_captions");
+    }
+
+    #[test]
+    fn test_json_audio_filters() {
+        let json = format_transcript(&sample_segments(), &sample_metadata());
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(parsed["metadata"].get("audio_filters").is_none());
+
+        let mut metadata = sample_metadata();
+        metadata.audio_filters = Some("loudnorm".to_string());
+        let json = format_transcript(&sample_segments(), &metadata);
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["metadata"]["audio_filters"], "loudnorm");
+    }
+
+    #[test]
+    fn test_json_speaker() {
+        let mut segments = sample_segments();
+        segments[1].speaker = Some("Alice".to_string());
+
+        let json = format_transcript(&segments, &sample_metadata());
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(parsed["transcript"][0].get("speaker").is_none());
+        assert_eq!(parsed["transcript"][1]["speaker"], "Alice");
     }
 
     #[test]
@@ -199,23 +645,78 @@ mod tests {
     }
 
     #[test]
-    fn test_json_default_language() {
+    fn test_json_unknown_language_is_null() {
         let segments = vec![Segment::new(0.0, 5.0, "Test")];
         let metadata = Metadata::new("Test", "test.mp3", Some(5.0), "tiny", None);
 
         let json = format_transcript(&segments, &metadata);
         let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
 
-        assert_eq!(
-            parsed
-                .get("metadata")
-                .unwrap()
-                .get("language")
-                .unwrap()
-                .as_str()
-                .unwrap(),
-            "en"
-        );
+        assert!(parsed["metadata"]["language"].is_null());
+    }
+
+    #[test]
+    fn test_json_video_metadata() {
+        let mut metadata = sample_metadata();
+        metadata.id = Some("abc123".to_string());
+        metadata.uploader = Some("Uploader".to_string());
+        metadata.channel = Some("Channel".to_string());
+        metadata.upload_date = Some("2024-01-31".to_string());
+        metadata.description = Some("Description".to_string());
+        metadata.tags = vec!["one".to_string(), "two".to_string()];
+
+        let json = format_transcript(&sample_segments(), &metadata);
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let metadata = parsed.get("metadata").unwrap();
+
+        assert_eq!(metadata["id"], "abc123");
+        assert_eq!(metadata["uploader"], "Uploader");
+        assert_eq!(metadata["channel"], "Channel");
+        assert_eq!(metadata["upload_date"], "2024-01-31");
+        assert_eq!(metadata["description"], "Description");
+        assert_eq!(metadata["tags"], serde_json::json!(["one", "two"]));
+    }
+
+    #[test]
+    fn test_json_chapters() {
+        let mut metadata = sample_metadata();
+        metadata.chapters = vec![
+            Chapter::new(0.0, 2.0, "Intro"),
+            Chapter::new(2.0, 6.0, "Main"),
+        ];
+
+        let json = format_transcript(&sample_segments(), &metadata);
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        let chapters = parsed["chapters"].as_array().unwrap();
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[0]["id"], 1);
+        assert_eq!(chapters[0]["title"], "Intro");
+        assert_eq!(chapters[0]["segments"], serde_json::json!([1]));
+        assert_eq!(chapters[1]["start"], 2.0);
+        assert_eq!(chapters[1]["segments"], serde_json::json!([2, 3]));
+
+        let transcript = parsed["transcript"].as_array().unwrap();
+        assert_eq!(transcript[0]["chapter"], 1);
+        assert_eq!(transcript[2]["chapter"], 2);
+    }
+
+    #[test]
+    fn test_json_omits_missing_video_metadata() {
+        let json = format_transcript(&sample_segments(), &sample_metadata());
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let metadata = parsed.get("metadata").unwrap();
+
+        for key in [
+            "id",
+            "uploader",
+            "channel",
+            "upload_date",
+            "tags",
+            "chapters",
+        ] {
+            assert!(metadata.get(key).is_none(), "unexpected key {}", key);
+        }
     }
 
     #[test]
@@ -235,4 +736,250 @@ mod tests {
         let transcript = parsed.get("transcript").unwrap().as_array().unwrap();
         assert!(transcript.is_empty());
     }
+
+    #[test]
+    fn test_json_schema_version_and_generator() {
+        let json = format_transcript(&sample_segments(), &sample_metadata());
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed["schema_version"], JSON_SCHEMA_VERSION);
+        assert_eq!(parsed["generator"]["name"], "voxtus");
+        assert_eq!(parsed["generator"]["version"], env!("CARGO_PKG_VERSION"));
+        assert!(parsed["generator"].get("audio_hash").is_none());
+
+        let mut metadata = sample_metadata();
+        metadata.created_at = Some("2024-01-31T12:00:00Z".to_string());
+        metadata.decoding_options = Some("greedy".to_string());
+        metadata.audio_hash = Some("fnv1a64:0123456789abcdef".to_string());
+        let json = format_transcript(&sample_segments(), &metadata);
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed["generator"]["created_at"], "2024-01-31T12:00:00Z");
+        assert_eq!(parsed["generator"]["decoding_options"], "greedy");
+        assert_eq!(
+            parsed["generator"]["audio_hash"],
+            "fnv1a64:0123456789abcdef"
+        );
+        assert!(parsed["metadata"].get("audio_hash").is_none());
+    }
+
+    /// Check a value against the parts of JSON Schema that [`json_schema`]
+    /// uses, returning the path of the first mismatch.
+    fn check(
+        schema: &Value,
+        root: &Value,
+        value: &Value,
+        path: &str,
+    ) -> std::result::Result<(), String> {
+        if let Some(reference) = schema["$ref"].as_str() {
+            let name = reference.trim_start_matches("#/$defs/");
+            return check(&root["$defs"][name], root, value, path);
+        }
+        let fail = |what: &str| Err(format!("{}: {}", path, what));
+
+        if let Some(types) = schema.get("type") {
+            let types: Vec<&str> = match types {
+                Value::Array(list) => list.iter().filter_map(Value::as_str).collect(),
+                other => vec![other.as_str().unwrap()],
+            };
+            let matches = types.iter().any(|t| match *t {
+                "object" => value.is_object(),
+                "array" => value.is_array(),
+                "string" => value.is_string(),
+                "number" => value.is_number(),
+                "integer" => value.is_u64() || value.is_i64(),
+                "null" => value.is_null(),
+                _ => false,
+            });
+            if !matches {
+                return fail(&format!("expected {:?}, got {}", types, value));
+            }
+        }
+        if let Some(expected) = schema.get("const")
+            && value != expected
+        {
+            return fail("const mismatch");
+        }
+        if let Some(options) = schema["enum"].as_array()
+            && !options.contains(value)
+        {
+            return fail("not in enum");
+        }
+        if let Some(options) = schema["oneOf"].as_array() {
+            let matching = options
+                .iter()
+                .filter(|option| check(option, root, value, path).is_ok())
+                .count();
+            if matching != 1 {
+                return fail(&format!("matches {} of oneOf", matching));
+            }
+        }
+        if let (Some(minimum), Some(number)) = (schema["minimum"].as_f64(), value.as_f64())
+            && number < minimum
+        {
+            return fail(&format!("below minimum {}", minimum));
+        }
+        if let Some(object) = value.as_object() {
+            for key in schema["required"].as_array().into_iter().flatten() {
+                if !object.contains_key(key.as_str().unwrap()) {
+                    return fail(&format!("missing {}", key));
+                }
+            }
+            for (key, field) in object {
+                match schema["properties"].get(key) {
+                    Some(property) => check(property, root, field, &format!("{}.{}", path, key))?,
+                    None if schema["additionalProperties"] == false => {
+                        return fail(&format!("undeclared {}", key));
+                    }
+                    None => {}
+                }
+            }
+        }
+        if let (Some(items), Some(list)) = (schema.get("items"), value.as_array()) {
+            for (i, item) in list.iter().enumerate() {
+                check(items, root, item, &format!("{}[{}]", path, i))?;
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_output_matches_schema() {
+        let schema = json_schema();
+        let mut segments = sample_segments();
+        segments[0].speaker = Some("Alice".to_string());
+        let mut metadata = sample_metadata();
+        metadata.id = Some("abc123".to_string());
+        metadata.uploader = Some("Uploader".to_string());
+        metadata.channel = Some("Channel".to_string());
+        metadata.upload_date = Some("2024-01-31".to_string());
+        metadata.description = Some("Description".to_string());
+        metadata.tags = vec!["one".to_string()];
+        metadata.chapters = vec![Chapter::new(0.0, 6.0, "All")];
+        metadata.audio_filters = Some("loudnorm".to_string());
+        metadata.created_at = Some("2024-01-31T12:00:00Z".to_string());
+        metadata.decoding_options = Some("greedy".to_string());
+        metadata.audio_hash = Some("fnv1a64:0123456789abcdef".to_string());
+
+        for (segments, metadata) in [
+            (segments, metadata),
+            (
+                Vec::new(),
+                Metadata::new("Empty", "a.mp3", None, "tiny", None),
+            ),
+        ] {
+            let parsed: Value =
+                serde_json::from_str(&format_transcript(&segments, &metadata)).unwrap();
+            check(&schema, &schema, &parsed, "$").unwrap();
+        }
+
+        // Fields added in a later minor version still validate
+        let mut parsed: Value =
+            serde_json::from_str(&format_transcript(&sample_segments(), &sample_metadata()))
+                .unwrap();
+        parsed["metadata"]["extra"] = json!(1);
+        assert!(check(&schema, &schema, &parsed, "$").is_ok());
+
+        // The checker itself rejects mistyped and out of range values
+        let mut mistyped = parsed.clone();
+        mistyped["transcript"][0]["id"] = json!("1");
+        assert!(check(&schema, &schema, &mistyped, "$").is_err());
+        let mut mistyped = parsed.clone();
+        mistyped["transcript"][0]["id"] = json!(0);
+        assert!(check(&schema, &schema, &mistyped, "$").is_err());
+        parsed["metadata"]["transcript_source"] = json!("guess");
+        assert!(check(&schema, &schema, &parsed, "$").is_err());
+    }
+
+    #[test]
+    fn test_published_schema_is_current() {
+        let published: Value =
+            serde_json::from_str(include_str!("../../schema/transcript.schema.json")).unwrap();
+        assert_eq!(
+            published,
+            json_schema(),
+            "regenerate schema/transcript.schema.json with `voxtus --json-schema`"
+        );
+    }
+
+    #[test]
+    fn test_parse_json_compat() {
+        assert_eq!("OpenAI".parse::<JsonCompat>().unwrap(), JsonCompat::OpenAi);
+        assert_eq!("voxtus".parse::<JsonCompat>().unwrap(), JsonCompat::Voxtus);
+        assert!(matches!(
+            "srt".parse::<JsonCompat>(),
+            Err(Error::InvalidJsonCompat(_))
+        ));
+    }
+
+    #[test]
+    fn test_openai_segments_and_words() {
+        let mut segments = sample_segments();
+        segments[0].confidence = Some(0.5);
+        segments[0].words = vec![
+            crate::formats::Word::new(0.0, 1.0, " Segment"),
+            crate::formats::Word::new(1.0, 2.0, "1"),
+        ];
+        let mut metadata = sample_metadata();
+        metadata.language = Some("sv".to_string());
+
+        let parsed: Value = serde_json::from_str(&format_openai(&segments, &metadata)).unwrap();
+        assert_eq!(parsed["task"], "transcribe");
+        assert_eq!(parsed["language"], "swedish");
+        assert_eq!(parsed["duration"], 6.0);
+        assert_eq!(
+            parsed["text"],
+            "Segment 1 text Segment 2 text Segment 3 text"
+        );
+
+        let first = &parsed["segments"][0];
+        assert_eq!(first["id"], 0);
+        assert_eq!(first["text"], "Segment 1 text");
+        assert_eq!(first["tokens"], json!([]));
+        assert_eq!(first["temperature"], 0.0);
+        assert!((first["avg_logprob"].as_f64().unwrap() - 0.5f64.ln()).abs() < 1e-9);
+        assert_eq!(parsed["segments"][1]["avg_logprob"], 0.0);
+
+        assert_eq!(
+            parsed["words"],
+            json!([
+                {"word": "Segment", "start": 0.0, "end": 1.0},
+                {"word": "1", "start": 1.0, "end": 2.0},
+            ])
+        );
+    }
+
+    #[test]
+    fn test_openai_without_words_or_language() {
+        let mut metadata = sample_metadata();
+        metadata.language = None;
+        metadata.duration = None;
+
+        let parsed: Value =
+            serde_json::from_str(&format_openai(&sample_segments(), &metadata)).unwrap();
+        assert!(parsed["language"].is_null());
+        assert_eq!(parsed["duration"], 6.0);
+        assert!(parsed.get("words").is_none());
+
+        metadata.language = Some("xx".to_string());
+        let parsed: Value =
+            serde_json::from_str(&format_openai(&sample_segments(), &metadata)).unwrap();
+        assert_eq!(parsed["language"], "xx");
+    }
+
+    #[test]
+    fn test_formatter_json_compat() {
+        let transcript = Transcript::new(sample_segments(), sample_metadata());
+        let context = FormatContext::default();
+        let mut formatter = JsonFormatter::default();
+        assert_eq!(
+            formatter.format(&transcript, &context),
+            format_transcript(&transcript.segments, &transcript.metadata)
+        );
+        formatter.compat = JsonCompat::OpenAi;
+        assert_eq!(
+            formatter.format(&transcript, &context),
+            format_openai(&transcript.segments, &transcript.metadata)
+        );
+    }
 }
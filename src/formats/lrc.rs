@@ -0,0 +1,275 @@
+// Voxtus - Transcribe YouTube videos and local media files to text
+// Copyright (C) 2024 Johan Thorén <johan@thoren.xyz>
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! LRC (lyrics) format output.
+//!
+//! Line-synchronized lyrics format used by music players.
+//! Uses minutes and centiseconds: `[mm:ss.xx]`
+//!
+//! Enhanced LRC adds a `<mm:ss.xx>` tag before each word, from word timings.
+
+use super::{FormatContext, Formatter, Metadata, Segment, Transcript};
+
+/// Format seconds as LRC timestamp (mm:ss.xx), without brackets.
+///
+/// Minutes are not wrapped into hours, since LRC has no hour field.
+///
+/// # Example
+/// ```
+/// use voxtus::formats::lrc::format_timestamp;
+///
+/// assert_eq!(format_timestamp(0.0), "00:00.00");
+/// assert_eq!(format_timestamp(65.5), "01:05.50");
+/// assert_eq!(format_timestamp(3661.123), "61:01.12");
+/// ```
+pub fn format_timestamp(seconds: f64) -> String {
+    let seconds = seconds.max(0.0);
+    let total_seconds = seconds.floor() as u64;
+    let minutes = total_seconds / 60;
+    let secs = total_seconds % 60;
+
+    // Calculate centiseconds, clamping to 99 to handle rounding
+    let centiseconds = ((seconds.fract() * 100.0).round() as u64).min(99);
+
+    format!("{:02}:{:02}.{:02}", minutes, secs, centiseconds)
+}
+
+/// Make text fit on a single lyrics line.
+fn line_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Format a single segment as an LRC line.
+///
+/// With `words`, each word timing becomes a `<mm:ss.xx>` tag. Segments
+/// without word timings are written as plain lines.
+///
+/// # Example
+/// ```
+/// use voxtus::formats::{Segment, Word, lrc::format_segment};
+///
+/// let mut segment = Segment::new(12.0, 14.0, "Hello world");
+/// assert_eq!(format_segment(&segment, false), "[00:12.00]Hello world");
+///
+/// segment.words = vec![Word::new(12.0, 12.5, "Hello"), Word::new(12.6, 14.0, "world")];
+/// assert_eq!(
+///     format_segment(&segment, true),
+///     "[00:12.00]<00:12.00>Hello <00:12.60>world <00:14.00>"
+/// );
+/// ```
+pub fn format_segment(segment: &Segment, words: bool) -> String {
+    let time = format_timestamp(segment.start);
+    if !words || segment.words.is_empty() {
+        return format!("[{}]{}", time, line_text(&segment.text));
+    }
+
+    let mut line = format!("[{}]", time);
+    for word in &segment.words {
+        line.push_str(&format!(
+            "<{}>{} ",
+            format_timestamp(word.start),
+            line_text(&word.text)
+        ));
+    }
+    // Closing tag marks the end of the last word
+    let end = segment.words.last().map_or(segment.end, |w| w.end);
+    line.push_str(&format!("<{}>", format_timestamp(end)));
+    line
+}
+
+/// Format metadata as LRC ID tags.
+///
+/// # Example
+/// ```
+/// use voxtus::formats::{Metadata, lrc::format_metadata};
+///
+/// let metadata = Metadata::new("Song", "song.mp3", Some(185.0), "tiny", None);
+/// assert_eq!(format_metadata(&metadata), "[ti:Song]\n[length:03:05]\n[re:voxtus]");
+/// ```
+pub fn format_metadata(metadata: &Metadata) -> String {
+    let mut tags = vec![format!("[ti:{}]", line_text(&metadata.title))];
+    if let Some(artist) = metadata.uploader.as_ref().or(metadata.channel.as_ref()) {
+        tags.push(format!("[ar:{}]", line_text(artist)));
+    }
+    if let Some(duration) = metadata.duration {
+        let total_seconds = duration.max(0.0).round() as u64;
+        tags.push(format!(
+            "[length:{:02}:{:02}]",
+            total_seconds / 60,
+            total_seconds % 60
+        ));
+    }
+    tags.push("[re:voxtus]".to_string());
+    tags.join("\n")
+}
+
+/// Format segments and metadata as LRC output.
+///
+/// A blank line (a timestamp without text) is added where there is a pause
+/// after a segment, so players clear the previous line.
+pub fn format_transcript(segments: &[Segment], metadata: &Metadata, words: bool) -> String {
+    let mut lines = vec![format_metadata(metadata)];
+    for (i, segment) in segments.iter().enumerate() {
+        lines.push(format_segment(segment, words));
+        let next_start = segments.get(i + 1).map(|s| s.start);
+        if next_start.is_none_or(|start| start > segment.end) {
+            lines.push(format!("[{}]", format_timestamp(segment.end)));
+        }
+    }
+    lines.join("\n")
+}
+
+/// LRC output as a [`Formatter`].
+#[derive(Debug, Clone, Default)]
+pub struct LrcFormatter {
+    /// Add word tags (enhanced LRC).
+    pub words: bool,
+}
+
+impl Formatter for LrcFormatter {
+    fn name(&self) -> &str {
+        "lrc"
+    }
+
+    fn extension(&self) -> &str {
+        "lrc"
+    }
+
+    fn mime_type(&self) -> &str {
+        "text/plain"
+    }
+
+    fn description(&self) -> &str {
+        "Synchronized lyrics, optionally with word tags"
+    }
+
+    fn format(&self, transcript: &Transcript, _context: &FormatContext) -> String {
+        format_transcript(&transcript.segments, &transcript.metadata, self.words)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::Word;
+
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn prop_format_timestamp_produces_valid_format(seconds in 0.0f64..100000.0) {
+            let result = format_timestamp(seconds);
+            // Should match mm:ss.xx pattern
+            let parts: Vec<&str> = result.split('.').collect();
+            prop_assert_eq!(parts.len(), 2, "Should have exactly one dot");
+
+            let time_parts: Vec<&str> = parts[0].split(':').collect();
+            prop_assert_eq!(time_parts.len(), 2, "Should have mm:ss format");
+
+            // Centiseconds should be 2 digits
+            prop_assert_eq!(parts[1].len(), 2, "Centiseconds should be 2 digits");
+
+            // All parts should be numeric
+            for part in &time_parts {
+                prop_assert!(part.parse::<u64>().is_ok(), "Time parts should be numeric");
+            }
+            prop_assert!(parts[1].parse::<u64>().is_ok(), "Centiseconds should be numeric");
+        }
+
+        #[test]
+        fn prop_format_timestamp_seconds_under_60(seconds in 0.0f64..100000.0) {
+            let result = format_timestamp(seconds);
+            let time_parts: Vec<&str> = result.split('.').next().unwrap().split(':').collect();
+            let secs: u64 = time_parts[1].parse().unwrap();
+            prop_assert!(secs < 60, "Seconds should be < 60");
+        }
+
+        #[test]
+        fn prop_format_timestamp_centiseconds_under_100(seconds in 0.0f64..100000.0) {
+            let result = format_timestamp(seconds);
+            let cs: u64 = result.split('.').nth(1).unwrap().parse().unwrap();
+            prop_assert!(cs < 100, "Centiseconds should be < 100");
+        }
+    }
+
+    #[test]
+    fn test_format_timestamp_zero() {
+        assert_eq!(format_timestamp(0.0), "00:00.00");
+    }
+
+    #[test]
+    fn test_format_timestamp_seconds() {
+        assert_eq!(format_timestamp(5.5), "00:05.50");
+        assert_eq!(format_timestamp(59.99), "00:59.99");
+    }
+
+    #[test]
+    fn test_format_timestamp_minutes() {
+        assert_eq!(format_timestamp(65.5), "01:05.50");
+        assert_eq!(format_timestamp(125.0), "02:05.00");
+    }
+
+    #[test]
+    fn test_format_timestamp_hours() {
+        assert_eq!(format_timestamp(3661.123), "61:01.12");
+        assert_eq!(format_timestamp(7200.0), "120:00.00");
+    }
+
+    #[test]
+    fn test_format_timestamp_edge_cases() {
+        assert_eq!(format_timestamp(0.999), "00:00.99");
+        assert_eq!(format_timestamp(3599.999), "59:59.99");
+    }
+
+    #[test]
+    fn test_format_segment_joins_lines() {
+        let segment = Segment::new(1.0, 2.0, "  Line one\nline two ");
+        assert_eq!(
+            format_segment(&segment, false),
+            "[00:01.00]Line one line two"
+        );
+    }
+
+    #[test]
+    fn test_format_segment_words_without_timings() {
+        let segment = Segment::new(1.0, 2.0, "No words");
+        assert_eq!(format_segment(&segment, true), "[00:01.00]No words");
+    }
+
+    #[test]
+    fn test_format_metadata_artist() {
+        let mut metadata = Metadata::new("Song", "song.mp3", None, "tiny", None);
+        metadata.channel = Some("The Band".to_string());
+        assert_eq!(
+            format_metadata(&metadata),
+            "[ti:Song]\n[ar:The Band]\n[re:voxtus]"
+        );
+    }
+
+    #[test]
+    fn test_format_transcript_clears_pauses() {
+        let metadata = Metadata::new("Song", "song.mp3", None, "tiny", None);
+        let segments = vec![
+            Segment::new(0.0, 2.0, "One"),
+            Segment::new(2.0, 3.0, "Two"),
+            Segment::new(5.0, 6.0, "Three"),
+        ];
+        assert_eq!(
+            format_transcript(&segments, &metadata, false),
+            "[ti:Song]\n[re:voxtus]\n\
+             [00:00.00]One\n\
+             [00:02.00]Two\n[00:03.00]\n\
+             [00:05.00]Three\n[00:06.00]"
+        );
+    }
+
+    #[test]
+    fn test_format_transcript_enhanced() {
+        let metadata = Metadata::new("Song", "song.mp3", None, "tiny", None);
+        let mut segment = Segment::new(0.0, 1.0, "La la");
+        segment.words = vec![Word::new(0.0, 0.4, "La"), Word::new(0.5, 0.9, "la")];
+        let lrc = format_transcript(&[segment], &metadata, true);
+        assert!(lrc.contains("\n[00:00.00]<00:00.00>La <00:00.50>la <00:00.90>\n[00:01.00]"));
+    }
+}
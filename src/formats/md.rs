@@ -0,0 +1,427 @@
+// Voxtus - Transcribe YouTube videos and local media files to text
+// Copyright (C) 2024 Johan Thorén <johan@thoren.xyz>
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Markdown format output.
+//!
+//! Readable document format: a metadata header followed by the transcript
+//! grouped into paragraphs, with a `##` heading per chapter and the speaker
+//! in bold when segments carry speaker labels.
+
+use super::{Chapter, FormatContext, Formatter, Metadata, Segment, Transcript, chapter_for};
+use crate::download::youtube::youtube_video_id;
+
+/// Silence between segments, in seconds, that starts a new paragraph.
+pub const PARAGRAPH_PAUSE: f64 = 2.0;
+
+/// Paragraph length, in characters, after which the next sentence end
+/// starts a new paragraph.
+pub const PARAGRAPH_CHARS: usize = 400;
+
+/// Options for Markdown output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MarkdownOptions {
+    /// Start each paragraph with its timestamp, linked to the source
+    /// position for YouTube sources.
+    pub timestamps: bool,
+}
+
+/// Format seconds as a short timestamp: `MM:SS`, or `H:MM:SS` from one hour.
+///
+/// # Example
+/// ```
+/// use voxtus::formats::md::format_timestamp;
+///
+/// assert_eq!(format_timestamp(65.5), "01:05");
+/// assert_eq!(format_timestamp(3661.0), "1:01:01");
+/// ```
+pub fn format_timestamp(seconds: f64) -> String {
+    let total_seconds = seconds.max(0.0).floor() as u64;
+    let hours = total_seconds / 3600;
+    let minutes = (total_seconds % 3600) / 60;
+    let secs = total_seconds % 60;
+
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("{:02}:{:02}", minutes, secs)
+    }
+}
+
+/// Link to a position in the source, for YouTube sources only.
+///
+/// Any existing `t` parameter and fragment are replaced.
+///
+/// # Example
+/// ```
+/// use voxtus::formats::md::timestamp_url;
+///
+/// assert_eq!(
+///     timestamp_url("https://www.youtube.com/watch?v=dQw4w9WgXcQ", 65.5).as_deref(),
+///     Some("https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=65s")
+/// );
+/// assert_eq!(
+///     timestamp_url("https://youtu.be/dQw4w9WgXcQ", 3.0).as_deref(),
+///     Some("https://youtu.be/dQw4w9WgXcQ?t=3s")
+/// );
+/// assert_eq!(timestamp_url("lecture.mp3", 3.0), None);
+/// ```
+pub fn timestamp_url(source: &str, seconds: f64) -> Option<String> {
+    youtube_video_id(source)?;
+
+    let source = source.split('#').next().unwrap_or(source);
+    let (base, query) = source.split_once('?').unwrap_or((source, ""));
+    let mut params: Vec<&str> = query
+        .split('&')
+        .filter(|p| !p.is_empty() && *p != "t" && !p.starts_with("t="))
+        .collect();
+    let t = format!("t={}s", seconds.max(0.0).floor() as u64);
+    params.push(&t);
+
+    Some(format!("{}?{}", base, params.join("&")))
+}
+
+/// Escape characters that Markdown would otherwise interpret.
+///
+/// # Example
+/// ```
+/// use voxtus::formats::md::escape;
+///
+/// assert_eq!(escape("5 * 3 = [15]"), "5 \\* 3 = \\[15\\]");
+/// ```
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Whether the text ends a sentence.
+pub(crate) fn ends_sentence(text: &str) -> bool {
+    text.trim_end()
+        .trim_end_matches(['"', '\'', ')', '»', '”'])
+        .ends_with(['.', '!', '?', '…'])
+}
+
+/// Group segments into paragraphs.
+///
+/// A new paragraph starts after a pause of at least [`PARAGRAPH_PAUSE`]
+/// seconds, at a chapter boundary, when the speaker changes, or at the first
+/// sentence end once the paragraph is [`PARAGRAPH_CHARS`] long.
+///
+/// # Example
+/// ```
+/// use voxtus::formats::{Segment, md::paragraphs};
+///
+/// let segments = vec![
+///     Segment::new(0.0, 2.0, "Hello."),
+///     Segment::new(2.0, 4.0, "Still talking."),
+///     Segment::new(9.0, 11.0, "After a pause."),
+/// ];
+/// let groups = paragraphs(&segments, &[]);
+/// assert_eq!(groups.len(), 2);
+/// assert_eq!(groups[0].len(), 2);
+/// ```
+pub fn paragraphs<'a>(segments: &'a [Segment], chapters: &[Chapter]) -> Vec<Vec<&'a Segment>> {
+    let mut groups: Vec<Vec<&Segment>> = Vec::new();
+    let mut length = 0;
+    let mut previous: Option<&Segment> = None;
+
+    for segment in segments {
+        if segment.text.trim().is_empty() {
+            continue;
+        }
+
+        let split = match previous {
+            None => true,
+            Some(prev) => {
+                segment.start - prev.end >= PARAGRAPH_PAUSE
+                    || chapter_for(chapters, segment) != chapter_for(chapters, prev)
+                    || segment.speaker != prev.speaker
+                    || (length >= PARAGRAPH_CHARS && ends_sentence(&prev.text))
+            }
+        };
+
+        if split {
+            groups.push(Vec::new());
+            length = 0;
+        }
+        length += segment.text.trim().len() + 1;
+        if let Some(group) = groups.last_mut() {
+            group.push(segment);
+        }
+        previous = Some(segment);
+    }
+
+    groups
+}
+
+/// Format metadata as a Markdown header.
+///
+/// The title becomes a `#` heading followed by a list of the known fields.
+pub fn format_metadata(metadata: &Metadata) -> String {
+    let mut lines = vec![format!("# {}", escape(&metadata.title)), String::new()];
+
+    if metadata.source.starts_with("http://") || metadata.source.starts_with("https://") {
+        lines.push(format!("- **Source:** <{}>", metadata.source));
+    } else {
+        lines.push(format!(
+            "- **Source:** `{}`",
+            metadata.source.replace('`', "'")
+        ));
+    }
+    if let Some(date) = &metadata.upload_date {
+        lines.push(format!("- **Date:** {}", escape(date)));
+    }
+    if let Some(duration) = metadata.duration {
+        lines.push(format!("- **Duration:** {}", format_timestamp(duration)));
+    }
+    if let Some(language) = &metadata.language {
+        lines.push(format!("- **Language:** {}", escape(language)));
+    }
+
+    lines.join("\n")
+}
+
+/// Format a paragraph, optionally prefixed with its timestamp, and with its
+/// speaker when known.
+fn format_paragraph(group: &[&Segment], source: &str, options: &MarkdownOptions) -> String {
+    let mut text = group
+        .iter()
+        .map(|s| escape(s.text.trim()))
+        .collect::<Vec<_>>()
+        .join(" ");
+    if let Some(speaker) = group.first().and_then(|s| s.speaker.as_deref()) {
+        text = format!("**{}:** {}", escape(speaker), text);
+    }
+
+    if !options.timestamps {
+        return text;
+    }
+
+    let start = group.first().map(|s| s.start).unwrap_or_default();
+    let label = format_timestamp(start);
+    match timestamp_url(source, start) {
+        Some(url) => format!("[{}]({}) {}", label, url, text),
+        None => format!("**{}** {}", label, text),
+    }
+}
+
+/// Format segments and metadata as Markdown output.
+///
+/// # Example
+/// ```
+/// use voxtus::formats::{Metadata, Segment, md::{MarkdownOptions, format_transcript}};
+///
+/// let metadata = Metadata::new("Talk", "talk.mp3", Some(4.0), "tiny", None);
+/// let segments = vec![Segment::new(0.0, 2.0, "Hi."), Segment::new(2.0, 4.0, "Bye.")];
+/// let md = format_transcript(&segments, &metadata, &MarkdownOptions::default());
+/// assert!(md.starts_with("# Talk\n"));
+/// assert!(md.ends_with("\n\nHi. Bye."));
+/// ```
+pub fn format_transcript(
+    segments: &[Segment],
+    metadata: &Metadata,
+    options: &MarkdownOptions,
+) -> String {
+    let chapters = &metadata.chapters;
+    let mut parts = vec![format_metadata(metadata)];
+    let mut current = None;
+
+    for group in paragraphs(segments, chapters) {
+        let chapter = group.first().and_then(|s| chapter_for(chapters, s));
+        if chapter != current {
+            if let Some(i) = chapter {
+                parts.push(format!("## {}", escape(&chapters[i].title)));
+            }
+            current = chapter;
+        }
+        parts.push(format_paragraph(&group, &metadata.source, options));
+    }
+
+    parts.join("\n\n")
+}
+
+/// Markdown output as a [`Formatter`].
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownFormatter {
+    pub options: MarkdownOptions,
+}
+
+impl Formatter for MarkdownFormatter {
+    fn name(&self) -> &str {
+        "md"
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        &["markdown"]
+    }
+
+    fn extension(&self) -> &str {
+        "md"
+    }
+
+    fn mime_type(&self) -> &str {
+        "text/markdown"
+    }
+
+    fn description(&self) -> &str {
+        "Markdown with metadata, chapters and paragraphs"
+    }
+
+    fn format(&self, transcript: &Transcript, _context: &FormatContext) -> String {
+        format_transcript(&transcript.segments, &transcript.metadata, &self.options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_metadata(source: &str) -> Metadata {
+        let mut metadata = Metadata::new(
+            "Test Video",
+            source,
+            Some(3725.0),
+            "tiny",
+            Some("en".to_string()),
+        );
+        metadata.upload_date = Some("2024-05-01".to_string());
+        metadata
+    }
+
+    #[test]
+    fn test_format_metadata() {
+        let md = format_metadata(&sample_metadata("https://youtu.be/dQw4w9WgXcQ"));
+        assert_eq!(
+            md,
+            "# Test Video\n\n\
+             - **Source:** <https://youtu.be/dQw4w9WgXcQ>\n\
+             - **Date:** 2024-05-01\n\
+             - **Duration:** 1:02:05\n\
+             - **Language:** en"
+        );
+    }
+
+    #[test]
+    fn test_format_metadata_local_file() {
+        let mut metadata = Metadata::new("Talk", "/tmp/talk.mp3", None, "tiny", None);
+        metadata.title = "Talk #1".to_string();
+        let md = format_metadata(&metadata);
+        assert_eq!(md, "# Talk \\#1\n\n- **Source:** `/tmp/talk.mp3`");
+    }
+
+    #[test]
+    fn test_paragraphs_split_on_pause() {
+        let segments = vec![
+            Segment::new(0.0, 1.0, "One"),
+            Segment::new(1.5, 2.0, "two"),
+            Segment::new(4.0, 5.0, "three"),
+        ];
+        let groups = paragraphs(&segments, &[]);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[1][0].text, "three");
+    }
+
+    #[test]
+    fn test_paragraphs_split_on_sentence_end_when_long() {
+        let long = "word ".repeat(PARAGRAPH_CHARS / 5);
+        let segments = vec![
+            Segment::new(0.0, 1.0, format!("{}and more", long)),
+            Segment::new(1.0, 2.0, "still going."),
+            Segment::new(2.0, 3.0, "New paragraph."),
+            Segment::new(3.0, 4.0, "Same one."),
+        ];
+        let groups = paragraphs(&segments, &[]);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].len(), 2);
+        assert_eq!(groups[1].len(), 2);
+    }
+
+    #[test]
+    fn test_paragraphs_short_sentences_stay_together() {
+        let segments = vec![
+            Segment::new(0.0, 1.0, "Hi."),
+            Segment::new(1.0, 2.0, "Hello."),
+        ];
+        assert_eq!(paragraphs(&segments, &[]).len(), 1);
+    }
+
+    #[test]
+    fn test_paragraphs_split_on_speaker_change() {
+        let mut segments = vec![
+            Segment::new(0.0, 1.0, "Hi"),
+            Segment::new(1.0, 2.0, "there"),
+            Segment::new(2.0, 3.0, "Hello"),
+        ];
+        segments[0].speaker = Some("Alice".to_string());
+        segments[1].speaker = Some("Alice".to_string());
+        segments[2].speaker = Some("Bob".to_string());
+
+        let metadata = Metadata::new("Talk", "talk.mp3", None, "tiny", None);
+        let md = format_transcript(&segments, &metadata, &MarkdownOptions::default());
+        assert!(md.ends_with("**Alice:** Hi there\n\n**Bob:** Hello"));
+    }
+
+    #[test]
+    fn test_paragraphs_skip_empty_segments() {
+        let segments = vec![Segment::new(0.0, 1.0, "  "), Segment::new(1.0, 2.0, "Hi")];
+        let groups = paragraphs(&segments, &[]);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].len(), 1);
+    }
+
+    #[test]
+    fn test_format_transcript_with_chapters() {
+        let mut metadata = Metadata::new("Talk", "talk.mp3", None, "tiny", None);
+        metadata.chapters = vec![
+            Chapter::new(0.0, 2.0, "Intro"),
+            Chapter::new(2.0, 4.0, "Main"),
+        ];
+        let segments = vec![
+            Segment::new(0.0, 2.0, "Welcome."),
+            Segment::new(2.0, 4.0, "Let's start."),
+        ];
+        let md = format_transcript(&segments, &metadata, &MarkdownOptions::default());
+        assert!(md.ends_with("## Intro\n\nWelcome.\n\n## Main\n\nLet's start."));
+    }
+
+    #[test]
+    fn test_format_transcript_linked_timestamps() {
+        let metadata = sample_metadata("https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=10s");
+        let segments = vec![
+            Segment::new(0.0, 2.0, "Hello."),
+            Segment::new(65.0, 67.0, "Later."),
+        ];
+        let options = MarkdownOptions { timestamps: true };
+        let md = format_transcript(&segments, &metadata, &options);
+        assert!(
+            md.contains("\n\n[00:00](https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=0s) Hello.\n\n")
+        );
+        assert!(md.contains("[01:05](https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=65s) Later."));
+    }
+
+    #[test]
+    fn test_format_transcript_plain_timestamps_for_local_files() {
+        let metadata = Metadata::new("Talk", "talk.mp3", None, "tiny", None);
+        let segments = vec![Segment::new(61.0, 62.0, "Hi")];
+        let options = MarkdownOptions { timestamps: true };
+        let md = format_transcript(&segments, &metadata, &options);
+        assert!(md.ends_with("\n\n**01:01** Hi"));
+    }
+
+    #[test]
+    fn test_escape_text() {
+        let metadata = Metadata::new("Talk", "talk.mp3", None, "tiny", None);
+        let segments = vec![Segment::new(0.0, 1.0, "use *args and <b>")];
+        let md = format_transcript(&segments, &metadata, &MarkdownOptions::default());
+        assert!(md.ends_with("use \\*args and \\<b\\>"));
+    }
+}
@@ -5,21 +5,47 @@
 //! Output format implementations.
 //!
 //! This module contains pure functions for formatting transcription output
-//! in various formats: TXT, JSON, SRT, and VTT.
+//! in various formats: TXT, JSON, SRT, VTT and more.
+//!
+//! Each format is also a [`Formatter`], looked up by name in a
+//! [`Formatters`] registry. Library users can register their own formatters
+//! next to the built-in ones.
 
+pub mod ass;
+pub mod csv;
+pub mod html;
 pub mod json;
+pub mod lrc;
+pub mod md;
+pub mod sbv;
 pub mod srt;
+pub mod template;
+pub mod ttml;
 pub mod txt;
 pub mod vtt;
 
+use std::sync::Arc;
+
 use serde::{Deserialize, Serialize};
 
+use crate::error::{Error, Result};
+
 /// A transcription segment with timing information.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Segment {
     pub start: f64,
     pub end: f64,
     pub text: String,
+    /// Speaker label, when the transcription source provides one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+    /// Word timings, when the transcription source provides them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<Word>,
+    /// Average token probability (0-1), when the transcription source
+    /// provides it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
 }
 
 impl Segment {
@@ -28,11 +54,103 @@ impl Segment {
             start,
             end,
             text: text.into(),
+            speaker: None,
+            words: Vec::new(),
+            confidence: None,
         }
     }
 }
 
+/// A word with timing information, within a segment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Word {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+}
+
+impl Word {
+    pub fn new(start: f64, end: f64, text: impl Into<String>) -> Self {
+        Self {
+            start,
+            end,
+            text: text.into(),
+        }
+    }
+}
+
+/// A named chapter of the source media.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Chapter {
+    pub start: f64,
+    pub end: f64,
+    pub title: String,
+}
+
+impl Chapter {
+    pub fn new(start: f64, end: f64, title: impl Into<String>) -> Self {
+        Self {
+            start,
+            end,
+            title: title.into(),
+        }
+    }
+}
+
+/// Where the transcript text came from.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+#[schemars(rename = "transcript_source")]
+pub enum TranscriptSource {
+    /// Transcribed locally with Whisper.
+    #[default]
+    Whisper,
+    /// Creator-uploaded subtitles.
+    Subtitles,
+    /// Automatically generated captions from the hosting site.
+    AutoCaptions,
+}
+
+impl TranscriptSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Whisper => "whisper",
+            Self::Subtitles => "subtitles",
+            Self::AutoCaptions => "auto_captions",
+        }
+    }
+}
+
+/// Find the chapter a segment belongs to, by its start time.
+///
+/// Segments that start before the first chapter are attached to it.
+/// Returns `None` only when there are no chapters.
+///
+/// # Example
+/// ```
+/// use voxtus::formats::{Chapter, Segment, chapter_for};
+///
+/// let chapters = vec![Chapter::new(0.0, 10.0, "Intro"), Chapter::new(10.0, 20.0, "Main")];
+/// assert_eq!(chapter_for(&chapters, &Segment::new(12.0, 14.0, "Hi")), Some(1));
+/// assert_eq!(chapter_for(&[], &Segment::new(12.0, 14.0, "Hi")), None);
+/// ```
+pub fn chapter_for(chapters: &[Chapter], segment: &Segment) -> Option<usize> {
+    if chapters.is_empty() {
+        return None;
+    }
+    let index = chapters
+        .iter()
+        .rposition(|c| c.start <= segment.start)
+        .unwrap_or(0);
+    Some(index)
+}
+
 /// Metadata about the transcription.
+///
+/// The source media fields (`id` through `chapters`) come from yt-dlp for
+/// downloads and from ffprobe tags for local files, and are often absent.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Metadata {
     pub title: String,
@@ -40,6 +158,35 @@ pub struct Metadata {
     pub duration: Option<f64>,
     pub model: String,
     pub language: Option<String>,
+    #[serde(default)]
+    pub transcript_source: TranscriptSource,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uploader: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+    /// Upload or recording date as `YYYY-MM-DD`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upload_date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chapters: Vec<Chapter>,
+    /// ffmpeg filter chain applied to the audio before transcription.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_filters: Option<String>,
+    /// Whisper decoding options used for the transcription.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decoding_options: Option<String>,
+    /// Hash of the transcribed audio, as `fnv1a64:` and 16 hex digits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_hash: Option<String>,
+    /// When the transcript was made, as an RFC 3339 UTC timestamp.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
 }
 
 impl Metadata {
@@ -56,12 +203,24 @@ impl Metadata {
             duration,
             model: model.into(),
             language,
+            transcript_source: TranscriptSource::Whisper,
+            id: None,
+            uploader: None,
+            channel: None,
+            upload_date: None,
+            description: None,
+            tags: Vec::new(),
+            chapters: Vec::new(),
+            audio_filters: None,
+            decoding_options: None,
+            audio_hash: None,
+            created_at: None,
         }
     }
 }
 
 /// A complete transcript with segments and metadata.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Transcript {
     pub segments: Vec<Segment>,
     pub metadata: Metadata,
@@ -72,27 +231,206 @@ impl Transcript {
         Self { segments, metadata }
     }
 
+    /// Split the transcript into one transcript per chapter.
+    ///
+    /// Segment timestamps are kept relative to the full media. Chapters
+    /// without any segments are left out. Returns an empty list when the
+    /// transcript has no chapters.
+    pub fn split_by_chapter(&self) -> Vec<(Chapter, Transcript)> {
+        let chapters = &self.metadata.chapters;
+        let mut parts: Vec<Vec<Segment>> = vec![Vec::new(); chapters.len()];
+        for segment in &self.segments {
+            if let Some(i) = chapter_for(chapters, segment) {
+                parts[i].push(segment.clone());
+            }
+        }
+
+        chapters
+            .iter()
+            .zip(parts)
+            .filter(|(_, segments)| !segments.is_empty())
+            .map(|(chapter, segments)| {
+                let mut metadata = self.metadata.clone();
+                metadata.title = format!("{} - {}", self.metadata.title, chapter.title);
+                metadata.chapters = vec![chapter.clone()];
+                (chapter.clone(), Transcript::new(segments, metadata))
+            })
+            .collect()
+    }
+
     /// Format the transcript as TXT.
+    ///
+    /// Chapter headings are inserted when the metadata has chapters.
+    #[deprecated(note = "use `txt::TxtFormatter` or the `txt` module functions")]
     pub fn to_txt(&self) -> String {
-        txt::format_transcript(&self.segments)
+        txt::TxtFormatter::default().format(self, &FormatContext::default())
     }
 
     /// Format the transcript as JSON.
+    #[deprecated(note = "use `json::format_transcript` or `json::JsonFormatter`")]
     pub fn to_json(&self) -> String {
         json::format_transcript(&self.segments, &self.metadata)
     }
 
     /// Format the transcript as SRT.
+    #[deprecated(note = "use `srt::format_transcript` or `srt::SrtFormatter`")]
     pub fn to_srt(&self) -> String {
         srt::format_transcript(&self.segments)
     }
 
     /// Format the transcript as VTT.
+    #[deprecated(note = "use `vtt::format_transcript` or `vtt::VttFormatter`")]
     pub fn to_vtt(&self) -> String {
         vtt::format_transcript(&self.segments, &self.metadata)
     }
 }
 
+/// Details of one output passed to every formatter.
+///
+/// Options of a single format live on its formatter, e.g.
+/// [`txt::TxtFormatter::options`], so that custom formatters can carry
+/// their own.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FormatContext {
+    /// Kept audio file, relative to the output file, e.g. for the HTML player.
+    pub media: Option<String>,
+}
+
+/// An output format for transcripts.
+pub trait Formatter: Send + Sync {
+    /// Short name used to select the format, e.g. `srt`.
+    fn name(&self) -> &str;
+
+    /// Other names that select the format, e.g. `markdown` for `md`.
+    fn aliases(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// File extension of the output, without the dot.
+    fn extension(&self) -> &str;
+
+    /// MIME type of the output.
+    fn mime_type(&self) -> &str;
+
+    /// One-line description for `--list-formats`.
+    fn description(&self) -> &str {
+        ""
+    }
+
+    /// Format the transcript.
+    fn format(&self, transcript: &Transcript, context: &FormatContext) -> String;
+}
+
+impl std::fmt::Debug for dyn Formatter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Formatter").field(&self.name()).finish()
+    }
+}
+
+/// Ordered registry of output formats.
+pub struct Formatters {
+    formatters: Vec<Arc<dyn Formatter>>,
+}
+
+impl Default for Formatters {
+    /// All built-in formats.
+    fn default() -> Self {
+        let formatters: Vec<Arc<dyn Formatter>> = vec![
+            Arc::new(txt::TxtFormatter::default()),
+            Arc::new(json::JsonFormatter::default()),
+            Arc::new(srt::SrtFormatter),
+            Arc::new(vtt::VttFormatter),
+            Arc::new(sbv::SbvFormatter),
+            Arc::new(ttml::TtmlFormatter),
+            Arc::new(ass::AssFormatter::default()),
+            Arc::new(lrc::LrcFormatter::default()),
+            Arc::new(md::MarkdownFormatter::default()),
+            Arc::new(html::HtmlFormatter),
+            Arc::new(csv::CsvFormatter::default()),
+            Arc::new(csv::TsvFormatter::default()),
+        ];
+        Self { formatters }
+    }
+}
+
+impl Formatters {
+    /// Create an empty registry.
+    pub fn empty() -> Self {
+        Self {
+            formatters: Vec::new(),
+        }
+    }
+
+    /// Add a formatter, replacing a registered one with the same name.
+    pub fn register(&mut self, formatter: Box<dyn Formatter>) {
+        let formatter: Arc<dyn Formatter> = Arc::from(formatter);
+        match self
+            .formatters
+            .iter()
+            .position(|f| f.name().eq_ignore_ascii_case(formatter.name()))
+        {
+            Some(i) => self.formatters[i] = formatter,
+            None => self.formatters.push(formatter),
+        }
+    }
+
+    /// Names of the registered formats, in registration order.
+    pub fn names(&self) -> Vec<&str> {
+        self.formatters.iter().map(|f| f.name()).collect()
+    }
+
+    /// All registered formatters, in registration order.
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Formatter> {
+        self.formatters.iter().map(|f| f.as_ref())
+    }
+
+    /// Look up a formatter by name or alias, ignoring case.
+    pub fn get(&self, name: &str) -> Option<Arc<dyn Formatter>> {
+        self.formatters
+            .iter()
+            .find(|f| {
+                f.name().eq_ignore_ascii_case(name)
+                    || f.aliases().iter().any(|a| a.eq_ignore_ascii_case(name))
+            })
+            .cloned()
+    }
+
+    /// Parse a comma-separated list of format names.
+    ///
+    /// `template:path` loads a [`template::TemplateFormatter`] from a file.
+    /// With `stdout_mode` only a single format is allowed.
+    ///
+    /// # Example
+    /// ```
+    /// use voxtus::formats::Formatters;
+    ///
+    /// let formats = Formatters::default().parse("txt, Markdown", false).unwrap();
+    /// let names: Vec<_> = formats.iter().map(|f| f.name()).collect();
+    /// assert_eq!(names, vec!["txt", "md"]);
+    /// ```
+    pub fn parse(&self, list: &str, stdout_mode: bool) -> Result<Vec<Arc<dyn Formatter>>> {
+        let formats = list
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(
+                |name| match template::TemplateFormatter::from_format(name) {
+                    Some(formatter) => Ok(Arc::new(formatter?) as Arc<dyn Formatter>),
+                    None => self
+                        .get(name)
+                        .ok_or_else(|| Error::InvalidFormat(name.to_string())),
+                },
+            )
+            .collect::<Result<Vec<_>>>()?;
+
+        if stdout_mode && formats.len() > 1 {
+            return Err(Error::MultipleFormatsWithStdout);
+        }
+
+        Ok(formats)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -136,9 +474,22 @@ mod tests {
         assert_eq!(metadata.duration, Some(60.0));
         assert_eq!(metadata.model, "small");
         assert_eq!(metadata.language, Some("en".to_string()));
+        assert_eq!(metadata.transcript_source, TranscriptSource::Whisper);
+        assert!(metadata.id.is_none());
+        assert!(metadata.tags.is_empty());
+        assert!(metadata.chapters.is_empty());
+    }
+
+    #[test]
+    fn test_chapter_creation() {
+        let chapter = Chapter::new(0.0, 30.0, "Intro");
+        assert_eq!(chapter.start, 0.0);
+        assert_eq!(chapter.end, 30.0);
+        assert_eq!(chapter.title, "Intro");
     }
 
     #[test]
+    #[allow(deprecated)]
     fn test_transcript_to_txt() {
         let transcript = Transcript::new(sample_segments(), sample_metadata());
         let output = transcript.to_txt();
@@ -147,6 +498,76 @@ mod tests {
     }
 
     #[test]
+    fn test_chapter_for() {
+        let chapters = vec![
+            Chapter::new(1.0, 5.0, "One"),
+            Chapter::new(5.0, 10.0, "Two"),
+        ];
+        // Before the first chapter clamps to it
+        assert_eq!(
+            chapter_for(&chapters, &Segment::new(0.0, 1.0, "a")),
+            Some(0)
+        );
+        assert_eq!(
+            chapter_for(&chapters, &Segment::new(4.9, 6.0, "b")),
+            Some(0)
+        );
+        assert_eq!(
+            chapter_for(&chapters, &Segment::new(5.0, 6.0, "c")),
+            Some(1)
+        );
+        // After the last chapter ends stays in the last chapter
+        assert_eq!(
+            chapter_for(&chapters, &Segment::new(12.0, 13.0, "d")),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_split_by_chapter() {
+        let mut metadata = sample_metadata();
+        metadata.chapters = vec![
+            Chapter::new(0.0, 5.0, "Intro"),
+            Chapter::new(5.0, 6.0, "Empty"),
+            Chapter::new(6.0, 10.5, "Main"),
+        ];
+        let segments = vec![
+            Segment::new(0.0, 5.0, "Hello world"),
+            Segment::new(6.5, 10.5, "This is a test"),
+        ];
+        let transcript = Transcript::new(segments, metadata);
+
+        let parts = transcript.split_by_chapter();
+        assert_eq!(parts.len(), 2);
+
+        let (chapter, part) = &parts[1];
+        assert_eq!(chapter.title, "Main");
+        assert_eq!(part.metadata.title, "Test Video - Main");
+        assert_eq!(part.metadata.chapters, vec![chapter.clone()]);
+        assert_eq!(
+            part.segments,
+            vec![Segment::new(6.5, 10.5, "This is a test")]
+        );
+    }
+
+    #[test]
+    fn test_split_by_chapter_without_chapters() {
+        let transcript = Transcript::new(sample_segments(), sample_metadata());
+        assert!(transcript.split_by_chapter().is_empty());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_transcript_to_txt_with_chapters() {
+        let mut metadata = sample_metadata();
+        metadata.chapters = vec![Chapter::new(5.0, 10.5, "Second")];
+        let transcript = Transcript::new(sample_segments(), metadata);
+        let output = transcript.to_txt();
+        assert!(output.starts_with("# Second\n\n[0.00 - 5.20]: Hello world"));
+    }
+
+    #[test]
+    #[allow(deprecated)]
     fn test_transcript_to_json() {
         let transcript = Transcript::new(sample_segments(), sample_metadata());
         let output = transcript.to_json();
@@ -156,6 +577,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn test_transcript_to_srt() {
         let transcript = Transcript::new(sample_segments(), sample_metadata());
         let output = transcript.to_srt();
@@ -164,6 +586,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn test_transcript_to_vtt() {
         let transcript = Transcript::new(sample_segments(), sample_metadata());
         let output = transcript.to_vtt();
@@ -171,4 +594,171 @@ mod tests {
         assert!(output.contains("00:00:00.000 --> 00:00:05.200"));
         assert!(output.contains("Hello world"));
     }
+
+    struct ShoutFormatter;
+
+    impl Formatter for ShoutFormatter {
+        fn name(&self) -> &str {
+            "shout"
+        }
+
+        fn extension(&self) -> &str {
+            "txt"
+        }
+
+        fn mime_type(&self) -> &str {
+            "text/plain"
+        }
+
+        fn format(&self, transcript: &Transcript, _context: &FormatContext) -> String {
+            transcript
+                .segments
+                .iter()
+                .map(|s| s.text.to_uppercase())
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    }
+
+    #[test]
+    fn test_default_formatters() {
+        let formatters = Formatters::default();
+        assert_eq!(
+            formatters.names(),
+            vec![
+                "txt", "json", "srt", "vtt", "sbv", "ttml", "ass", "lrc", "md", "html", "csv",
+                "tsv"
+            ]
+        );
+        for formatter in formatters.iter() {
+            assert!(!formatter.extension().is_empty());
+            assert!(formatter.mime_type().contains('/'));
+            assert!(!formatter.description().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_formatters_get_by_alias() {
+        let formatters = Formatters::default();
+        assert_eq!(formatters.get("MARKDOWN").unwrap().name(), "md");
+        assert_eq!(formatters.get("dfxp").unwrap().name(), "ttml");
+        assert!(formatters.get("docx").is_none());
+    }
+
+    #[test]
+    fn test_formatters_parse_errors() {
+        let formatters = Formatters::default();
+        assert!(matches!(
+            formatters.parse("txt,docx", false),
+            Err(Error::InvalidFormat(name)) if name == "docx"
+        ));
+        assert!(matches!(
+            formatters.parse("txt,json", true),
+            Err(Error::MultipleFormatsWithStdout)
+        ));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_builtin_formatter_matches_transcript_method() {
+        let transcript = Transcript::new(sample_segments(), sample_metadata());
+        let context = FormatContext::default();
+        let formatters = Formatters::default();
+        let format = |name: &str| formatters.get(name).unwrap().format(&transcript, &context);
+        assert_eq!(format("txt"), transcript.to_txt());
+        assert_eq!(format("srt"), transcript.to_srt());
+        assert_eq!(
+            format("csv"),
+            csv::format_csv(&transcript.segments, csv::DEFAULT_COLUMNS)
+        );
+    }
+
+    #[test]
+    fn test_formatter_uses_its_own_options() {
+        let transcript = Transcript::new(sample_segments(), sample_metadata());
+        let mut formatters = Formatters::default();
+        formatters.register(Box::new(txt::TxtFormatter {
+            options: txt::TxtOptions {
+                style: txt::TxtStyle::Plain,
+                width: 0,
+            },
+        }));
+        let output = formatters
+            .get("txt")
+            .unwrap()
+            .format(&transcript, &FormatContext::default());
+        assert_eq!(
+            output,
+            txt::format_plain(&transcript.segments, &transcript.metadata.chapters, 0)
+        );
+        assert!(!output.contains("[0.00 - 5.20]"));
+    }
+
+    #[test]
+    fn test_register_custom_formatter() {
+        let mut formatters = Formatters::default();
+        formatters.register(Box::new(ShoutFormatter));
+        assert_eq!(formatters.names().last(), Some(&"shout"));
+
+        let transcript = Transcript::new(sample_segments(), sample_metadata());
+        let formats = formatters.parse("shout", true).unwrap();
+        assert_eq!(
+            formats[0].format(&transcript, &FormatContext::default()),
+            "HELLO WORLD\nTHIS IS A TEST"
+        );
+    }
+
+    #[test]
+    fn test_formatters_parse_template() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("titles.tmpl");
+        std::fs::write(&path, "{{ metadata.title }}").unwrap();
+
+        let formats = Formatters::default()
+            .parse(&format!("srt,template:{}", path.display()), false)
+            .unwrap();
+        assert_eq!(formats[1].extension(), "txt");
+        let transcript = Transcript::new(sample_segments(), sample_metadata());
+        assert_eq!(
+            formats[1].format(&transcript, &FormatContext::default()),
+            "Test Video"
+        );
+
+        assert!(matches!(
+            Formatters::default().parse("template:/nonexistent/x.tmpl", false),
+            Err(Error::FileNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_register_replaces_same_name() {
+        struct PlainTxt;
+
+        impl Formatter for PlainTxt {
+            fn name(&self) -> &str {
+                "txt"
+            }
+
+            fn extension(&self) -> &str {
+                "txt"
+            }
+
+            fn mime_type(&self) -> &str {
+                "text/plain"
+            }
+
+            fn format(&self, _transcript: &Transcript, _context: &FormatContext) -> String {
+                "plain".to_string()
+            }
+        }
+
+        let mut formatters = Formatters::default();
+        let count = formatters.names().len();
+        formatters.register(Box::new(PlainTxt));
+        assert_eq!(formatters.names().len(), count);
+
+        let transcript = Transcript::new(sample_segments(), sample_metadata());
+        let txt = formatters.get("txt").unwrap();
+        assert_eq!(txt.format(&transcript, &FormatContext::default()), "plain");
+    }
 }
@@ -0,0 +1,211 @@
+// Voxtus - Transcribe YouTube videos and local media files to text
+// Copyright (C) 2024 Johan Thorén <johan@thoren.xyz>
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! SBV (SubViewer) format output.
+//!
+//! Simple caption format accepted by YouTube Studio.
+//! Uses dot for milliseconds and an unpadded hour: `H:MM:SS.mmm`
+
+use super::{FormatContext, Formatter, Segment, Transcript};
+
+/// Format seconds as SBV timestamp (H:MM:SS.mmm).
+///
+/// # Example
+/// ```
+/// use voxtus::formats::sbv::format_timestamp;
+///
+/// assert_eq!(format_timestamp(0.0), "0:00:00.000");
+/// assert_eq!(format_timestamp(65.5), "0:01:05.500");
+/// assert_eq!(format_timestamp(3661.123), "1:01:01.123");
+/// ```
+pub fn format_timestamp(seconds: f64) -> String {
+    let seconds = seconds.max(0.0);
+    let total_seconds = seconds.floor() as u64;
+    let hours = total_seconds / 3600;
+    let minutes = (total_seconds % 3600) / 60;
+    let secs = total_seconds % 60;
+
+    // Calculate milliseconds, clamping to 999 to handle rounding
+    let milliseconds = ((seconds.fract() * 1000.0).round() as u64).min(999);
+
+    format!("{}:{:02}:{:02}.{:03}", hours, minutes, secs, milliseconds)
+}
+
+/// Format a single segment as an SBV caption.
+///
+/// Blank lines would end the caption early, so they are dropped.
+///
+/// # Example
+/// ```
+/// use voxtus::formats::{Segment, sbv::format_segment};
+///
+/// let segment = Segment::new(0.0, 5.2, "Hello world");
+/// assert_eq!(format_segment(&segment), "0:00:00.000,0:00:05.200\nHello world");
+/// ```
+pub fn format_segment(segment: &Segment) -> String {
+    let text = segment
+        .text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!(
+        "{},{}\n{}",
+        format_timestamp(segment.start),
+        format_timestamp(segment.end),
+        text
+    )
+}
+
+/// Format multiple segments as SBV output.
+pub fn format_transcript(segments: &[Segment]) -> String {
+    segments
+        .iter()
+        .map(format_segment)
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// SBV output as a [`Formatter`].
+pub struct SbvFormatter;
+
+impl Formatter for SbvFormatter {
+    fn name(&self) -> &str {
+        "sbv"
+    }
+
+    fn extension(&self) -> &str {
+        "sbv"
+    }
+
+    fn mime_type(&self) -> &str {
+        "text/plain"
+    }
+
+    fn description(&self) -> &str {
+        "SubViewer captions for YouTube Studio"
+    }
+
+    fn format(&self, transcript: &Transcript, _context: &FormatContext) -> String {
+        format_transcript(&transcript.segments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn prop_format_timestamp_produces_valid_format(seconds in 0.0f64..100000.0) {
+            let result = format_timestamp(seconds);
+            // Should match H:MM:SS.mmm pattern
+            let parts: Vec<&str> = result.split('.').collect();
+            prop_assert_eq!(parts.len(), 2, "Should have exactly one dot");
+
+            let time_parts: Vec<&str> = parts[0].split(':').collect();
+            prop_assert_eq!(time_parts.len(), 3, "Should have H:MM:SS format");
+
+            // Milliseconds should be 3 digits
+            prop_assert_eq!(parts[1].len(), 3, "Milliseconds should be 3 digits");
+
+            // All parts should be numeric
+            for part in &time_parts {
+                prop_assert!(part.parse::<u64>().is_ok(), "Time parts should be numeric");
+            }
+            prop_assert!(parts[1].parse::<u64>().is_ok(), "Milliseconds should be numeric");
+        }
+
+        #[test]
+        fn prop_format_timestamp_minutes_under_60(seconds in 0.0f64..100000.0) {
+            let result = format_timestamp(seconds);
+            let time_parts: Vec<&str> = result.split('.').next().unwrap().split(':').collect();
+            let minutes: u64 = time_parts[1].parse().unwrap();
+            prop_assert!(minutes < 60, "Minutes should be < 60");
+        }
+
+        #[test]
+        fn prop_format_timestamp_seconds_under_60(seconds in 0.0f64..100000.0) {
+            let result = format_timestamp(seconds);
+            let time_parts: Vec<&str> = result.split('.').next().unwrap().split(':').collect();
+            let secs: u64 = time_parts[2].parse().unwrap();
+            prop_assert!(secs < 60, "Seconds should be < 60");
+        }
+
+        #[test]
+        fn prop_format_timestamp_milliseconds_under_1000(seconds in 0.0f64..100000.0) {
+            let result = format_timestamp(seconds);
+            let ms: u64 = result.split('.').nth(1).unwrap().parse().unwrap();
+            prop_assert!(ms < 1000, "Milliseconds should be < 1000");
+        }
+    }
+
+    #[test]
+    fn test_format_timestamp_zero() {
+        assert_eq!(format_timestamp(0.0), "0:00:00.000");
+    }
+
+    #[test]
+    fn test_format_timestamp_seconds() {
+        assert_eq!(format_timestamp(5.5), "0:00:05.500");
+        assert_eq!(format_timestamp(59.999), "0:00:59.999");
+    }
+
+    #[test]
+    fn test_format_timestamp_minutes() {
+        assert_eq!(format_timestamp(65.5), "0:01:05.500");
+        assert_eq!(format_timestamp(125.0), "0:02:05.000");
+    }
+
+    #[test]
+    fn test_format_timestamp_hours() {
+        assert_eq!(format_timestamp(3661.123), "1:01:01.123");
+        assert_eq!(format_timestamp(36000.0), "10:00:00.000");
+    }
+
+    #[test]
+    fn test_format_timestamp_edge_cases() {
+        assert_eq!(format_timestamp(0.999), "0:00:00.999");
+        assert_eq!(format_timestamp(3599.999), "0:59:59.999");
+    }
+
+    #[test]
+    fn test_format_segment_strips_whitespace() {
+        let segment = Segment::new(10.5, 15.75, "  Text with spaces  ");
+        assert_eq!(
+            format_segment(&segment),
+            "0:00:10.500,0:00:15.750\nText with spaces"
+        );
+    }
+
+    #[test]
+    fn test_format_segment_drops_blank_lines() {
+        let segment = Segment::new(0.0, 1.0, "Line one\n\nline two");
+        assert_eq!(
+            format_segment(&segment),
+            "0:00:00.000,0:00:01.000\nLine one\nline two"
+        );
+    }
+
+    #[test]
+    fn test_format_transcript_structure() {
+        let segments = vec![
+            Segment::new(0.0, 2.0, "Caption 1"),
+            Segment::new(2.0, 4.0, "Caption 2"),
+        ];
+        assert_eq!(
+            format_transcript(&segments),
+            "0:00:00.000,0:00:02.000\nCaption 1\n\n0:00:02.000,0:00:04.000\nCaption 2"
+        );
+    }
+
+    #[test]
+    fn test_format_transcript_empty() {
+        assert_eq!(format_transcript(&[]), "");
+    }
+}
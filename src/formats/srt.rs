@@ -7,7 +7,7 @@
 //! Standard subtitle format compatible with video players.
 //! Uses comma for milliseconds: `HH:MM:SS,mmm`
 
-use super::Segment;
+use super::{FormatContext, Formatter, Segment, Transcript};
 
 /// Format seconds as SRT timestamp (HH:MM:SS,mmm).
 ///
@@ -64,6 +64,31 @@ pub fn format_transcript(segments: &[Segment]) -> String {
         .join("\n\n")
 }
 
+/// SRT output as a [`Formatter`].
+pub struct SrtFormatter;
+
+impl Formatter for SrtFormatter {
+    fn name(&self) -> &str {
+        "srt"
+    }
+
+    fn extension(&self) -> &str {
+        "srt"
+    }
+
+    fn mime_type(&self) -> &str {
+        "application/x-subrip"
+    }
+
+    fn description(&self) -> &str {
+        "SubRip subtitles"
+    }
+
+    fn format(&self, transcript: &Transcript, _context: &FormatContext) -> String {
+        format_transcript(&transcript.segments)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
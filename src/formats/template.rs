@@ -0,0 +1,894 @@
+// Voxtus - Transcribe YouTube videos and local media files to text
+// Copyright (C) 2024 Johan Thorén <johan@thoren.xyz>
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Template format output.
+//!
+//! Renders a transcript with a user-supplied template, for layouts that no
+//! built-in format covers, such as a CMS import format. The template
+//! language is a small Jinja-like subset:
+//!
+//! - `{{ expr }}` outputs a value, e.g. `{{ segment.text | escape }}`
+//! - `{% for x in list %}...{% endfor %}` repeats its body, with
+//!   `loop.index` (from 1), `loop.index0`, `loop.first`, `loop.last` and
+//!   `loop.length`
+//! - `{% if cond %}...{% else %}...{% endif %}`, where `cond` is a value,
+//!   a comparison with `==` or `!=`, and may start with `not`
+//! - `{# comment #}`
+//!
+//! A `-` next to a tag delimiter (`{%-`, `-%}`, `{{-`, ...) strips the
+//! whitespace, including newlines, before or after the tag.
+//!
+//! Templates see `metadata`, `segments` (with `speaker`, `words` and
+//! `confidence` when known), `words` (the words of all segments),
+//! `speakers` (distinct labels in order of appearance) and `text` (the text
+//! of all segments). Missing values render as nothing and are false.
+
+use std::path::Path;
+
+use serde_json::{Value, json};
+
+use super::csv::csv_field;
+use super::{FormatContext, Formatter, Transcript, md, vtt};
+use crate::config::expand_tilde;
+use crate::error::{Error, Result};
+
+/// Prefix of `--format` values that name a template file.
+pub const TEMPLATE_PREFIX: &str = "template:";
+
+/// Filters that can follow a value, e.g. `{{ segment.start | timestamp }}`.
+///
+/// - `upper`, `lower`, `trim`: change the text
+/// - `escape`: escape `&`, `<`, `>`, `"` and `'` for HTML and XML
+/// - `json`: the value as JSON, e.g. a quoted string
+/// - `csv`: quote the text as a CSV field when needed
+/// - `timestamp`: seconds as `HH:MM:SS.mmm`
+/// - `clock`: seconds as `MM:SS`, or `H:MM:SS` from one hour
+/// - `round(n)`: a number with `n` decimals (default 0)
+/// - `default(value)`: `value` when the input is missing or false
+/// - `join(separator)`: the items of a list, joined
+/// - `length`: number of items in a list, or characters in a text
+pub const FILTERS: &[&str] = &[
+    "upper",
+    "lower",
+    "trim",
+    "escape",
+    "json",
+    "csv",
+    "timestamp",
+    "clock",
+    "round",
+    "default",
+    "join",
+    "length",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Path(Vec<String>),
+    Literal(Value),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Filter {
+    name: String,
+    arg: Option<Operand>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Expr {
+    operand: Operand,
+    filters: Vec<Filter>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Condition {
+    negate: bool,
+    left: Expr,
+    /// Right-hand side of `==` (`true`) or `!=` (`false`).
+    compare: Option<(bool, Expr)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Output(Expr),
+    If {
+        condition: Condition,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    For {
+        var: String,
+        list: Expr,
+        body: Vec<Node>,
+    },
+}
+
+enum Token {
+    Text(String),
+    Output(String, usize),
+    Statement(String, usize),
+}
+
+/// A parsed template.
+///
+/// # Example
+/// ```
+/// use voxtus::formats::{Metadata, Segment, Transcript, template::Template};
+///
+/// let template = Template::parse(
+///     "{% for segment in segments %}{{ loop.index }}. {{ segment.text | upper }}\n{% endfor %}",
+/// )
+/// .unwrap();
+/// let transcript = Transcript::new(
+///     vec![Segment::new(0.0, 1.0, "Hello"), Segment::new(1.0, 2.0, "world")],
+///     Metadata::new("Test", "test.mp3", None, "tiny", None),
+/// );
+/// assert_eq!(template.render(&transcript), "1. HELLO\n2. WORLD\n");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+impl Template {
+    /// Parse a template, checking tags, expressions and filter names.
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut tokens = tokenize(source)?.into_iter();
+        let (nodes, end) = parse_nodes(&mut tokens, &[])?;
+        match end {
+            Some((keyword, line)) => Err(syntax(line, &format!("unexpected '{}'", keyword))),
+            None => Ok(Self { nodes }),
+        }
+    }
+
+    /// Render the template for a transcript.
+    pub fn render(&self, transcript: &Transcript) -> String {
+        let root = context(transcript);
+        let mut scope = Scope {
+            root: &root,
+            locals: Vec::new(),
+        };
+        let mut out = String::new();
+        render_nodes(&self.nodes, &mut scope, &mut out);
+        out
+    }
+}
+
+/// A template loaded from a file, as a [`Formatter`].
+pub struct TemplateFormatter {
+    name: String,
+    extension: String,
+    template: Template,
+}
+
+impl TemplateFormatter {
+    /// Create a formatter from a parsed template.
+    pub fn new(name: impl Into<String>, extension: impl Into<String>, template: Template) -> Self {
+        Self {
+            name: name.into(),
+            extension: extension.into(),
+            template,
+        }
+    }
+
+    /// Load a template file.
+    ///
+    /// The output extension comes from the file name without a trailing
+    /// `.tmpl`, so `cms.xml.tmpl` writes `.xml` files. Names without another
+    /// extension write `.txt` files.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        if !path.is_file() {
+            return Err(Error::FileNotFound(path.display().to_string()));
+        }
+        let source = std::fs::read_to_string(path)?;
+        let template = Template::parse(&source).map_err(|e| match e {
+            Error::InvalidFormatTemplate(reason) => {
+                Error::InvalidFormatTemplate(format!("{}: {}", path.display(), reason))
+            }
+            e => e,
+        })?;
+        Ok(Self::new(
+            format!("{}{}", TEMPLATE_PREFIX, path.display()),
+            template_extension(path),
+            template,
+        ))
+    }
+
+    /// Load the template named by a `template:path` format value.
+    ///
+    /// Returns `None` when the value does not start with [`TEMPLATE_PREFIX`].
+    pub fn from_format(format: &str) -> Option<Result<Self>> {
+        let path = format.strip_prefix(TEMPLATE_PREFIX)?;
+        Some(Self::from_file(&expand_tilde(path.trim())))
+    }
+}
+
+impl Formatter for TemplateFormatter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn extension(&self) -> &str {
+        &self.extension
+    }
+
+    fn mime_type(&self) -> &str {
+        match self.extension.as_str() {
+            "json" => "application/json",
+            "xml" => "application/xml",
+            "html" | "htm" => "text/html",
+            "csv" => "text/csv",
+            "md" => "text/markdown",
+            _ => "text/plain",
+        }
+    }
+
+    fn description(&self) -> &str {
+        "Custom layout from a template file"
+    }
+
+    fn format(&self, transcript: &Transcript, _context: &FormatContext) -> String {
+        self.template.render(transcript)
+    }
+}
+
+/// Output extension for a template file.
+fn template_extension(path: &Path) -> String {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let name = name.strip_suffix(".tmpl").unwrap_or(&name);
+    Path::new(name)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_else(|| "txt".to_string())
+}
+
+/// The values a template can use.
+fn context(transcript: &Transcript) -> Value {
+    let segments = &transcript.segments;
+    let words: Vec<_> = segments.iter().flat_map(|s| &s.words).collect();
+    let mut speakers: Vec<&str> = Vec::new();
+    for speaker in segments.iter().filter_map(|s| s.speaker.as_deref()) {
+        if !speakers.contains(&speaker) {
+            speakers.push(speaker);
+        }
+    }
+    let text = segments
+        .iter()
+        .map(|s| s.text.trim())
+        .filter(|t| !t.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    json!({
+        "metadata": transcript.metadata,
+        "segments": segments,
+        "words": words,
+        "speakers": speakers,
+        "text": text,
+    })
+}
+
+fn syntax(line: usize, reason: &str) -> Error {
+    Error::InvalidFormatTemplate(format!("line {}: {}", line, reason))
+}
+
+/// Split the source into text and tags.
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+    let mut trim_start = false;
+
+    loop {
+        let start = ["{{", "{%", "{#"]
+            .iter()
+            .filter_map(|open| rest.find(open))
+            .min();
+        let mut text = &rest[..start.unwrap_or(rest.len())];
+        if trim_start {
+            text = text.trim_start();
+        }
+        let Some(start) = start else {
+            if !text.is_empty() {
+                tokens.push(Token::Text(text.to_string()));
+            }
+            break;
+        };
+
+        let line = source[..source.len() - rest.len() + start]
+            .matches('\n')
+            .count()
+            + 1;
+        let open = &rest[start..start + 2];
+        let close = match open {
+            "{{" => "}}",
+            "{%" => "%}",
+            _ => "#}",
+        };
+        let Some(len) = rest[start + 2..].find(close) else {
+            return Err(syntax(line, &format!("unclosed '{}'", open)));
+        };
+        let inner = &rest[start + 2..start + 2 + len];
+        let (inner, trim_before) = inner
+            .strip_prefix('-')
+            .map_or((inner, false), |i| (i, true));
+        let (inner, trim_after) = inner
+            .strip_suffix('-')
+            .map_or((inner, false), |i| (i, true));
+
+        if trim_before {
+            text = text.trim_end();
+        }
+        if !text.is_empty() {
+            tokens.push(Token::Text(text.to_string()));
+        }
+        match open {
+            "{{" => tokens.push(Token::Output(inner.trim().to_string(), line)),
+            "{%" => tokens.push(Token::Statement(inner.trim().to_string(), line)),
+            _ => {}
+        }
+
+        trim_start = trim_after;
+        rest = &rest[start + 2 + len + 2..];
+    }
+
+    Ok(tokens)
+}
+
+/// Parsed nodes, and the statement and line that ended them.
+type Block = (Vec<Node>, Option<(String, usize)>);
+
+/// Parse nodes up to one of the `ends` statements, which is returned.
+fn parse_nodes(tokens: &mut std::vec::IntoIter<Token>, ends: &[&str]) -> Result<Block> {
+    let mut nodes = Vec::new();
+
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Text(text) => nodes.push(Node::Text(text)),
+            Token::Output(expr, line) => {
+                nodes.push(Node::Output(
+                    parse_expr(&expr).map_err(|e| syntax(line, &e))?,
+                ));
+            }
+            Token::Statement(statement, line) => {
+                let (keyword, args) = statement
+                    .split_once(char::is_whitespace)
+                    .map_or((statement.as_str(), ""), |(k, a)| (k, a.trim()));
+                if ends.contains(&keyword) {
+                    return Ok((nodes, Some((keyword.to_string(), line))));
+                }
+                match keyword {
+                    "for" => {
+                        let Some((var, list)) = args.split_once(" in ") else {
+                            return Err(syntax(line, "expected 'for name in list'"));
+                        };
+                        let var = var.trim();
+                        if !is_identifier(var) {
+                            return Err(syntax(line, &format!("invalid loop variable '{}'", var)));
+                        }
+                        let list = parse_expr(list).map_err(|e| syntax(line, &e))?;
+                        let (body, end) = parse_nodes(tokens, &["endfor"])?;
+                        if end.is_none() {
+                            return Err(syntax(line, "missing 'endfor'"));
+                        }
+                        nodes.push(Node::For {
+                            var: var.to_string(),
+                            list,
+                            body,
+                        });
+                    }
+                    "if" => {
+                        let condition = parse_condition(args).map_err(|e| syntax(line, &e))?;
+                        let (then, end) = parse_nodes(tokens, &["else", "endif"])?;
+                        let otherwise = match end {
+                            Some((keyword, _)) if keyword == "else" => {
+                                let (otherwise, end) = parse_nodes(tokens, &["endif"])?;
+                                if end.is_none() {
+                                    return Err(syntax(line, "missing 'endif'"));
+                                }
+                                otherwise
+                            }
+                            Some(_) => Vec::new(),
+                            None => return Err(syntax(line, "missing 'endif'")),
+                        };
+                        nodes.push(Node::If {
+                            condition,
+                            then,
+                            otherwise,
+                        });
+                    }
+                    _ => return Err(syntax(line, &format!("unexpected '{}'", keyword))),
+                }
+            }
+        }
+    }
+
+    Ok((nodes, None))
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Byte offset of `pattern` in `source`, outside quoted strings.
+fn find_outside_quotes(source: &str, pattern: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in source.char_indices() {
+        match quote {
+            Some(_) if escaped => escaped = false,
+            Some(_) if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if source[i..].starts_with(pattern) => return Some(i),
+            None => {}
+        }
+    }
+    None
+}
+
+fn parse_condition(source: &str) -> std::result::Result<Condition, String> {
+    let source = source.trim();
+    let (negate, source) = source
+        .strip_prefix("not ")
+        .map_or((false, source), |s| (true, s));
+    for (op, equal) in [("==", true), ("!=", false)] {
+        if let Some(i) = find_outside_quotes(source, op) {
+            return Ok(Condition {
+                negate,
+                left: parse_expr(&source[..i])?,
+                compare: Some((equal, parse_expr(&source[i + op.len()..])?)),
+            });
+        }
+    }
+    Ok(Condition {
+        negate,
+        left: parse_expr(source)?,
+        compare: None,
+    })
+}
+
+fn parse_expr(source: &str) -> std::result::Result<Expr, String> {
+    let mut parts = Vec::new();
+    let mut rest = source;
+    while let Some(i) = find_outside_quotes(rest, "|") {
+        parts.push(&rest[..i]);
+        rest = &rest[i + 1..];
+    }
+    parts.push(rest);
+
+    let operand = parse_operand(parts[0])?;
+    let filters = parts[1..]
+        .iter()
+        .copied()
+        .map(parse_filter)
+        .collect::<std::result::Result<_, _>>()?;
+    Ok(Expr { operand, filters })
+}
+
+fn parse_filter(source: &str) -> std::result::Result<Filter, String> {
+    let source = source.trim();
+    let (name, arg) = match source.split_once('(') {
+        Some((name, rest)) => {
+            let arg = rest
+                .trim_end()
+                .strip_suffix(')')
+                .ok_or_else(|| format!("missing ')' in '{}'", source))?;
+            (name.trim(), Some(parse_operand(arg)?))
+        }
+        None => (source, None),
+    };
+    if !FILTERS.contains(&name) {
+        return Err(format!("unknown filter '{}'", name));
+    }
+    Ok(Filter {
+        name: name.to_string(),
+        arg,
+    })
+}
+
+fn parse_operand(source: &str) -> std::result::Result<Operand, String> {
+    let source = source.trim();
+    if source.is_empty() {
+        return Err("empty expression".to_string());
+    }
+    for quote in ['"', '\''] {
+        if let Some(s) = source
+            .strip_prefix(quote)
+            .and_then(|s| s.strip_suffix(quote))
+        {
+            return Ok(Operand::Literal(Value::String(unescape(s))));
+        }
+    }
+    if let Ok(n) = source.parse::<i64>() {
+        return Ok(Operand::Literal(json!(n)));
+    }
+    if let Ok(n) = source.parse::<f64>() {
+        return Ok(Operand::Literal(json!(n)));
+    }
+    match source {
+        "true" => return Ok(Operand::Literal(Value::Bool(true))),
+        "false" => return Ok(Operand::Literal(Value::Bool(false))),
+        "none" | "null" => return Ok(Operand::Literal(Value::Null)),
+        _ => {}
+    }
+
+    let path: Vec<String> = source.split('.').map(|p| p.trim().to_string()).collect();
+    if !path.iter().all(|p| is_identifier(p)) {
+        return Err(format!("invalid expression '{}'", source));
+    }
+    Ok(Operand::Path(path))
+}
+
+/// Resolve `\n`, `\t` and escaped quotes and backslashes in a string literal.
+fn unescape(s: &str) -> String {
+    let mut out = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some(c) => out.push(c),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+struct Scope<'a> {
+    root: &'a Value,
+    locals: Vec<(String, Value)>,
+}
+
+impl Scope<'_> {
+    fn lookup(&self, path: &[String]) -> Value {
+        let mut value = self
+            .locals
+            .iter()
+            .rev()
+            .find(|(name, _)| *name == path[0])
+            .map(|(_, v)| v)
+            .or_else(|| self.root.get(&path[0]));
+        for key in &path[1..] {
+            value = value.and_then(|v| match v {
+                Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+                _ => v.get(key),
+            });
+        }
+        value.cloned().unwrap_or(Value::Null)
+    }
+
+    fn eval_operand(&self, operand: &Operand) -> Value {
+        match operand {
+            Operand::Path(path) => self.lookup(path),
+            Operand::Literal(value) => value.clone(),
+        }
+    }
+
+    fn eval(&self, expr: &Expr) -> Value {
+        let mut value = self.eval_operand(&expr.operand);
+        for filter in &expr.filters {
+            let arg = filter.arg.as_ref().map(|a| self.eval_operand(a));
+            value = apply_filter(&filter.name, value, arg);
+        }
+        value
+    }
+
+    fn holds(&self, condition: &Condition) -> bool {
+        let left = self.eval(&condition.left);
+        let result = match &condition.compare {
+            Some((equal, right)) => values_equal(&left, &self.eval(right)) == *equal,
+            None => truthy(&left),
+        };
+        result != condition.negate
+    }
+}
+
+fn render_nodes(nodes: &[Node], scope: &mut Scope, out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Output(expr) => out.push_str(&to_text(&scope.eval(expr))),
+            Node::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let branch = if scope.holds(condition) {
+                    then
+                } else {
+                    otherwise
+                };
+                render_nodes(branch, scope, out);
+            }
+            Node::For { var, list, body } => {
+                let Value::Array(items) = scope.eval(list) else {
+                    continue;
+                };
+                let length = items.len();
+                for (i, item) in items.into_iter().enumerate() {
+                    let state = json!({
+                        "index": i + 1,
+                        "index0": i,
+                        "first": i == 0,
+                        "last": i + 1 == length,
+                        "length": length,
+                    });
+                    scope.locals.push(("loop".to_string(), state));
+                    scope.locals.push((var.clone(), item));
+                    render_nodes(body, scope, out);
+                    scope.locals.truncate(scope.locals.len() - 2);
+                }
+            }
+        }
+    }
+}
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(fields) => !fields.is_empty(),
+    }
+}
+
+fn values_equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        _ => left == right,
+    }
+}
+
+/// Text of a value as it is output.
+fn to_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::Array(_) | Value::Object(_) => value.to_string(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn apply_filter(name: &str, value: Value, arg: Option<Value>) -> Value {
+    let text = || to_text(&value);
+    match name {
+        "upper" => Value::String(text().to_uppercase()),
+        "lower" => Value::String(text().to_lowercase()),
+        "trim" => Value::String(text().trim().to_string()),
+        "escape" => Value::String(escape(&text())),
+        "json" => Value::String(value.to_string()),
+        "csv" => Value::String(csv_field(&text())),
+        "timestamp" => match value.as_f64() {
+            Some(seconds) => Value::String(vtt::format_timestamp(seconds)),
+            None => value,
+        },
+        "clock" => match value.as_f64() {
+            Some(seconds) => Value::String(md::format_timestamp(seconds)),
+            None => value,
+        },
+        "round" => match value.as_f64() {
+            Some(n) => {
+                let decimals = arg.and_then(|a| a.as_u64()).unwrap_or(0) as usize;
+                Value::String(format!("{:.*}", decimals, n))
+            }
+            None => value,
+        },
+        "default" => {
+            if truthy(&value) {
+                value
+            } else {
+                arg.unwrap_or(Value::Null)
+            }
+        }
+        "join" => match &value {
+            Value::Array(items) => {
+                let separator = arg.map(|a| to_text(&a)).unwrap_or_default();
+                Value::String(
+                    items
+                        .iter()
+                        .map(to_text)
+                        .collect::<Vec<_>>()
+                        .join(&separator),
+                )
+            }
+            _ => value,
+        },
+        "length" => match &value {
+            Value::Array(items) => json!(items.len()),
+            Value::Object(fields) => json!(fields.len()),
+            Value::Null => json!(0),
+            _ => json!(text().chars().count()),
+        },
+        _ => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::{Metadata, Segment, Word};
+
+    fn sample_transcript() -> Transcript {
+        let mut segments = vec![
+            Segment::new(0.0, 2.5, " Hello <world> "),
+            Segment::new(2.5, 65.0, "Second, line"),
+        ];
+        segments[0].speaker = Some("Alice".to_string());
+        segments[0].words = vec![Word::new(0.0, 1.0, "Hello"), Word::new(1.0, 2.5, "<world>")];
+        segments[1].speaker = Some("Bob".to_string());
+        Transcript::new(
+            segments,
+            Metadata::new(
+                "Episode 1",
+                "ep1.mp3",
+                Some(65.0),
+                "tiny",
+                Some("en".to_string()),
+            ),
+        )
+    }
+
+    fn render(source: &str) -> String {
+        Template::parse(source)
+            .unwrap()
+            .render(&sample_transcript())
+    }
+
+    fn parse_error(source: &str) -> String {
+        match Template::parse(source) {
+            Err(Error::InvalidFormatTemplate(reason)) => reason,
+            other => panic!("expected a template error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_render_metadata_and_text() {
+        assert_eq!(
+            render("{{ metadata.title }} ({{ metadata.language | upper }}): {{ text }}"),
+            "Episode 1 (EN): Hello <world> Second, line"
+        );
+    }
+
+    #[test]
+    fn test_render_loop_variables() {
+        assert_eq!(
+            render(
+                "{% for s in segments %}{{ loop.index }}/{{ loop.length }}\
+                 {% if not loop.last %},{% endif %}{% endfor %}"
+            ),
+            "1/2,2/2"
+        );
+    }
+
+    #[test]
+    fn test_render_nested_words() {
+        assert_eq!(
+            render(
+                "{% for s in segments %}{% for w in s.words %}[{{ w.text | escape }}]{% endfor %}{% endfor %}"
+            ),
+            "[Hello][&lt;world&gt;]"
+        );
+        assert_eq!(render("{{ words | length }}"), "2");
+    }
+
+    #[test]
+    fn test_render_speakers_and_comparison() {
+        assert_eq!(render("{{ speakers | join(\", \") }}"), "Alice, Bob");
+        assert_eq!(
+            render(
+                "{% for s in segments %}{% if s.speaker == \"Bob\" %}B{% else %}A{% endif %}{% endfor %}"
+            ),
+            "AB"
+        );
+    }
+
+    #[test]
+    fn test_render_filters() {
+        assert_eq!(render("{{ segments.1.end | timestamp }}"), "00:01:05.000");
+        assert_eq!(render("{{ segments.1.end | clock }}"), "01:05");
+        assert_eq!(render("{{ segments.0.end | round(2) }}"), "2.50");
+        assert_eq!(render("{{ segments.1.text | csv }}"), "\"Second, line\"");
+        assert_eq!(
+            render("{{ segments.0.text | trim | json }}"),
+            "\"Hello <world>\""
+        );
+        assert_eq!(render("{{ metadata.uploader | default(\"n/a\") }}"), "n/a");
+    }
+
+    #[test]
+    fn test_missing_values_render_empty() {
+        assert_eq!(
+            render("[{{ segments.1.confidence }}{{ nothing.here }}]"),
+            "[]"
+        );
+        assert_eq!(
+            render("{% if segments.1.words %}yes{% else %}no{% endif %}"),
+            "no"
+        );
+    }
+
+    #[test]
+    fn test_whitespace_control() {
+        let source = "<items>\n{%- for s in segments %}\n  <item>{{ loop.index }}</item>\n{%- endfor %}\n</items>";
+        assert_eq!(
+            render(source),
+            "<items>\n  <item>1</item>\n  <item>2</item>\n</items>"
+        );
+        assert_eq!(render("a {# note #}b {{- \" c\" -}} d"), "a b cd");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse_error("ok\n{{ text"), "line 2: unclosed '{{'");
+        assert_eq!(
+            parse_error("{{ text | shout }}"),
+            "line 1: unknown filter 'shout'"
+        );
+        assert_eq!(
+            parse_error("{% for s in segments %}\n{{ s.text }}"),
+            "line 1: missing 'endfor'"
+        );
+        assert_eq!(parse_error("{% endif %}"), "line 1: unexpected 'endif'");
+        assert_eq!(
+            parse_error("{% while x %}{% endwhile %}"),
+            "line 1: unexpected 'while'"
+        );
+        assert_eq!(
+            parse_error("{{ a..b }}"),
+            "line 1: invalid expression 'a..b'"
+        );
+    }
+
+    #[test]
+    fn test_template_extension() {
+        assert_eq!(template_extension(Path::new("cms.xml.tmpl")), "xml");
+        assert_eq!(template_extension(Path::new("dir/export.json")), "json");
+        assert_eq!(template_extension(Path::new("layout.tmpl")), "txt");
+    }
+
+    #[test]
+    fn test_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cms.xml.tmpl");
+        std::fs::write(&path, "<title>{{ metadata.title | escape }}</title>").unwrap();
+
+        let formatter = TemplateFormatter::from_file(&path).unwrap();
+        assert_eq!(formatter.extension(), "xml");
+        assert_eq!(formatter.mime_type(), "application/xml");
+        assert_eq!(
+            formatter.format(&sample_transcript(), &FormatContext::default()),
+            "<title>Episode 1</title>"
+        );
+
+        std::fs::write(&path, "{% if %}").unwrap();
+        assert!(matches!(
+            TemplateFormatter::from_file(&path),
+            Err(Error::InvalidFormatTemplate(reason)) if reason.contains("cms.xml.tmpl: line 1")
+        ));
+        assert!(matches!(
+            TemplateFormatter::from_file(&dir.path().join("missing.tmpl")),
+            Err(Error::FileNotFound(_))
+        ));
+    }
+}
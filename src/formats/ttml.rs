@@ -0,0 +1,353 @@
+// Voxtus - Transcribe YouTube videos and local media files to text
+// Copyright (C) 2024 Johan Thorén <johan@thoren.xyz>
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! TTML format output.
+//!
+//! XML subtitle format (also known as DFXP), following the IMSC1 text
+//! profile used by broadcast and streaming platforms. Times are media time
+//! clock values: `HH:MM:SS.mmm`.
+
+use super::vtt::format_timestamp;
+use super::{FormatContext, Formatter, Metadata, Segment, Transcript, chapter_for};
+
+/// IMSC1 text profile designator.
+pub const IMSC1_TEXT_PROFILE: &str = "http://www.w3.org/ns/ttml/profile/imsc1/text";
+
+/// Escape text for XML content and attribute values.
+///
+/// # Example
+/// ```
+/// use voxtus::formats::ttml::escape;
+///
+/// assert_eq!(escape("Q&A <live>"), "Q&amp;A &lt;live&gt;");
+/// ```
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than tab and newline are not allowed in XML
+            c if c.is_control() && c != '\t' && c != '\n' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Format a single segment as a `<p>` element.
+///
+/// `agent` is the ID of the segment's speaker, if any. Line breaks become
+/// `<br/>`.
+///
+/// # Example
+/// ```
+/// use voxtus::formats::{Segment, ttml::format_segment};
+///
+/// let segment = Segment::new(0.0, 5.2, "Hello world");
+/// assert_eq!(
+///     format_segment(&segment, None),
+///     r#"<p begin="00:00:00.000" end="00:00:05.200">Hello world</p>"#
+/// );
+/// ```
+pub fn format_segment(segment: &Segment, agent: Option<&str>) -> String {
+    let agent = agent
+        .map(|id| format!(" ttm:agent=\"{}\"", id))
+        .unwrap_or_default();
+    let text = segment
+        .text
+        .trim()
+        .lines()
+        .map(escape)
+        .collect::<Vec<_>>()
+        .join("<br/>");
+
+    format!(
+        "<p begin=\"{}\" end=\"{}\"{}>{}</p>",
+        format_timestamp(segment.start),
+        format_timestamp(segment.end),
+        agent,
+        text
+    )
+}
+
+/// Format segments and metadata as TTML output.
+///
+/// The document language comes from the metadata (`und` when unknown).
+/// Speakers are declared as `ttm:agent`s and each chapter gets its own
+/// `<div>`.
+pub fn format_transcript(segments: &[Segment], metadata: &Metadata) -> String {
+    let language = metadata.language.as_deref().unwrap_or("und");
+
+    let mut speakers: Vec<&str> = Vec::new();
+    for speaker in segments.iter().filter_map(|s| s.speaker.as_deref()) {
+        if !speakers.contains(&speaker) {
+            speakers.push(speaker);
+        }
+    }
+    let agent_id = |speaker: &str| {
+        let i = speakers.iter().position(|s| *s == speaker)?;
+        Some(format!("speaker{}", i + 1))
+    };
+
+    let mut lines = vec![
+        r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_string(),
+        format!(
+            "<tt xmlns=\"http://www.w3.org/ns/ttml\" \
+             xmlns:ttp=\"http://www.w3.org/ns/ttml#parameter\" \
+             xmlns:tts=\"http://www.w3.org/ns/ttml#styling\" \
+             xmlns:ttm=\"http://www.w3.org/ns/ttml#metadata\" \
+             ttp:profile=\"{}\" ttp:timeBase=\"media\" xml:lang=\"{}\">",
+            IMSC1_TEXT_PROFILE,
+            escape(language)
+        ),
+        "  <head>".to_string(),
+        "    <metadata>".to_string(),
+        format!("      <ttm:title>{}</ttm:title>", escape(&metadata.title)),
+        format!("      <ttm:desc>{}</ttm:desc>", escape(&metadata.source)),
+    ];
+    for (i, speaker) in speakers.iter().enumerate() {
+        lines.push(format!(
+            "      <ttm:agent xml:id=\"speaker{}\" type=\"person\"><ttm:name type=\"full\">{}</ttm:name></ttm:agent>",
+            i + 1,
+            escape(speaker)
+        ));
+    }
+    lines.extend([
+        "    </metadata>".to_string(),
+        "    <styling>".to_string(),
+        "      <style xml:id=\"default\" tts:fontFamily=\"proportionalSansSerif\" \
+         tts:fontSize=\"100%\" tts:lineHeight=\"125%\" tts:color=\"white\" \
+         tts:backgroundColor=\"rgba(0,0,0,191)\" tts:textAlign=\"center\"/>"
+            .to_string(),
+        "    </styling>".to_string(),
+        "    <layout>".to_string(),
+        "      <region xml:id=\"bottom\" tts:origin=\"10% 75%\" tts:extent=\"80% 20%\" \
+         tts:displayAlign=\"after\"/>"
+            .to_string(),
+        "    </layout>".to_string(),
+        "  </head>".to_string(),
+        "  <body region=\"bottom\" style=\"default\">".to_string(),
+    ]);
+
+    let chapters = &metadata.chapters;
+    let mut current = None;
+    let mut open = false;
+    for segment in segments {
+        let chapter = chapter_for(chapters, segment);
+        if !open || chapter != current {
+            if open {
+                lines.push("    </div>".to_string());
+            }
+            match chapter {
+                Some(i) => {
+                    lines.push("    <div>".to_string());
+                    lines.push(format!(
+                        "      <metadata><ttm:title>{}</ttm:title></metadata>",
+                        escape(&chapters[i].title)
+                    ));
+                }
+                None => lines.push("    <div>".to_string()),
+            }
+            current = chapter;
+            open = true;
+        }
+        let agent = segment.speaker.as_deref().and_then(agent_id);
+        lines.push(format!(
+            "      {}",
+            format_segment(segment, agent.as_deref())
+        ));
+    }
+    if open {
+        lines.push("    </div>".to_string());
+    }
+
+    lines.push("  </body>".to_string());
+    lines.push("</tt>".to_string());
+    lines.join("\n")
+}
+
+/// TTML output as a [`Formatter`].
+pub struct TtmlFormatter;
+
+impl Formatter for TtmlFormatter {
+    fn name(&self) -> &str {
+        "ttml"
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        &["dfxp"]
+    }
+
+    fn extension(&self) -> &str {
+        "ttml"
+    }
+
+    fn mime_type(&self) -> &str {
+        "application/ttml+xml"
+    }
+
+    fn description(&self) -> &str {
+        "TTML (IMSC1) subtitles for broadcast and streaming"
+    }
+
+    fn format(&self, transcript: &Transcript, _context: &FormatContext) -> String {
+        format_transcript(&transcript.segments, &transcript.metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::Chapter;
+
+    const TT_NS: &str = "http://www.w3.org/ns/ttml";
+    const TTM_NS: &str = "http://www.w3.org/ns/ttml#metadata";
+    const TTS_NS: &str = "http://www.w3.org/ns/ttml#styling";
+    const XML_NS: &str = "http://www.w3.org/XML/1998/namespace";
+
+    type Node<'a> = roxmltree::Node<'a, 'a>;
+
+    fn children<'a>(node: Node<'a>, ns: &'a str, name: &'a str) -> Vec<Node<'a>> {
+        node.children()
+            .filter(|c| c.has_tag_name((ns, name)))
+            .collect()
+    }
+
+    fn child<'a>(node: Node<'a>, ns: &'a str, name: &'a str) -> Node<'a> {
+        children(node, ns, name)[0]
+    }
+
+    fn sample_metadata() -> Metadata {
+        Metadata::new(
+            "Test Video",
+            "https://example.com/watch?v=1&list=2",
+            Some(10.5),
+            "tiny",
+            Some("sv".to_string()),
+        )
+    }
+
+    #[test]
+    fn test_format_transcript_is_well_formed() {
+        let mut metadata = sample_metadata();
+        metadata.title = "Tom & Jerry's <\"best\"> bits".to_string();
+        metadata.chapters = vec![
+            Chapter::new(0.0, 5.0, "Intro & welcome"),
+            Chapter::new(5.0, 10.5, "Q&A"),
+        ];
+        let mut segments = vec![
+            Segment::new(0.0, 5.2, "Hello <world> & \"friends\""),
+            Segment::new(5.2, 10.5, "Line one\nline two\u{1}"),
+        ];
+        segments[1].speaker = Some("Ann & Bo".to_string());
+
+        let ttml = format_transcript(&segments, &metadata);
+        let document = roxmltree::Document::parse(&ttml).unwrap();
+        let root = document.root_element();
+        assert!(root.has_tag_name((TT_NS, "tt")));
+        assert_eq!(root.attribute((XML_NS, "lang")), Some("sv"));
+
+        let head = child(root, TT_NS, "head");
+        let head_metadata = child(head, TT_NS, "metadata");
+        assert_eq!(
+            child(head_metadata, TTM_NS, "title").text(),
+            Some(metadata.title.as_str())
+        );
+        let agent = child(head_metadata, TTM_NS, "agent");
+        assert_eq!(child(agent, TTM_NS, "name").text(), Some("Ann & Bo"));
+
+        let divs = children(child(root, TT_NS, "body"), TT_NS, "div");
+        assert_eq!(divs.len(), 2);
+        let chapter = child(child(divs[1], TT_NS, "metadata"), TTM_NS, "title");
+        assert_eq!(chapter.text(), Some("Q&A"));
+        let first = child(divs[0], TT_NS, "p");
+        assert_eq!(first.text(), Some("Hello <world> & \"friends\""));
+        assert_eq!(first.attribute("begin"), Some("00:00:00.000"));
+        let second = child(divs[1], TT_NS, "p");
+        assert_eq!(second.attribute((TTM_NS, "agent")), Some("speaker1"));
+        assert_eq!(children(second, TT_NS, "br").len(), 1);
+    }
+
+    #[test]
+    fn test_format_transcript_without_segments_is_well_formed() {
+        let ttml = format_transcript(&[], &sample_metadata());
+        let document = roxmltree::Document::parse(&ttml).unwrap();
+        let body = child(document.root_element(), TT_NS, "body");
+        assert!(children(body, TT_NS, "div").is_empty());
+    }
+
+    #[test]
+    fn test_language_and_profile() {
+        let ttml = format_transcript(&[Segment::new(0.0, 1.0, "Hej")], &sample_metadata());
+        assert!(ttml.contains(" xml:lang=\"sv\""));
+        assert!(ttml.contains(&format!("ttp:profile=\"{}\"", IMSC1_TEXT_PROFILE)));
+
+        let mut metadata = sample_metadata();
+        metadata.language = None;
+        let ttml = format_transcript(&[], &metadata);
+        assert!(ttml.contains(" xml:lang=\"und\""));
+    }
+
+    #[test]
+    fn test_styling_and_region() {
+        let ttml = format_transcript(&[Segment::new(0.0, 1.0, "Hi")], &sample_metadata());
+        let document = roxmltree::Document::parse(&ttml).unwrap();
+        let root = document.root_element();
+        let head = child(root, TT_NS, "head");
+
+        let style = child(child(head, TT_NS, "styling"), TT_NS, "style");
+        assert_eq!(style.attribute((XML_NS, "id")), Some("default"));
+        // TTML rgba() components are integers from 0 to 255
+        assert_eq!(
+            style.attribute((TTS_NS, "backgroundColor")),
+            Some("rgba(0,0,0,191)")
+        );
+
+        let region = child(child(head, TT_NS, "layout"), TT_NS, "region");
+        assert_eq!(region.attribute((XML_NS, "id")), Some("bottom"));
+
+        let body = child(root, TT_NS, "body");
+        assert_eq!(body.attribute("region"), Some("bottom"));
+        assert_eq!(body.attribute("style"), Some("default"));
+    }
+
+    #[test]
+    fn test_segments_and_line_breaks() {
+        let segments = vec![Segment::new(1.5, 3.25, "Line one\nline <two>")];
+        let ttml = format_transcript(&segments, &sample_metadata());
+        assert!(ttml.contains(
+            "<p begin=\"00:00:01.500\" end=\"00:00:03.250\">Line one<br/>line &lt;two&gt;</p>"
+        ));
+    }
+
+    #[test]
+    fn test_speaker_agents() {
+        let mut segments = vec![Segment::new(0.0, 1.0, "Hi"), Segment::new(1.0, 2.0, "Hey")];
+        segments[0].speaker = Some("Alice".to_string());
+        segments[1].speaker = Some("Bob".to_string());
+
+        let ttml = format_transcript(&segments, &sample_metadata());
+        assert!(ttml.contains("<ttm:agent xml:id=\"speaker2\" type=\"person\"><ttm:name type=\"full\">Bob</ttm:name></ttm:agent>"));
+        assert!(ttml.contains("ttm:agent=\"speaker1\">Hi</p>"));
+        assert!(ttml.contains("ttm:agent=\"speaker2\">Hey</p>"));
+    }
+
+    #[test]
+    fn test_chapter_divs() {
+        let mut metadata = sample_metadata();
+        metadata.chapters = vec![
+            Chapter::new(0.0, 1.0, "Intro"),
+            Chapter::new(1.0, 2.0, "Main"),
+        ];
+        let segments = vec![Segment::new(0.0, 1.0, "Hi"), Segment::new(1.0, 2.0, "Hey")];
+
+        let ttml = format_transcript(&segments, &metadata);
+        assert_eq!(ttml.matches("<div>").count(), 2);
+        assert!(ttml.contains("<metadata><ttm:title>Main</ttm:title></metadata>"));
+    }
+}
@@ -6,8 +6,55 @@
 //!
 //! Plain text format with timestamps, designed to be LLM-friendly.
 //! Format: `[start - end]: text`
+//!
+//! The plain style drops the timestamps and joins segments into flowing
+//! paragraphs, wrapped at a configurable width.
+
+use super::md::{ends_sentence, paragraphs};
+use super::{Chapter, FormatContext, Formatter, Segment, Transcript, chapter_for};
+use crate::error::{Error, Result};
+
+/// Default line width of plain TXT output.
+pub const DEFAULT_TXT_WIDTH: usize = 80;
+
+/// Layout of TXT output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TxtStyle {
+    /// One `[start - end]: text` line per segment.
+    #[default]
+    Timestamps,
+    /// Wrapped paragraphs of text without timestamps.
+    Plain,
+}
+
+impl std::str::FromStr for TxtStyle {
+    type Err = Error;
 
-use super::Segment;
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "timestamps" => Ok(Self::Timestamps),
+            "plain" => Ok(Self::Plain),
+            _ => Err(Error::InvalidTxtStyle(s.to_string())),
+        }
+    }
+}
+
+/// Options for TXT output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxtOptions {
+    pub style: TxtStyle,
+    /// Line width of the plain style; `0` disables wrapping.
+    pub width: usize,
+}
+
+impl Default for TxtOptions {
+    fn default() -> Self {
+        Self {
+            style: TxtStyle::default(),
+            width: DEFAULT_TXT_WIDTH,
+        }
+    }
+}
 
 /// Format a single segment as a TXT line.
 ///
@@ -36,6 +83,179 @@ pub fn format_transcript(segments: &[Segment]) -> String {
         .join("\n")
 }
 
+/// Format segments as TXT output with a `# Title` heading for each chapter.
+///
+/// Headings are separated from the surrounding lines by blank lines. Without
+/// chapters this is the same as [`format_transcript`].
+///
+/// # Example
+/// ```
+/// use voxtus::formats::{Chapter, Segment, txt::format_transcript_with_chapters};
+///
+/// let segments = vec![Segment::new(0.0, 2.0, "Hi"), Segment::new(2.0, 4.0, "Bye")];
+/// let chapters = vec![Chapter::new(0.0, 2.0, "Start"), Chapter::new(2.0, 4.0, "End")];
+/// assert_eq!(
+///     format_transcript_with_chapters(&segments, &chapters),
+///     "# Start\n\n[0.00 - 2.00]: Hi\n\n# End\n\n[2.00 - 4.00]: Bye"
+/// );
+/// ```
+pub fn format_transcript_with_chapters(segments: &[Segment], chapters: &[Chapter]) -> String {
+    if chapters.is_empty() {
+        return format_transcript(segments);
+    }
+
+    let mut lines = Vec::new();
+    let mut current = None;
+    for segment in segments {
+        let chapter = chapter_for(chapters, segment);
+        if chapter != current {
+            if current.is_some() {
+                lines.push(String::new());
+            }
+            if let Some(i) = chapter {
+                lines.push(format!("# {}", chapters[i].title));
+                lines.push(String::new());
+            }
+            current = chapter;
+        }
+        lines.push(format_segment(segment));
+    }
+
+    lines.join("\n")
+}
+
+/// Length in characters of words joined by single spaces.
+fn joined_len(words: &[&str]) -> usize {
+    words.iter().map(|w| w.chars().count()).sum::<usize>() + words.len().saturating_sub(1)
+}
+
+/// Wrap text into lines of at most `width` characters.
+///
+/// Lines are filled greedily, but a line is broken early after a sentence
+/// end in its last third, so sentences tend to start on a new line. Words
+/// longer than `width` get a line of their own. A `width` of `0` only
+/// collapses whitespace.
+///
+/// # Example
+/// ```
+/// use voxtus::formats::txt::wrap;
+///
+/// assert_eq!(wrap("one two three four", 9), "one two\nthree\nfour");
+/// assert_eq!(
+///     wrap("The end is here. Next one starts", 20),
+///     "The end is here.\nNext one starts"
+/// );
+/// ```
+pub fn wrap(text: &str, width: usize) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if width == 0 {
+        return words.join(" ");
+    }
+
+    let mut lines = Vec::new();
+    let mut line: Vec<&str> = Vec::new();
+    for word in words {
+        let length = word.chars().count();
+        if !line.is_empty() && joined_len(&line) + 1 + length > width {
+            // Carry the words after the last sentence end over to the next
+            // line, when that sentence end is late enough and they still fit
+            let split = (0..line.len() - 1)
+                .rev()
+                .find(|&i| ends_sentence(line[i]))
+                .filter(|&i| joined_len(&line[..=i]) >= width * 2 / 3)
+                .filter(|&i| joined_len(&line[i + 1..]) + 1 + length <= width);
+            let rest = split.map_or_else(Vec::new, |i| line.split_off(i + 1));
+            lines.push(line.join(" "));
+            line = rest;
+        }
+        line.push(word);
+    }
+    if !line.is_empty() {
+        lines.push(line.join(" "));
+    }
+
+    lines.join("\n")
+}
+
+/// Format segments as plain TXT output: wrapped paragraphs without timestamps.
+///
+/// Segments are grouped into paragraphs as for Markdown (see
+/// [`paragraphs`]). Chapters get a `# Title` heading, and a paragraph with a
+/// known speaker starts with `Speaker:`. Paragraphs are separated by blank
+/// lines.
+///
+/// # Example
+/// ```
+/// use voxtus::formats::{Segment, txt::format_plain};
+///
+/// let segments = vec![
+///     Segment::new(0.0, 2.0, "Hello there."),
+///     Segment::new(2.0, 4.0, "How are you?"),
+///     Segment::new(9.0, 11.0, "After a pause."),
+/// ];
+/// assert_eq!(
+///     format_plain(&segments, &[], 80),
+///     "Hello there. How are you?\n\nAfter a pause."
+/// );
+/// ```
+pub fn format_plain(segments: &[Segment], chapters: &[Chapter], width: usize) -> String {
+    let mut blocks = Vec::new();
+    let mut current = None;
+    for paragraph in paragraphs(segments, chapters) {
+        let chapter = chapter_for(chapters, paragraph[0]);
+        if chapter != current {
+            if let Some(i) = chapter {
+                blocks.push(format!("# {}", chapters[i].title));
+            }
+            current = chapter;
+        }
+
+        let mut text = paragraph
+            .iter()
+            .map(|s| s.text.trim())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if let Some(speaker) = &paragraph[0].speaker {
+            text = format!("{}: {}", speaker, text);
+        }
+        blocks.push(wrap(&text, width));
+    }
+
+    blocks.join("\n\n")
+}
+
+/// TXT output as a [`Formatter`].
+#[derive(Debug, Clone, Default)]
+pub struct TxtFormatter {
+    pub options: TxtOptions,
+}
+
+impl Formatter for TxtFormatter {
+    fn name(&self) -> &str {
+        "txt"
+    }
+
+    fn extension(&self) -> &str {
+        "txt"
+    }
+
+    fn mime_type(&self) -> &str {
+        "text/plain"
+    }
+
+    fn description(&self) -> &str {
+        "Plain text, one timestamped line per segment or wrapped paragraphs"
+    }
+
+    fn format(&self, transcript: &Transcript, _context: &FormatContext) -> String {
+        let chapters = &transcript.metadata.chapters;
+        match self.options.style {
+            TxtStyle::Timestamps => format_transcript_with_chapters(&transcript.segments, chapters),
+            TxtStyle::Plain => format_plain(&transcript.segments, chapters, self.options.width),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -101,4 +321,115 @@ mod tests {
             "[3661.50 - 3665.00]: Over an hour"
         );
     }
+
+    #[test]
+    fn test_format_with_chapters_groups_segments() {
+        let segments = vec![
+            Segment::new(0.0, 1.0, "One"),
+            Segment::new(1.0, 2.0, "Two"),
+            Segment::new(10.0, 11.0, "Three"),
+        ];
+        let chapters = vec![
+            Chapter::new(0.0, 10.0, "First"),
+            Chapter::new(10.0, 20.0, "Second"),
+        ];
+        let expected = "# First\n\n[0.00 - 1.00]: One\n[1.00 - 2.00]: Two\n\n# Second\n\n[10.00 - 11.00]: Three";
+        assert_eq!(
+            format_transcript_with_chapters(&segments, &chapters),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_format_with_no_chapters_matches_plain() {
+        let segments = vec![Segment::new(0.0, 1.0, "One")];
+        assert_eq!(
+            format_transcript_with_chapters(&segments, &[]),
+            format_transcript(&segments)
+        );
+    }
+
+    #[test]
+    fn test_parse_style() {
+        assert_eq!("plain".parse::<TxtStyle>().unwrap(), TxtStyle::Plain);
+        assert_eq!(
+            "Timestamps".parse::<TxtStyle>().unwrap(),
+            TxtStyle::Timestamps
+        );
+        assert!(matches!(
+            "prose".parse::<TxtStyle>(),
+            Err(Error::InvalidTxtStyle(_))
+        ));
+    }
+
+    #[test]
+    fn test_wrap_respects_width() {
+        let text = "The quick brown fox jumps over the lazy dog and keeps running far away";
+        for line in wrap(text, 20).lines() {
+            assert!(line.chars().count() <= 20, "line too long: {line:?}");
+        }
+        assert_eq!(
+            wrap(text, 20)
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" "),
+            text
+        );
+    }
+
+    #[test]
+    fn test_wrap_breaks_after_sentence() {
+        // Greedy filling would put "Then" at the end of the first line
+        assert_eq!(
+            wrap("It was a dark night. Then it rained", 25),
+            "It was a dark night.\nThen it rained"
+        );
+    }
+
+    #[test]
+    fn test_wrap_ignores_early_sentence_end() {
+        // A sentence end in the first two thirds does not break the line
+        assert_eq!(
+            wrap("Yes. It was a dark and stormy night", 25),
+            "Yes. It was a dark and\nstormy night"
+        );
+    }
+
+    #[test]
+    fn test_wrap_long_word() {
+        assert_eq!(
+            wrap("a supercalifragilistic b", 10),
+            "a\nsupercalifragilistic\nb"
+        );
+    }
+
+    #[test]
+    fn test_wrap_zero_width() {
+        assert_eq!(wrap("  one\ntwo   three ", 0), "one two three");
+    }
+
+    #[test]
+    fn test_format_plain_with_chapters_and_speakers() {
+        let mut segments = vec![
+            Segment::new(0.0, 1.0, "Welcome."),
+            Segment::new(1.0, 2.0, "Thanks for having me."),
+            Segment::new(10.0, 11.0, "Next topic."),
+        ];
+        segments[0].speaker = Some("Host".to_string());
+        segments[1].speaker = Some("Guest".to_string());
+        segments[2].speaker = Some("Guest".to_string());
+        let chapters = vec![
+            Chapter::new(0.0, 10.0, "Intro"),
+            Chapter::new(10.0, 20.0, "Main"),
+        ];
+        assert_eq!(
+            format_plain(&segments, &chapters, 80),
+            "# Intro\n\nHost: Welcome.\n\nGuest: Thanks for having me.\n\n# Main\n\nGuest: Next topic."
+        );
+    }
+
+    #[test]
+    fn test_format_plain_empty() {
+        assert_eq!(format_plain(&[], &[], 80), "");
+    }
 }
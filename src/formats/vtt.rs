@@ -2,12 +2,14 @@
 // Copyright (C) 2024 Johan Thorén <johan@thoren.xyz>
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
-//! VTT (WebVTT) format output.
+//! VTT (WebVTT) format output and parsing.
 //!
 //! Web standard subtitle format with metadata support.
 //! Uses dot for milliseconds: `HH:MM:SS.mmm`
+//!
+//! Parsing is used to turn existing YouTube captions into segments.
 
-use super::{Metadata, Segment};
+use super::{FormatContext, Formatter, Metadata, Segment, Transcript};
 
 /// Format seconds as VTT timestamp (HH:MM:SS.mmm).
 ///
@@ -46,7 +48,20 @@ pub fn format_segment(segment: &Segment) -> String {
     )
 }
 
+/// Make free text safe for a NOTE block.
+///
+/// NOTE blocks end at the first blank line and may not contain `-->`.
+fn note_text(text: &str) -> String {
+    text.lines()
+        .map(|l| l.trim_end().replace("-->", "->"))
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// Format metadata as VTT NOTE blocks.
+///
+/// Source media fields are only included when present.
 pub fn format_metadata(metadata: &Metadata) -> String {
     let mut notes = Vec::new();
 
@@ -70,6 +85,39 @@ pub fn format_metadata(metadata: &Metadata) -> String {
     // Model
     notes.push(format!("NOTE Model\n{}", metadata.model));
 
+    // Transcript source
+    notes.push(format!(
+        "NOTE Transcript Source\n{}",
+        metadata.transcript_source.as_str()
+    ));
+
+    // Source media
+    let optional = [
+        ("Video ID", &metadata.id),
+        ("Uploader", &metadata.uploader),
+        ("Channel", &metadata.channel),
+        ("Upload Date", &metadata.upload_date),
+    ];
+    for (label, value) in optional {
+        if let Some(value) = value {
+            notes.push(format!("NOTE {}\n{}", label, note_text(value)));
+        }
+    }
+
+    if !metadata.tags.is_empty() {
+        notes.push(format!(
+            "NOTE Tags\n{}",
+            note_text(&metadata.tags.join(", "))
+        ));
+    }
+
+    if let Some(description) = &metadata.description {
+        let description = note_text(description);
+        if !description.is_empty() {
+            notes.push(format!("NOTE Description\n{}", description));
+        }
+    }
+
     notes.join("\n\n")
 }
 
@@ -91,6 +139,148 @@ pub fn format_transcript(segments: &[Segment], metadata: &Metadata) -> String {
     parts.join("\n\n")
 }
 
+/// Parse a VTT timestamp (`HH:MM:SS.mmm` or `MM:SS.mmm`) into seconds.
+///
+/// # Example
+/// ```
+/// use voxtus::formats::vtt::parse_timestamp;
+///
+/// assert_eq!(parse_timestamp("01:01:01.123"), Some(3661.123));
+/// assert_eq!(parse_timestamp("01:05.500"), Some(65.5));
+/// assert_eq!(parse_timestamp("nonsense"), None);
+/// ```
+pub fn parse_timestamp(timestamp: &str) -> Option<f64> {
+    let (clock, millis) = timestamp.trim().split_once('.')?;
+    let parts: Vec<u64> = clock
+        .split(':')
+        .map(|p| p.parse().ok())
+        .collect::<Option<_>>()?;
+    let (hours, minutes, seconds) = match parts.as_slice() {
+        [h, m, s] => (*h, *m, *s),
+        [m, s] => (0, *m, *s),
+        _ => return None,
+    };
+    if millis.len() != 3 {
+        return None;
+    }
+    let millis: u64 = millis.parse().ok()?;
+
+    Some((hours * 3600 + minutes * 60 + seconds) as f64 + millis as f64 / 1000.0)
+}
+
+/// Strip cue markup (`<c>`, `<b>`, inline `<00:00:01.000>` timestamps)
+/// and decode the basic character references.
+fn strip_cue_markup(line: &str) -> String {
+    let mut text = String::with_capacity(line.len());
+    let mut in_tag = false;
+    for c in line.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
+
+/// Parse VTT content into segments.
+///
+/// Header, NOTE, STYLE and REGION blocks and cue identifiers are skipped.
+/// YouTube's automatic captions repeat the previous line at the top of
+/// every cue to make the text roll; lines already emitted by the previous
+/// cue are dropped so each line appears once.
+///
+/// # Example
+/// ```
+/// use voxtus::formats::vtt::parse_transcript;
+///
+/// let vtt = "WEBVTT\n\n1\n00:00:00.000 --> 00:00:02.500\nHello <b>world</b>\n";
+/// let segments = parse_transcript(vtt);
+/// assert_eq!(segments.len(), 1);
+/// assert_eq!(segments[0].text, "Hello world");
+/// assert_eq!(segments[0].end, 2.5);
+/// ```
+pub fn parse_transcript(content: &str) -> Vec<Segment> {
+    let content = content.replace("\r\n", "\n");
+    let mut segments = Vec::new();
+    let mut previous_lines: Vec<String> = Vec::new();
+
+    for block in content.split("\n\n") {
+        let mut lines = block.lines().skip_while(|l| !l.contains("-->"));
+        let Some(timing) = lines.next() else {
+            continue;
+        };
+
+        let mut times = timing.split("-->");
+        let start = times.next().and_then(parse_timestamp);
+        // Cue settings such as `align:start` follow the end time
+        let end = times
+            .next()
+            .and_then(|t| t.split_whitespace().next())
+            .and_then(parse_timestamp);
+        let (Some(start), Some(end)) = (start, end) else {
+            continue;
+        };
+
+        let cue_lines: Vec<String> = lines
+            .map(strip_cue_markup)
+            .filter(|l| !l.is_empty())
+            .collect();
+        let new_lines: Vec<&String> = cue_lines
+            .iter()
+            .filter(|l| !previous_lines.contains(l))
+            .collect();
+
+        if !new_lines.is_empty() {
+            let text = new_lines
+                .iter()
+                .map(|l| l.as_str())
+                .collect::<Vec<_>>()
+                .join(" ");
+            segments.push(Segment::new(start, end, text));
+        }
+        if !cue_lines.is_empty() {
+            previous_lines = cue_lines;
+        }
+    }
+
+    segments
+}
+
+/// VTT output as a [`Formatter`].
+pub struct VttFormatter;
+
+impl Formatter for VttFormatter {
+    fn name(&self) -> &str {
+        "vtt"
+    }
+
+    fn extension(&self) -> &str {
+        "vtt"
+    }
+
+    fn mime_type(&self) -> &str {
+        "text/vtt"
+    }
+
+    fn description(&self) -> &str {
+        "WebVTT subtitles with metadata"
+    }
+
+    fn format(&self, transcript: &Transcript, _context: &FormatContext) -> String {
+        format_transcript(&transcript.segments, &transcript.metadata)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -214,6 +404,7 @@ mod tests {
         assert!(result.contains("NOTE Duration\n00:02:03.450"));
         assert!(result.contains("NOTE Language\nen"));
         assert!(result.contains("NOTE Model\nbase"));
+        assert!(result.contains("NOTE Transcript Source\nwhisper"));
     }
 
     #[test]
@@ -228,6 +419,36 @@ mod tests {
         assert!(result.contains("NOTE Model\nbase"));
     }
 
+    #[test]
+    fn test_format_metadata_video_fields() {
+        let mut metadata = sample_metadata();
+        metadata.id = Some("abc123".to_string());
+        metadata.uploader = Some("Uploader".to_string());
+        metadata.channel = Some("Channel".to_string());
+        metadata.upload_date = Some("2024-01-31".to_string());
+        metadata.tags = vec!["rust".to_string(), "audio".to_string()];
+        metadata.description = Some("Line one\n\nLine --> two".to_string());
+
+        let result = format_metadata(&metadata);
+
+        assert!(result.contains("NOTE Video ID\nabc123"));
+        assert!(result.contains("NOTE Uploader\nUploader"));
+        assert!(result.contains("NOTE Channel\nChannel"));
+        assert!(result.contains("NOTE Upload Date\n2024-01-31"));
+        assert!(result.contains("NOTE Tags\nrust, audio"));
+        // Blank lines and cue arrows would break the NOTE block
+        assert!(result.contains("NOTE Description\nLine one\nLine -> two"));
+    }
+
+    #[test]
+    fn test_format_metadata_omits_missing_video_fields() {
+        let result = format_metadata(&sample_metadata());
+        assert!(!result.contains("NOTE Video ID"));
+        assert!(!result.contains("NOTE Uploader"));
+        assert!(!result.contains("NOTE Tags"));
+        assert!(!result.contains("NOTE Description"));
+    }
+
     #[test]
     fn test_format_transcript_structure() {
         let segments = vec![
@@ -303,4 +524,54 @@ mod tests {
         assert!(timestamp.contains("."));
         assert!(!timestamp.contains(","));
     }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("00:00:00.000"), Some(0.0));
+        assert_eq!(parse_timestamp("00:00:05.500"), Some(5.5));
+        assert_eq!(parse_timestamp(" 02:00:00.000 "), Some(7200.0));
+        assert_eq!(parse_timestamp("00:05.5"), None);
+        assert_eq!(parse_timestamp("00:00:05,500"), None);
+    }
+
+    #[test]
+    fn test_parse_roundtrip() {
+        let segments = vec![
+            Segment::new(0.0, 2.0, "Subtitle 1"),
+            Segment::new(2.0, 4.5, "Subtitle 2"),
+        ];
+        let vtt = format_transcript(&segments, &sample_metadata());
+        assert_eq!(parse_transcript(&vtt), segments);
+    }
+
+    #[test]
+    fn test_parse_skips_blocks_and_settings() {
+        let vtt = "WEBVTT\nKind: captions\nLanguage: en\n\nSTYLE\n::cue { color: red }\n\nNOTE a note\n\nintro\n00:01.000 --> 00:03.000 align:start position:0%\nTom &amp; Jerry\nsecond line\n";
+        let segments = parse_transcript(vtt);
+        assert_eq!(
+            segments,
+            vec![Segment::new(1.0, 3.0, "Tom & Jerry second line")]
+        );
+    }
+
+    #[test]
+    fn test_parse_dedupes_rolling_auto_captions() {
+        let vtt = "WEBVTT\n\n\
+00:00:00.000 --> 00:00:02.000 align:start position:0%\n\
+hello<00:00:00.500><c> there</c>\n\n\
+00:00:02.000 --> 00:00:02.010 align:start position:0%\n\
+hello there\n \n\n\
+00:00:02.010 --> 00:00:04.000 align:start position:0%\n\
+hello there\n\
+general<00:00:02.500><c> kenobi</c>\n";
+
+        let segments = parse_transcript(vtt);
+        assert_eq!(
+            segments,
+            vec![
+                Segment::new(0.0, 2.0, "hello there"),
+                Segment::new(2.01, 4.0, "general kenobi"),
+            ]
+        );
+    }
 }
@@ -5,16 +5,22 @@
 //! Voxtus: Transcribe YouTube videos and local media files to text.
 //!
 //! This library provides the core functionality for the voxtus CLI tool.
-//! It supports downloading media from YouTube, processing local files,
-//! and transcribing audio using Whisper.
+//! It supports downloading media from YouTube (single videos, playlists and
+//! channels) and other sites, podcast feeds, processing local files, and
+//! transcribing audio using Whisper.
 
 pub mod audio;
+pub mod cache;
 pub mod cli;
 pub mod config;
+pub mod deps;
 pub mod download;
 pub mod error;
+pub mod feed;
 pub mod formats;
 pub mod logging;
+pub mod output;
+pub mod playlist;
 pub mod signals;
 pub mod transcribe;
 
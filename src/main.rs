@@ -6,14 +6,30 @@
 
 use std::path::{Path, PathBuf};
 
-use voxtus::audio::{check_ffmpeg, convert_to_mp3};
-use voxtus::cli::Args;
-use voxtus::config::{AVAILABLE_MODELS, Config, OutputFormat, is_url};
-use voxtus::download::download_audio_sync;
-use voxtus::formats::Transcript;
+use voxtus::audio::{
+    KeepFormat, check_ffmpeg, export_audio, extract_audio_stream, is_audio_file, preprocess_audio,
+    probe_media_info,
+};
+use voxtus::cache::{MediaCache, TranscriptCache, hash_file, media_cache_key};
+use voxtus::cli::{Args, CacheAction, Command, DepsAction};
+use voxtus::config::{AVAILABLE_MODELS, Config, ConfigFile, SubsMode, is_url};
+use voxtus::deps::{self, BinarySource};
+use voxtus::download::{
+    Downloaders, VideoInfo, YtDlpDownloader, download_subtitles_sync, fetch_playlist_sync,
+    fetch_video_info_sync, is_playlist_url, select_subtitle_track,
+};
+use voxtus::feed::{FeedState, ProcessedEpisode, is_feed_input, load_feed};
+use voxtus::formats::{
+    FormatContext, Formatters, Metadata, Transcript, TranscriptSource, json, vtt,
+};
 use voxtus::logging::setup_logger;
+use voxtus::output::{
+    CollisionPolicy, NameFields, append_to_name, copy_atomic, output_file_name,
+    render_output_template, sanitize_filename, suffixed_path, unique_output_name, write_atomic,
+};
+use voxtus::playlist::{IndexEntry, IndexFormat, PlaylistIndex, entry_name_fields};
 use voxtus::signals::{setup_signal_handlers, shutdown_requested};
-use voxtus::transcribe::transcribe;
+use voxtus::transcribe::{DECODING_OPTIONS, transcribe};
 
 fn main() {
     // Set up signal handlers
@@ -34,6 +50,33 @@ fn run() -> i32 {
         return 0;
     }
 
+    // Handle --list-formats
+    if args.list_formats {
+        print_available_formats();
+        return 0;
+    }
+
+    // Handle --json-schema
+    if args.json_schema {
+        println!("{:#}", json::json_schema());
+        return 0;
+    }
+
+    // Handle `voxtus deps ...` and `voxtus cache ...`
+    if let Some(command) = &args.command {
+        let result = match command {
+            Command::Deps { action } => run_deps(&args, *action),
+            Command::Cache { action } => run_cache(&args, *action),
+        };
+        return match result {
+            Ok(()) => 0,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                1
+            }
+        };
+    }
+
     // Create config from args
     let config = match Config::from_args(&args) {
         Ok(c) => c,
@@ -49,6 +92,8 @@ fn run() -> i32 {
         return 1;
     }
 
+    deps::set_source(config.binaries);
+
     // Check ffmpeg is available
     if let Err(e) = check_ffmpeg() {
         log::error!("{}", e);
@@ -70,80 +115,624 @@ fn run() -> i32 {
 
 /// Main processing workflow.
 fn process(config: &Config) -> voxtus::Result<()> {
-    // Create temp directory for intermediate files (auto-cleaned on drop)
-    let temp_dir = tempfile::tempdir()?;
-
-    // Determine input type and get audio file
-    let (audio_path, title) = if is_url(&config.input_path) {
-        download_and_convert(config, temp_dir.path())?
-    } else {
-        convert_local_file(config, temp_dir.path())?
-    };
+    if config.feed || is_feed_input(&config.input_path) {
+        return process_feed(config);
+    }
 
-    // Check for shutdown
-    if shutdown_requested() {
-        log::info!("Interrupted, exiting.");
-        return Ok(());
+    if is_url(&config.input_path) && is_playlist_url(&config.input_path) {
+        return process_playlist(config);
     }
 
-    // Transcribe
-    let transcript = transcribe(
-        &audio_path,
-        temp_dir.path(),
-        &title,
+    process_media(
+        config,
         &config.input_path,
-        &config.model,
+        &Naming::new(&config.output_template),
+        None,
     )?;
+    Ok(())
+}
+
+/// How to name the outputs of one input: an output template, and the
+/// playlist or feed fields it can use.
+struct Naming<'a> {
+    template: &'a str,
+    index: Option<String>,
+    /// Playlist or feed title; set for playlist entries and feed episodes.
+    playlist: Option<String>,
+}
+
+impl<'a> Naming<'a> {
+    fn new(template: &'a str) -> Self {
+        Self {
+            template,
+            index: None,
+            playlist: None,
+        }
+    }
+}
+
+/// Transcribe a single URL or local file.
+///
+/// `known` is metadata from a listing such as a playlist or podcast feed,
+/// which takes precedence over what the download reports.
+///
+/// Returns the paths of all files written, which is empty when interrupted.
+fn process_media(
+    config: &Config,
+    input: &str,
+    naming: &Naming,
+    known: Option<&VideoInfo>,
+) -> voxtus::Result<Vec<PathBuf>> {
+    // Create temp directory for intermediate files (auto-cleaned on drop)
+    let temp_dir = tempfile::tempdir()?;
+
+    // Use existing subtitles if requested and available, otherwise transcribe
+    let (mut transcript, info, audio_path, fetched) =
+        match fetch_subtitles(config, input, temp_dir.path())? {
+            Subtitles::Found(transcript, info) => {
+                let mut transcript = *transcript;
+                transcript.metadata.created_at = Some(now_rfc3339());
+                let merged = merge_video_info(known, info.clone());
+                (transcript, merged, None, Some(info))
+            }
+            Subtitles::Missing(fetched) => {
+                let (audio_path, info) = acquire_audio(config, input, temp_dir.path(), fetched)?;
+                let info = merge_video_info(known, info);
+
+                // Check for shutdown
+                if shutdown_requested() {
+                    log::info!("Interrupted, exiting.");
+                    return Ok(Vec::new());
+                }
+
+                let transcript =
+                    transcribe_cached(config, &audio_path, temp_dir.path(), &info.title, input)?;
+                (transcript, info, Some(audio_path), None)
+            }
+        };
+    apply_video_info(&mut transcript.metadata, info);
 
     // Check for shutdown
     if shutdown_requested() {
         log::info!("Interrupted, exiting.");
-        return Ok(());
+        return Ok(Vec::new());
     }
 
+    // Prepare kept audio before naming, since its extension can depend on
+    // the source
+    let kept_audio = if config.keep_audio {
+        let source = match audio_path {
+            Some(path) => path,
+            None => acquire_audio(config, input, temp_dir.path(), fetched)?.0,
+        };
+        Some(prepare_kept_audio(config, &source, temp_dir.path())?)
+    } else {
+        None
+    };
+    let kept_extension = kept_audio
+        .as_ref()
+        .and_then(|p| p.extension())
+        .map(|e| e.to_string_lossy().to_string());
+
     // Output results
-    output_transcript(&transcript, config)?;
+    let output_name = render_output_name(&transcript.metadata, input, naming, config);
+    // Keep all outputs of this input under one suffixed name
+    let output_name = if config.collision == CollisionPolicy::Suffix
+        && !config.stdout_mode
+        && !config.split_by_chapter
+    {
+        let mut extensions: Vec<&str> = config.formats.iter().map(|f| f.extension()).collect();
+        extensions.extend(kept_extension.as_deref());
+        unique_output_name(&config.output_dir, &output_name, &extensions)
+    } else {
+        output_name
+    };
+    // The HTML viewer plays the kept audio, which sits next to it
+    let media = kept_extension.as_ref().and_then(|extension| {
+        Path::new(&output_file_name(&output_name, extension))
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+    });
+    let mut written = if config.split_by_chapter {
+        output_chapters(&transcript, &output_name, media.as_deref(), config)?
+    } else {
+        output_transcript(&transcript, &output_name, media.as_deref(), config)?
+    };
 
     // Keep audio if requested
-    if config.keep_audio {
+    if let (Some(kept_audio), Some(extension)) = (kept_audio, kept_extension) {
         let final_audio = config
             .output_dir
-            .join(format!("{}.mp3", get_output_name(&title, config)));
-        std::fs::copy(&audio_path, &final_audio)?;
+            .join(output_file_name(&output_name, &extension));
+        create_parent_dir(&final_audio)?;
+        if let Some(final_audio) = resolve_collision(&final_audio, config)? {
+            copy_atomic(&kept_audio, &final_audio)?;
+            if !config.stdout_mode {
+                log::info!("Audio saved: {}", final_audio.display());
+            }
+            written.push(final_audio);
+        }
+    }
+
+    Ok(written)
+}
+
+/// Get the audio file to keep, in the format chosen with `--keep-format`.
+///
+/// The original is the source file itself for audio-only files, and
+/// otherwise its audio stream copied out without re-encoding.
+fn prepare_kept_audio(config: &Config, source: &Path, temp_dir: &Path) -> voxtus::Result<PathBuf> {
+    match &config.keep_format {
+        KeepFormat::Original if is_audio_file(source) => Ok(source.to_path_buf()),
+        KeepFormat::Original => extract_audio_stream(source, temp_dir, "kept"),
+        KeepFormat::Encode { codec, bitrate } => {
+            if !config.stdout_mode {
+                log::info!("Exporting audio as {}", codec.extension());
+            }
+            let output = temp_dir.join(format!("kept.{}", codec.extension()));
+            export_audio(source, &output, *codec, bitrate.as_deref())?;
+            Ok(output)
+        }
+    }
+}
+
+/// Transcribe audio, reusing an earlier transcript of the same audio.
+///
+/// The audio is preprocessed first when filters are configured. The cache
+/// is keyed by the original audio content, model, decoding options and
+/// filters, so only the title and source are taken from this run.
+fn transcribe_cached(
+    config: &Config,
+    audio_path: &Path,
+    temp_dir: &Path,
+    title: &str,
+    source: &str,
+) -> voxtus::Result<Transcript> {
+    let filters = &config.audio_filters;
+    let options = if filters.is_empty() {
+        DECODING_OPTIONS.to_string()
+    } else {
+        format!("{};af={}", DECODING_OPTIONS, filters.chain())
+    };
+
+    let audio_hash = hash_file(audio_path)
+        .inspect_err(|e| log::warn!("Could not hash audio: {}", e))
+        .ok();
+    let cache = match audio_hash {
+        Some(hash) if !config.no_cache => {
+            let key = TranscriptCache::key_for_hash(hash, &config.model, &options);
+            TranscriptCache::open_default()
+                .map(|cache| (key, cache))
+                .inspect_err(|e| log::warn!("Transcript cache unavailable: {}", e))
+                .ok()
+        }
+        _ => None,
+    };
+
+    if let Some((key, cache)) = &cache
+        && let Some(mut transcript) = cache.get(key)
+    {
+        if !config.stdout_mode {
+            log::info!("Using cached transcript: {}", title);
+        }
+        transcript.metadata.title = title.to_string();
+        transcript.metadata.source = source.to_string();
+        return Ok(transcript);
+    }
+
+    let mut transcript = if filters.is_empty() {
+        transcribe(audio_path, temp_dir, title, source, &config.model)?
+    } else {
+        if !config.stdout_mode {
+            log::info!("Preprocessing audio: {}", filters.chain());
+        }
+        let preprocessed = temp_dir.join("preprocessed.wav");
+        preprocess_audio(audio_path, &preprocessed, filters)?;
+
+        let mut transcript = transcribe(&preprocessed, temp_dir, title, source, &config.model)?;
+        // Map timestamps of sped up or slowed down audio back to the original
+        if filters.tempo != 1.0 {
+            for segment in &mut transcript.segments {
+                segment.start *= filters.tempo;
+                segment.end *= filters.tempo;
+                for word in &mut segment.words {
+                    word.start *= filters.tempo;
+                    word.end *= filters.tempo;
+                }
+            }
+            transcript.metadata.duration = transcript.metadata.duration.map(|d| d * filters.tempo);
+        }
+        transcript.metadata.audio_filters = Some(filters.chain());
+        transcript
+    };
+    transcript.metadata.decoding_options = Some(DECODING_OPTIONS.to_string());
+    transcript.metadata.audio_hash = audio_hash.map(|hash| format!("fnv1a64:{:016x}", hash));
+    transcript.metadata.created_at = Some(now_rfc3339());
+
+    if let Some((key, cache)) = &cache
+        && let Err(e) = cache.put(key, &config.model, &options, &transcript)
+    {
+        log::warn!("Could not cache transcript: {}", e);
+    }
+
+    Ok(transcript)
+}
+
+/// Current time as an RFC 3339 UTC timestamp, for transcript metadata.
+fn now_rfc3339() -> String {
+    chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+/// Get the audio of the input, downloading it first for URLs.
+///
+/// `fetched` is video info already fetched for the URL, which the download
+/// then doesn't fetch again.
+fn acquire_audio(
+    config: &Config,
+    input: &str,
+    temp_dir: &Path,
+    fetched: Option<VideoInfo>,
+) -> voxtus::Result<(PathBuf, VideoInfo)> {
+    if is_url(input) {
+        download_media(config, input, temp_dir, fetched)
+    } else {
+        probe_local_file(input)
+    }
+}
+
+/// Outcome of looking for existing subtitles.
+enum Subtitles {
+    /// A transcript built from the subtitles, and the video info.
+    Found(Box<Transcript>, VideoInfo),
+    /// Transcribe with Whisper instead, reusing the video info if it was
+    /// already fetched.
+    Missing(Option<VideoInfo>),
+}
+
+/// Build a transcript from the video's existing subtitles.
+///
+/// Whisper is used instead when subtitles are disabled, or (with
+/// `--subs prefer`) for local files and videos without creator-uploaded
+/// subtitles. With `--subs only`, missing subtitles are an error.
+fn fetch_subtitles(config: &Config, input: &str, temp_dir: &Path) -> voxtus::Result<Subtitles> {
+    if config.subs == SubsMode::Never {
+        return Ok(Subtitles::Missing(None));
+    }
+    if !is_url(input) {
+        if config.subs == SubsMode::Only {
+            return Err(voxtus::Error::NoSubtitles(input.to_string()));
+        }
+        return Ok(Subtitles::Missing(None));
+    }
+
+    // Only yt-dlp knows how to find subtitles
+    let downloaders = Downloaders::with_yt_dlp_options(config.yt_dlp.clone());
+    let downloader = downloaders.select(input, config.downloader.as_deref())?;
+    if downloader.name() != YtDlpDownloader::NAME {
+        if config.subs == SubsMode::Only {
+            return Err(voxtus::Error::NoSubtitles(input.to_string()));
+        }
+        return Ok(Subtitles::Missing(None));
+    }
+
+    let info = fetch_video_info_sync(input, temp_dir, &config.yt_dlp)?;
+    let Some(track) = select_subtitle_track(&info, config.subs == SubsMode::Only) else {
+        if config.subs == SubsMode::Only {
+            return Err(voxtus::Error::NoSubtitles(input.to_string()));
+        }
+        if !config.stdout_mode {
+            log::info!("No subtitles found, transcribing with Whisper");
+        }
+        return Ok(Subtitles::Missing(Some(info)));
+    };
+
+    if !config.stdout_mode {
+        let kind = if track.automatic {
+            "automatic captions"
+        } else {
+            "subtitles"
+        };
+        log::info!("Using {} ({})", kind, track.language);
+    }
+
+    let segments = match download_subtitles_sync(input, &track, temp_dir, &config.yt_dlp)
+        .and_then(|path| Ok(std::fs::read_to_string(path)?))
+    {
+        Ok(vtt) => vtt::parse_transcript(&vtt),
+        Err(e) if config.subs == SubsMode::Prefer => {
+            log::warn!(
+                "Could not download subtitles, transcribing with Whisper: {}",
+                e
+            );
+            return Ok(Subtitles::Missing(Some(info)));
+        }
+        Err(e) => return Err(e),
+    };
+    if segments.is_empty() {
+        if config.subs == SubsMode::Only {
+            return Err(voxtus::Error::NoSubtitles(input.to_string()));
+        }
+        log::warn!("Subtitles are empty, transcribing with Whisper");
+        return Ok(Subtitles::Missing(Some(info)));
+    }
+
+    let mut metadata = Metadata::new(
+        &info.title,
+        input,
+        segments.last().map(|s| s.end),
+        "none",
+        Some(track.language.clone()),
+    );
+    metadata.transcript_source = if track.automatic {
+        TranscriptSource::AutoCaptions
+    } else {
+        TranscriptSource::Subtitles
+    };
+
+    Ok(Subtitles::Found(
+        Box::new(Transcript::new(segments, metadata)),
+        info,
+    ))
+}
+
+/// Transcribe every entry of a playlist or channel and write a playlist index.
+///
+/// Entries whose outputs already exist are skipped, so an interrupted or
+/// scheduled run only picks up new videos.
+fn process_playlist(config: &Config) -> voxtus::Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+
+    if !config.stdout_mode {
+        log::info!("Listing playlist: {}", config.input_path);
+    }
+    let playlist = fetch_playlist_sync(&config.input_path, temp_dir.path(), &config.yt_dlp)?;
+    if !config.stdout_mode {
+        log::info!(
+            "Found {} entries in '{}'",
+            playlist.entries.len(),
+            playlist.title
+        );
+    }
+
+    let index_name = get_output_name(&playlist.title, config);
+    let index_path = |format: IndexFormat| {
+        config
+            .output_dir
+            .join(format!("{}.index.{}", index_name, format.extension()))
+    };
+    let mut index = PlaylistIndex::load(&index_path(IndexFormat::Json))?
+        .unwrap_or_else(|| PlaylistIndex::new(&playlist, &config.input_path));
+
+    let mut failed = 0;
+    for entry in &playlist.entries {
+        if shutdown_requested() {
+            log::info!("Interrupted, exiting.");
+            break;
+        }
+
+        // Name from the listing, to find outputs of an earlier run; the
+        // transcript is named the same way, as listing metadata comes first
+        let fields = NameFields {
+            model: config.model.clone(),
+            ..entry_name_fields(entry, &playlist)
+        };
+        let name = render_output_template(&config.playlist_template, &fields);
+        let expected: Vec<PathBuf> = config
+            .formats
+            .iter()
+            .map(|f| {
+                config
+                    .output_dir
+                    .join(output_file_name(&name, f.extension()))
+            })
+            .collect();
+
+        let outputs = if !config.stdout_mode
+            && (index.is_complete(&entry.id, &config.output_dir)
+                || expected.iter().all(|p| p.exists()))
+        {
+            log::info!("Skipping (already transcribed): {}", entry.title);
+            if index.get(&entry.id).is_some() {
+                continue;
+            }
+            expected
+        } else {
+            if !config.stdout_mode {
+                log::info!(
+                    "[{}/{}] {}",
+                    entry.index,
+                    playlist.entries.len(),
+                    entry.title
+                );
+            }
+            let naming = Naming {
+                index: fields.index.clone(),
+                playlist: fields.playlist.clone(),
+                ..Naming::new(&config.playlist_template)
+            };
+            let known = VideoInfo {
+                id: Some(entry.id.clone()),
+                title: entry.title.clone(),
+                ..Default::default()
+            };
+            match process_media(config, &entry.url, &naming, Some(&known)) {
+                Ok(outputs) => outputs,
+                Err(e) => {
+                    log::error!("Failed to transcribe '{}': {}", entry.title, e);
+                    failed += 1;
+                    continue;
+                }
+            }
+        };
+
+        if outputs.is_empty() || config.stdout_mode {
+            continue;
+        }
+
+        index.upsert(IndexEntry {
+            index: entry.index,
+            id: entry.id.clone(),
+            title: entry.title.clone(),
+            url: entry.url.clone(),
+            outputs: relative_outputs(&outputs, config),
+        });
+
+        // Save progress after every entry so an interrupted run can resume
+        for format in &config.index_formats {
+            write_atomic(&index_path(*format), index.format(*format))?;
+        }
+    }
+
+    if !config.stdout_mode {
+        for format in &config.index_formats {
+            log::info!("Index saved: {}", index_path(*format).display());
+        }
+    }
+
+    if failed > 0 {
+        return Err(voxtus::Error::PlaylistEntriesFailed(failed));
+    }
+
+    Ok(())
+}
+
+/// Transcribe the new episodes of a podcast feed.
+///
+/// Processed episode GUIDs are recorded in `<show>.feed.json` in the output
+/// directory, so later runs only transcribe episodes published since.
+fn process_feed(config: &Config) -> voxtus::Result<()> {
+    if !config.stdout_mode {
+        log::info!("Reading feed: {}", config.input_path);
+    }
+    let feed = load_feed(&config.input_path)?;
+    if !config.stdout_mode {
+        log::info!("Found {} episodes in '{}'", feed.episodes.len(), feed.title);
+    }
+
+    let state_path = config.output_dir.join(format!(
+        "{}.feed.json",
+        get_output_name(&feed.title, config)
+    ));
+    let mut state =
+        FeedState::load(&state_path)?.unwrap_or_else(|| FeedState::new(&feed, &config.input_path));
+
+    let mut failed = 0;
+    // Feeds list the newest episode first; transcribe in publishing order
+    for episode in feed.episodes.iter().rev() {
+        if shutdown_requested() {
+            log::info!("Interrupted, exiting.");
+            break;
+        }
+
+        if state.is_processed(&episode.guid) {
+            log::debug!("Skipping (already transcribed): {}", episode.title);
+            continue;
+        }
+
         if !config.stdout_mode {
-            log::info!("Audio saved: {}", final_audio.display());
+            log::info!("Episode: {}", episode.title);
         }
+        let naming = Naming {
+            playlist: Some(feed.title.clone()),
+            ..Naming::new(&config.episode_template)
+        };
+        let info = episode.video_info(&feed);
+        let outputs = match process_media(config, &episode.enclosure_url, &naming, Some(&info)) {
+            Ok(outputs) => outputs,
+            Err(e) => {
+                log::error!("Failed to transcribe '{}': {}", episode.title, e);
+                failed += 1;
+                continue;
+            }
+        };
+
+        if outputs.is_empty() || config.stdout_mode {
+            continue;
+        }
+
+        state.mark_processed(ProcessedEpisode {
+            guid: episode.guid.clone(),
+            title: episode.title.clone(),
+            published: episode.published.clone(),
+            outputs: relative_outputs(&outputs, config),
+        });
+
+        // Save after every episode so an interrupted run can resume
+        write_atomic(&state_path, state.to_json())?;
+        log::info!("Feed state saved: {}", state_path.display());
+    }
+
+    if failed > 0 {
+        return Err(voxtus::Error::FeedEpisodesFailed(failed));
     }
 
     Ok(())
 }
 
-/// Download from URL and convert to MP3.
-fn download_and_convert(config: &Config, temp_dir: &Path) -> voxtus::Result<(PathBuf, String)> {
+/// Download audio from a URL, going through the media cache.
+///
+/// The stream is used as downloaded; Whisper decodes any format.
+fn download_media(
+    config: &Config,
+    url: &str,
+    temp_dir: &Path,
+    fetched: Option<VideoInfo>,
+) -> voxtus::Result<(PathBuf, VideoInfo)> {
+    let cache = if !config.no_cache && config.media_cache_size > 0 {
+        MediaCache::open_default(config.media_cache_size)
+            .inspect_err(|e| log::warn!("Media cache unavailable: {}", e))
+            .ok()
+    } else {
+        None
+    };
+    let downloaders = Downloaders::with_yt_dlp_options(config.yt_dlp.clone());
+    let downloader = downloaders.select(url, config.downloader.as_deref())?;
+    // Streams picked by another backend or format selection are different files
+    let key = media_cache_key(
+        url,
+        &format!("{}|{}", downloader.name(), config.yt_dlp.format_selector()),
+    );
+
+    if let Some((path, info)) = cache.as_ref().and_then(|c| c.get(&key)) {
+        if !config.stdout_mode {
+            log::info!("Using cached download: {}", info.title);
+        }
+        return Ok((path, info));
+    }
+
     if !config.stdout_mode {
-        log::info!("Downloading: {}", config.input_path);
+        log::info!("Downloading: {}", url);
     }
 
-    // Download audio using yt-dlp (returns m4a format)
-    let (downloaded_path, info) = download_audio_sync(&config.input_path, temp_dir)?;
+    // Download audio with the selected backend (yt-dlp returns m4a or webm)
+    log::debug!("Using downloader: {}", downloader.name());
+    let (downloaded_path, info) = match fetched {
+        Some(info) => downloader.download_audio_with_info(url, temp_dir, info)?,
+        None => downloader.download_audio(url, temp_dir)?,
+    };
 
     if !config.stdout_mode {
         log::info!("Downloaded: {}", info.title);
     }
 
-    // Convert to MP3 using our ffmpeg wrapper
-    let mp3_path = temp_dir.join("audio.mp3");
-    convert_to_mp3(&downloaded_path, &mp3_path)?;
+    // A failing cache must not fail the transcription
+    if let Some(cache) = &cache
+        && let Err(e) = cache.put(&key, &downloaded_path, &info)
+    {
+        log::warn!("Could not cache download: {}", e);
+    }
 
-    Ok((mp3_path, info.title))
+    Ok((downloaded_path, info))
 }
 
-/// Convert a local file to MP3.
-fn convert_local_file(config: &Config, temp_dir: &Path) -> voxtus::Result<(PathBuf, String)> {
-    let input_path = Path::new(&config.input_path);
+/// Check a local file and read its metadata.
+///
+/// The file is transcribed in place, without conversion.
+fn probe_local_file(input: &str) -> voxtus::Result<(PathBuf, VideoInfo)> {
+    let input_path = Path::new(input);
 
     if !input_path.exists() {
-        return Err(voxtus::Error::FileNotFound(config.input_path.clone()));
+        return Err(voxtus::Error::FileNotFound(input.to_string()));
     }
 
     let title = input_path
@@ -152,60 +741,166 @@ fn convert_local_file(config: &Config, temp_dir: &Path) -> voxtus::Result<(PathB
         .unwrap_or("audio")
         .to_string();
 
-    if !config.stdout_mode {
-        log::info!("Converting: {}", input_path.display());
-    }
+    // Tags are a nice-to-have, so a failing ffprobe only loses metadata
+    let info = probe_media_info(input_path, &title).unwrap_or_else(|e| {
+        log::debug!("Could not read media tags: {}", e);
+        VideoInfo {
+            title,
+            ..Default::default()
+        }
+    });
 
-    // If already MP3, just copy
-    let audio_path = if input_path.extension().is_some_and(|e| e == "mp3") {
-        let dest = temp_dir.join("audio.mp3");
-        std::fs::copy(input_path, &dest)?;
-        dest
-    } else {
-        let output_path = temp_dir.join("audio.mp3");
-        convert_to_mp3(input_path, &output_path)?;
-        output_path
+    Ok((input_path.to_path_buf(), info))
+}
+
+/// Prefer metadata known from a listing over what the download reported.
+///
+/// The download still provides duration, chapters and language.
+fn merge_video_info(known: Option<&VideoInfo>, info: VideoInfo) -> VideoInfo {
+    let Some(known) = known else {
+        return info;
     };
 
-    Ok((audio_path, title))
+    VideoInfo {
+        id: known.id.clone().or(info.id),
+        title: if known.title.is_empty() {
+            info.title
+        } else {
+            known.title.clone()
+        },
+        uploader: known.uploader.clone().or(info.uploader),
+        channel: known.channel.clone().or(info.channel),
+        upload_date: known.upload_date.clone().or(info.upload_date),
+        description: known.description.clone().or(info.description),
+        ..info
+    }
+}
+
+/// Copy source media metadata into the transcript metadata.
+fn apply_video_info(metadata: &mut Metadata, info: VideoInfo) {
+    metadata.id = info.id;
+    metadata.uploader = info.uploader;
+    metadata.channel = info.channel;
+    metadata.upload_date = info.upload_date;
+    metadata.description = info.description;
+    metadata.tags = info.tags;
+    metadata.chapters = info.chapters;
+
+    // The container duration covers trailing silence the segments don't
+    if info.duration.is_some() {
+        metadata.duration = info.duration;
+    }
 }
 
 /// Output transcript in requested formats.
-fn output_transcript(transcript: &Transcript, config: &Config) -> voxtus::Result<()> {
-    let output_name = get_output_name(&transcript.metadata.title, config);
+///
+/// `media` is the kept audio file, relative to the output files. Returns
+/// the paths of the files written.
+fn output_transcript(
+    transcript: &Transcript,
+    output_name: &str,
+    media: Option<&str>,
+    config: &Config,
+) -> voxtus::Result<Vec<PathBuf>> {
+    let mut written = Vec::new();
+    let context = FormatContext {
+        media: media.map(str::to_string),
+    };
 
     for format in &config.formats {
-        let content = match format {
-            OutputFormat::Txt => transcript.to_txt(),
-            OutputFormat::Json => transcript.to_json(),
-            OutputFormat::Srt => transcript.to_srt(),
-            OutputFormat::Vtt => transcript.to_vtt(),
-        };
+        let content = format.format(transcript, &context);
 
         if config.stdout_mode {
             println!("{}", content);
         } else {
-            let output_path =
-                config
-                    .output_dir
-                    .join(format!("{}.{}", output_name, format.extension()));
-
-            // Check for overwrite
-            if output_path.exists() && !config.overwrite_files {
-                eprint!("File '{}' exists. Overwrite? [y/N] ", output_path.display());
-                let mut response = String::new();
-                if std::io::stdin().read_line(&mut response).is_err()
-                    || !response.trim().eq_ignore_ascii_case("y")
-                {
-                    return Err(voxtus::Error::UserAborted);
-                }
-            }
+            let output_path = config
+                .output_dir
+                .join(output_file_name(output_name, format.extension()));
+            create_parent_dir(&output_path)?;
+
+            let Some(output_path) = resolve_collision(&output_path, config)? else {
+                continue;
+            };
 
-            std::fs::write(&output_path, content)?;
+            write_atomic(&output_path, content)?;
             log::info!("Saved: {}", output_path.display());
+            written.push(output_path);
         }
     }
 
+    Ok(written)
+}
+
+/// Output one transcript per chapter, named `<name> - <NN> <chapter>`.
+///
+/// Falls back to a single transcript when the media has no chapters.
+fn output_chapters(
+    transcript: &Transcript,
+    output_name: &str,
+    media: Option<&str>,
+    config: &Config,
+) -> voxtus::Result<Vec<PathBuf>> {
+    let parts = transcript.split_by_chapter();
+    if parts.is_empty() {
+        log::warn!("No chapters found, writing a single transcript");
+        return output_transcript(transcript, output_name, media, config);
+    }
+
+    let width = parts.len().to_string().len().max(2);
+    let mut written = Vec::new();
+    for (i, (chapter, part)) in parts.iter().enumerate() {
+        let suffix = format!(
+            " - {:0width$} {}",
+            i + 1,
+            sanitize_filename(&chapter.title),
+            width = width
+        );
+        let name = append_to_name(output_name, &suffix);
+        written.extend(output_transcript(part, &name, media, config)?);
+    }
+
+    Ok(written)
+}
+
+/// Output paths relative to the output directory, for playlist and feed
+/// bookkeeping.
+fn relative_outputs(outputs: &[PathBuf], config: &Config) -> Vec<String> {
+    outputs
+        .iter()
+        .map(|p| {
+            p.strip_prefix(&config.output_dir)
+                .unwrap_or(p)
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect()
+}
+
+/// Get the output name for a transcript: `--name` for single inputs, or
+/// the output template.
+fn render_output_name(
+    metadata: &Metadata,
+    input: &str,
+    naming: &Naming,
+    config: &Config,
+) -> String {
+    if let (Some(name), None) = (&config.custom_name, &naming.playlist) {
+        return name.clone();
+    }
+    let fields = NameFields {
+        index: naming.index.clone(),
+        playlist: naming.playlist.clone(),
+        ..NameFields::new(metadata, input)
+    };
+    render_output_template(naming.template, &fields)
+}
+
+/// Create the directory an output file goes into, for templates with
+/// subdirectories.
+fn create_parent_dir(path: &Path) -> voxtus::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
     Ok(())
 }
 
@@ -214,7 +909,181 @@ fn get_output_name(title: &str, config: &Config) -> String {
     config
         .custom_name
         .clone()
-        .unwrap_or_else(|| title.to_string())
+        .unwrap_or_else(|| sanitize_filename(title))
+}
+
+/// Apply the collision policy to an output path that is about to be written.
+///
+/// Returns the path to write to, or `None` to skip this output.
+fn resolve_collision(path: &Path, config: &Config) -> voxtus::Result<Option<PathBuf>> {
+    if !path.exists() {
+        return Ok(Some(path.to_path_buf()));
+    }
+
+    match config.collision {
+        CollisionPolicy::Overwrite => Ok(Some(path.to_path_buf())),
+        CollisionPolicy::Skip => {
+            log::info!("Skipping existing file: {}", path.display());
+            Ok(None)
+        }
+        CollisionPolicy::Suffix => Ok(Some(suffixed_path(path))),
+        CollisionPolicy::Prompt => {
+            eprint!("File '{}' exists. Overwrite? [y/N] ", path.display());
+            let mut response = String::new();
+            if std::io::stdin().read_line(&mut response).is_err()
+                || !response.trim().eq_ignore_ascii_case("y")
+            {
+                return Err(voxtus::Error::UserAborted);
+            }
+            Ok(Some(path.to_path_buf()))
+        }
+    }
+}
+
+/// Show, update or switch the yt-dlp and ffmpeg binaries.
+fn run_deps(args: &Args, action: DepsAction) -> voxtus::Result<()> {
+    let config_path = args.config.as_deref().map(Path::new);
+    // `use-system` and `use-bundled` create the config file if needed
+    let file = match config_path {
+        Some(path) if !path.exists() => ConfigFile::default(),
+        _ => ConfigFile::load(config_path)?,
+    };
+    deps::set_source(file.binaries);
+
+    match action {
+        DepsAction::Status => {
+            println!("Binary source: {}", deps::source().as_str());
+            if deps::source() == BinarySource::Bundled {
+                println!("Libs directory: {}", deps::libs_dir()?.display());
+            }
+            for binary in deps::status() {
+                match &binary.path {
+                    Some(path) => println!(
+                        "   {:<8} {:<14} {}",
+                        binary.name,
+                        binary.version.as_deref().unwrap_or("unknown"),
+                        path.display()
+                    ),
+                    None => println!("   {:<8} not installed", binary.name),
+                }
+            }
+        }
+        DepsAction::Update => {
+            let version = deps::update_yt_dlp()?;
+            println!("yt-dlp is at version {}", version);
+        }
+        DepsAction::UseSystem | DepsAction::UseBundled => {
+            let source = if action == DepsAction::UseSystem {
+                BinarySource::System
+            } else {
+                BinarySource::Bundled
+            };
+            if source == BinarySource::System {
+                for name in ["yt-dlp", "ffmpeg"] {
+                    if deps::find_on_path(name).is_none() {
+                        eprintln!("Warning: {} not found on PATH", name);
+                    }
+                }
+            }
+            let path = ConfigFile::set(config_path, "binaries", serde_json::json!(source))?;
+            println!(
+                "Using {} binaries (saved to {})",
+                source.as_str(),
+                path.display()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// List or clear the download cache.
+fn run_cache(args: &Args, action: CacheAction) -> voxtus::Result<()> {
+    let file = ConfigFile::load(args.config.as_deref().map(Path::new))?;
+    let media = MediaCache::open_default(file.cache.media_max_size())?;
+    let transcripts = TranscriptCache::open_default()?;
+
+    match action {
+        CacheAction::Ls => {
+            let entries = media.entries()?;
+            print_cache_header("Media", media.dir(), entries.iter().map(|e| e.size));
+            for entry in entries {
+                print_cache_entry(&entry.key, entry.size, entry.last_used, &entry.title);
+            }
+
+            let entries = transcripts.entries()?;
+            println!();
+            print_cache_header(
+                "Transcript",
+                transcripts.dir(),
+                entries.iter().map(|e| e.size),
+            );
+            for entry in entries {
+                let label = format!("{} ({})", entry.title, entry.model);
+                print_cache_entry(&entry.key, entry.size, entry.last_used, &label);
+            }
+        }
+        CacheAction::Clear => {
+            let downloads = media.clear()?;
+            let transcripts = transcripts.clear()?;
+            println!(
+                "Removed {} cached downloads and {} cached transcripts",
+                downloads, transcripts
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn print_cache_header(name: &str, dir: &Path, sizes: impl ExactSizeIterator<Item = u64>) {
+    let count = sizes.len();
+    let total: u64 = sizes.sum();
+    println!(
+        "{} cache: {} ({} entries, {:.1} MiB)",
+        name,
+        dir.display(),
+        count,
+        total as f64 / (1024.0 * 1024.0)
+    );
+}
+
+fn print_cache_entry(key: &str, size: u64, last_used: std::time::SystemTime, label: &str) {
+    let last_used = chrono::DateTime::<chrono::Local>::from(last_used);
+    println!(
+        "   {:<33} {:>9.1} MiB  {}  {}",
+        key,
+        size as f64 / (1024.0 * 1024.0),
+        last_used.format("%Y-%m-%d %H:%M"),
+        label
+    );
+}
+
+fn print_available_formats() {
+    println!("Available Output Formats:\n");
+
+    for formatter in Formatters::default().iter() {
+        let mut name = formatter.name().to_string();
+        for alias in formatter.aliases() {
+            name.push_str(&format!(", {}", alias));
+        }
+        println!("   {:<18} - {}", name, formatter.description());
+        println!(
+            "                      .{}, {}",
+            formatter.extension(),
+            formatter.mime_type()
+        );
+    }
+    println!(
+        "   {:<18} - Custom layout from a template file",
+        "template:FILE"
+    );
+
+    println!();
+    println!("Examples:");
+    println!("   voxtus -f srt,vtt video.mp4              # Subtitles in two formats");
+    println!("   voxtus -f json --stdout video.mp4        # JSON to stdout");
+    println!("   voxtus -f template:cms.xml.tmpl video.mp4 # Custom layout as .xml");
 }
 
 fn print_available_models() {
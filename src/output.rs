@@ -0,0 +1,669 @@
+// Voxtus - Transcribe YouTube videos and local media files to text
+// Copyright (C) 2024 Johan Thorén <johan@thoren.xyz>
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Output file naming.
+//!
+//! Output names are rendered from a template such as `{uploader}/{title}`,
+//! for single inputs as well as playlist entries and feed episodes.
+//! Every placeholder value is sanitized into a single safe path component,
+//! so titles with `/`, `:` or very long names cannot escape the output
+//! directory or produce invalid file names. `/` in the template itself
+//! creates subdirectories.
+//!
+//! When an output file already exists, a [`CollisionPolicy`] decides
+//! whether to ask, overwrite, skip or pick a free `-1`, `-2`, ... name.
+//!
+//! Outputs are written atomically: to a hidden temporary file in the target
+//! directory that is renamed into place once complete, so an interrupted
+//! run never leaves a truncated output behind.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::config::is_url;
+use crate::download::http::url_file_stem;
+use crate::error::{Error, Result};
+use crate::formats::Metadata;
+
+/// Default output template: the media title.
+pub const DEFAULT_OUTPUT_TEMPLATE: &str = "{title}";
+
+/// Placeholders supported in output templates.
+pub const TEMPLATE_FIELDS: &[&str] = &[
+    "title", "id", "date", "uploader", "model", "lang", "stem", "index", "playlist", "show",
+    "guid", "ext",
+];
+
+/// Maximum length of a single file name component, in bytes.
+///
+/// Leaves room below the common 255 byte limit for an extension and
+/// chapter or collision suffixes.
+pub const MAX_COMPONENT_LEN: usize = 200;
+
+/// What to do when an output file already exists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CollisionPolicy {
+    /// Ask before overwriting.
+    #[default]
+    Prompt,
+    /// Overwrite without asking.
+    Overwrite,
+    /// Keep the existing file and don't write the output.
+    Skip,
+    /// Write to the first free name with a `-1`, `-2`, ... suffix.
+    Suffix,
+}
+
+impl std::str::FromStr for CollisionPolicy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "prompt" => Ok(Self::Prompt),
+            "overwrite" => Ok(Self::Overwrite),
+            "skip" => Ok(Self::Skip),
+            "suffix" => Ok(Self::Suffix),
+            _ => Err(Error::InvalidCollisionPolicy(s.to_string())),
+        }
+    }
+}
+
+/// Values available to output templates.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NameFields {
+    pub title: String,
+    pub id: Option<String>,
+    /// Upload or recording date as `YYYY-MM-DD`.
+    pub date: Option<String>,
+    pub uploader: Option<String>,
+    pub model: String,
+    pub lang: Option<String>,
+    /// Input file name without extension.
+    pub stem: String,
+    /// Position in the playlist, zero-padded to the playlist length.
+    pub index: Option<String>,
+    /// Playlist title, or the show title for feed episodes.
+    pub playlist: Option<String>,
+}
+
+impl NameFields {
+    /// Collect the fields from transcript metadata and the input path or URL.
+    pub fn new(metadata: &Metadata, input: &str) -> Self {
+        let stem = if is_url(input) {
+            url_file_stem(input)
+        } else {
+            Path::new(input)
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+        };
+
+        Self {
+            title: metadata.title.clone(),
+            id: metadata.id.clone(),
+            date: metadata.upload_date.clone(),
+            uploader: metadata
+                .uploader
+                .clone()
+                .or_else(|| metadata.channel.clone()),
+            model: metadata.model.clone(),
+            lang: metadata.language.clone(),
+            stem: stem.unwrap_or_else(|| metadata.title.clone()),
+            index: None,
+            playlist: None,
+        }
+    }
+
+    fn get(&self, field: &str) -> Option<&str> {
+        let value = match field {
+            "title" => &self.title,
+            // Feed episode IDs are their GUIDs
+            "id" | "guid" => self.id.as_deref().unwrap_or("unknown"),
+            "date" => self.date.as_deref().unwrap_or("undated"),
+            "uploader" => self.uploader.as_deref().unwrap_or("unknown"),
+            "model" => &self.model,
+            "lang" => self.lang.as_deref().unwrap_or("unknown"),
+            "stem" => &self.stem,
+            "index" => self.index.as_deref().unwrap_or("0"),
+            "playlist" | "show" => self.playlist.as_deref().unwrap_or("unknown"),
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+/// Check that an output template only uses known placeholders and stays
+/// inside the output directory.
+///
+/// # Examples
+///
+/// ```
+/// use voxtus::output::validate_output_template;
+///
+/// assert!(validate_output_template("{uploader}/{date} {title}").is_ok());
+/// assert!(validate_output_template("{name}").is_err());
+/// assert!(validate_output_template("../{title}").is_err());
+/// ```
+pub fn validate_output_template(template: &str) -> Result<()> {
+    let invalid = |reason: &str| Err(Error::InvalidTemplate(format!("{} ({})", template, reason)));
+
+    if template.trim().is_empty() {
+        return invalid("empty template");
+    }
+    if template.starts_with('/') || template.starts_with('\\') {
+        return invalid("must be relative to the output directory");
+    }
+    if template
+        .split(['/', '\\'])
+        .any(|c| c.trim() == ".." || c.trim().is_empty())
+    {
+        return invalid("empty or '..' path component");
+    }
+
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            return invalid("unclosed '{'");
+        };
+        let field = &rest[start + 1..start + len];
+        if !TEMPLATE_FIELDS.contains(&field) {
+            return invalid(&format!("unknown placeholder {{{}}}", field));
+        }
+        rest = &rest[start + len + 1..];
+    }
+
+    Ok(())
+}
+
+/// Render an output template into a relative output name.
+///
+/// Placeholder values are sanitized, and `/` in the template separates
+/// subdirectories. `{ext}` is kept as is, to be filled in per output format
+/// by [`output_file_name`].
+///
+/// # Examples
+///
+/// ```
+/// use voxtus::output::{NameFields, render_output_template};
+///
+/// let fields = NameFields {
+///     title: "Q&A: what/why?".to_string(),
+///     uploader: Some("Rust Team".to_string()),
+///     model: "small".to_string(),
+///     ..Default::default()
+/// };
+///
+/// assert_eq!(
+///     render_output_template("{uploader}/{title} [{model}]", &fields),
+///     "Rust Team/Q&A_ what_why_ [small]"
+/// );
+/// ```
+pub fn render_output_template(template: &str, fields: &NameFields) -> String {
+    template
+        .split(['/', '\\'])
+        .map(|component| {
+            let mut rendered = String::new();
+            let mut rest = component;
+            while let Some(start) = rest.find('{') {
+                rendered.push_str(&rest[..start]);
+                let end = rest[start..].find('}').map(|len| start + len);
+                let value = end.and_then(|end| fields.get(&rest[start + 1..end]));
+                match (end, value) {
+                    (Some(end), Some(value)) => {
+                        rendered.push_str(&sanitize_filename(value));
+                        rest = &rest[end + 1..];
+                    }
+                    // `{ext}` and anything unknown is kept literally
+                    _ => {
+                        rendered.push('{');
+                        rest = &rest[start + 1..];
+                    }
+                }
+            }
+            rendered.push_str(rest);
+            sanitize_component(&rendered)
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// File name for one output format of a rendered output name.
+///
+/// `{ext}` is replaced by the extension. Unless the file name itself (the
+/// last path component) contains `{ext}`, the extension is also appended.
+///
+/// # Examples
+///
+/// ```
+/// use voxtus::output::output_file_name;
+///
+/// assert_eq!(output_file_name("talk", "srt"), "talk.srt");
+/// assert_eq!(output_file_name("{ext}/talk.{ext}", "srt"), "srt/talk.srt");
+/// assert_eq!(output_file_name("{ext}/talk", "srt"), "srt/talk.srt");
+/// ```
+pub fn output_file_name(name: &str, ext: &str) -> String {
+    let file_name = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    let rendered = name.replace("{ext}", ext);
+    if file_name.contains("{ext}") {
+        rendered
+    } else {
+        format!("{}.{}", rendered, ext)
+    }
+}
+
+/// Append a suffix to an output name, before a trailing `.{ext}`.
+///
+/// # Examples
+///
+/// ```
+/// use voxtus::output::append_to_name;
+///
+/// assert_eq!(append_to_name("talk", " - 01 Intro"), "talk - 01 Intro");
+/// assert_eq!(append_to_name("talk.{ext}", " - 01 Intro"), "talk - 01 Intro.{ext}");
+/// ```
+pub fn append_to_name(name: &str, suffix: &str) -> String {
+    match name.strip_suffix(".{ext}") {
+        Some(base) => format!("{}{}.{{ext}}", base, suffix),
+        None => format!("{}{}", name, suffix),
+    }
+}
+
+/// The first output name with a `-N` suffix whose files don't exist yet.
+///
+/// Returns `name` unchanged when none of its files exist. Checking every
+/// extension keeps the outputs of one input under the same name.
+pub fn unique_output_name(dir: &Path, name: &str, extensions: &[&str]) -> String {
+    let taken = |name: &str| {
+        extensions
+            .iter()
+            .any(|ext| dir.join(output_file_name(name, ext)).exists())
+    };
+    if !taken(name) {
+        return name.to_string();
+    }
+    (1..)
+        .map(|n| append_to_name(name, &format!("-{}", n)))
+        .find(|candidate| !taken(candidate))
+        .unwrap_or_else(|| name.to_string())
+}
+
+/// The first path with a `-N` suffix before the extension that doesn't
+/// exist yet, or `path` itself when it is free.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::Path;
+/// use voxtus::output::suffixed_path;
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("talk.txt");
+/// assert_eq!(suffixed_path(&path), path);
+///
+/// std::fs::write(&path, "").unwrap();
+/// assert_eq!(suffixed_path(&path), dir.path().join("talk-1.txt"));
+/// ```
+pub fn suffixed_path(path: &Path) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
+    }
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let ext = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    (1..)
+        .map(|n| path.with_file_name(format!("{}-{}{}", stem, n, ext)))
+        .find(|candidate| !candidate.exists())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Write a file atomically.
+///
+/// The content goes to a temporary file next to `path`, which is flushed to
+/// disk and then renamed over `path`. Readers see either the old file or the
+/// complete new one.
+///
+/// # Examples
+///
+/// ```
+/// use voxtus::output::write_atomic;
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("talk.txt");
+/// write_atomic(&path, b"Hello").unwrap();
+/// assert_eq!(std::fs::read_to_string(&path).unwrap(), "Hello");
+/// ```
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let mut file = temp_file_for(path)?;
+    file.write_all(contents.as_ref())?;
+    persist(file, path)
+}
+
+/// Copy a file atomically, like [`write_atomic`].
+pub fn copy_atomic(from: &Path, to: &Path) -> Result<()> {
+    let mut file = temp_file_for(to)?;
+    std::io::copy(&mut std::fs::File::open(from)?, &mut file)?;
+    persist(file, to)
+}
+
+/// A hidden temporary file in the directory of `path`, so the final rename
+/// stays on one filesystem. It is removed if dropped before persisting.
+///
+/// The file gets the mode of an existing `path`, or like [`std::fs::write`]
+/// 0666 minus the umask, so outputs stay readable by other users.
+fn temp_file_for(path: &Path) -> Result<tempfile::NamedTempFile> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(".voxtus-").suffix(".tmp");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        builder.permissions(std::fs::Permissions::from_mode(0o666));
+    }
+    let file = builder.tempfile_in(dir)?;
+
+    if let Ok(metadata) = std::fs::metadata(path) {
+        file.as_file().set_permissions(metadata.permissions())?;
+    }
+    Ok(file)
+}
+
+fn persist(file: tempfile::NamedTempFile, path: &Path) -> Result<()> {
+    file.as_file().sync_all()?;
+    file.persist(path).map_err(|e| Error::Io(e.error))?;
+    Ok(())
+}
+
+/// Make a string safe to use as a single file name.
+///
+/// Path separators and characters that are invalid on Windows become `_`,
+/// control characters and whitespace runs collapse to one space, leading
+/// and trailing dots and spaces are removed and the result is truncated to
+/// [`MAX_COMPONENT_LEN`] bytes. Empty names become `untitled`.
+///
+/// # Examples
+///
+/// ```
+/// use voxtus::output::sanitize_filename;
+///
+/// assert_eq!(sanitize_filename("AC/DC: Live"), "AC_DC_ Live");
+/// assert_eq!(sanitize_filename("  ..hidden  "), "hidden");
+/// assert_eq!(sanitize_filename("🎉 Party"), "🎉 Party");
+/// assert_eq!(sanitize_filename(""), "untitled");
+/// ```
+pub fn sanitize_filename(name: &str) -> String {
+    let replaced: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect();
+    sanitize_component(&replaced)
+}
+
+/// Normalize an already rendered path component without replacing
+/// characters, so template literals such as `{ext}` survive.
+fn sanitize_component(name: &str) -> String {
+    let collapsed = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    let trimmed = collapsed.trim_matches(['.', ' ']);
+
+    let mut end = trimmed.len().min(MAX_COMPONENT_LEN);
+    while !trimmed.is_char_boundary(end) {
+        end -= 1;
+    }
+    let truncated = trimmed[..end].trim_end_matches(['.', ' ']);
+
+    if truncated.is_empty() {
+        return "untitled".to_string();
+    }
+
+    // Device names are reserved on Windows regardless of extension
+    let base = truncated.split('.').next().unwrap_or_default();
+    let reserved = matches!(
+        base.to_ascii_uppercase().as_str(),
+        "CON" | "PRN" | "AUX" | "NUL"
+    ) || (base.len() == 4
+        && base
+            .get(..3)
+            .is_some_and(|p| p.eq_ignore_ascii_case("COM") || p.eq_ignore_ascii_case("LPT"))
+        && base.as_bytes()[3].is_ascii_digit());
+    if reserved {
+        format!("_{}", truncated)
+    } else {
+        truncated.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields() -> NameFields {
+        NameFields {
+            title: "Talk".to_string(),
+            id: Some("abc123".to_string()),
+            date: Some("2024-05-01".to_string()),
+            uploader: Some("Chan".to_string()),
+            model: "base".to_string(),
+            lang: Some("en".to_string()),
+            stem: "watch".to_string(),
+            index: Some("03".to_string()),
+            playlist: Some("Course".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_render_all_fields() {
+        assert_eq!(
+            render_output_template(
+                "{uploader}/{date} {title} [{id}] {model}.{lang} {stem}",
+                &fields()
+            ),
+            "Chan/2024-05-01 Talk [abc123] base.en watch"
+        );
+    }
+
+    #[test]
+    fn test_render_playlist_fields() {
+        assert_eq!(
+            render_output_template("{playlist}/{index} - {title}", &fields()),
+            "Course/03 - Talk"
+        );
+        assert_eq!(
+            render_output_template("{show}/{date} [{guid}]", &fields()),
+            "Course/2024-05-01 [abc123]"
+        );
+
+        let fields = NameFields {
+            playlist: Some("Talks 2024/25".to_string()),
+            ..fields()
+        };
+        assert_eq!(
+            render_output_template("{playlist}/{title}", &fields),
+            "Talks 2024_25/Talk"
+        );
+    }
+
+    #[test]
+    fn test_render_missing_fields() {
+        let fields = NameFields {
+            title: "Talk".to_string(),
+            model: "base".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            render_output_template("{date} {title} {id} {lang} {index} {playlist}", &fields),
+            "undated Talk unknown unknown 0 unknown"
+        );
+    }
+
+    #[test]
+    fn test_render_values_cannot_create_directories() {
+        let fields = NameFields {
+            title: "../../etc/passwd".to_string(),
+            ..fields()
+        };
+        assert_eq!(render_output_template("{title}", &fields), "_.._etc_passwd");
+    }
+
+    #[test]
+    fn test_render_keeps_ext_placeholder() {
+        assert_eq!(
+            render_output_template("{ext}/{title}.{ext}", &fields()),
+            "{ext}/Talk.{ext}"
+        );
+    }
+
+    #[test]
+    fn test_validate_output_template() {
+        assert!(validate_output_template(DEFAULT_OUTPUT_TEMPLATE).is_ok());
+        assert!(validate_output_template("{ext}/{title}.{ext}").is_ok());
+        assert!(validate_output_template("").is_err());
+        assert!(validate_output_template("/abs/{title}").is_err());
+        assert!(validate_output_template("a//{title}").is_err());
+        assert!(validate_output_template("{title").is_err());
+        assert!(matches!(
+            validate_output_template("{foo}"),
+            Err(Error::InvalidTemplate(_))
+        ));
+    }
+
+    #[test]
+    fn test_collision_policy_from_str() {
+        assert_eq!(
+            "suffix".parse::<CollisionPolicy>().unwrap(),
+            CollisionPolicy::Suffix
+        );
+        assert_eq!(
+            "Skip".parse::<CollisionPolicy>().unwrap(),
+            CollisionPolicy::Skip
+        );
+        assert!(matches!(
+            "rename".parse::<CollisionPolicy>(),
+            Err(Error::InvalidCollisionPolicy(_))
+        ));
+    }
+
+    #[test]
+    fn test_unique_output_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let exts = ["txt", "srt"];
+        assert_eq!(unique_output_name(dir.path(), "talk", &exts), "talk");
+
+        // Any existing format takes the name for all formats
+        std::fs::write(dir.path().join("talk.srt"), "").unwrap();
+        assert_eq!(unique_output_name(dir.path(), "talk", &exts), "talk-1");
+
+        std::fs::write(dir.path().join("talk-1.txt"), "").unwrap();
+        assert_eq!(unique_output_name(dir.path(), "talk", &exts), "talk-2");
+        assert_eq!(
+            unique_output_name(dir.path(), "talk.{ext}", &exts),
+            "talk-2.{ext}"
+        );
+    }
+
+    #[test]
+    fn test_suffixed_path_counts_up() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "").unwrap();
+        std::fs::write(dir.path().join("a-1.txt"), "").unwrap();
+        assert_eq!(
+            suffixed_path(&dir.path().join("a.txt")),
+            dir.path().join("a-2.txt")
+        );
+    }
+
+    #[test]
+    fn test_write_atomic_replaces_and_leaves_no_temp_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("talk.srt");
+        std::fs::write(&path, "old").unwrap();
+
+        write_atomic(&path, "new").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+
+        let names: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(names, vec![std::ffi::OsString::from("talk.srt")]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomic_file_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+        // A new file gets the same mode as with std::fs::write
+        let reference = dir.path().join("reference.txt");
+        std::fs::write(&reference, "").unwrap();
+        let path = dir.path().join("talk.txt");
+        write_atomic(&path, "new").unwrap();
+        assert_eq!(mode(&path), mode(&reference));
+
+        // An overwritten file keeps its mode
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o640)).unwrap();
+        write_atomic(&path, "newer").unwrap();
+        assert_eq!(mode(&path), 0o640);
+        copy_atomic(&reference, &path).unwrap();
+        assert_eq!(mode(&path), 0o640);
+    }
+
+    #[test]
+    fn test_copy_atomic() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = dir.path().join("audio.mp3");
+        let to = dir.path().join("talk.mp3");
+        std::fs::write(&from, [1u8, 2, 3]).unwrap();
+
+        copy_atomic(&from, &to).unwrap();
+        assert_eq!(std::fs::read(&to).unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_sanitize_truncates_on_char_boundary() {
+        let long = "é".repeat(150);
+        let sanitized = sanitize_filename(&long);
+        assert!(sanitized.len() <= MAX_COMPONENT_LEN);
+        assert!(sanitized.chars().all(|c| c == 'é'));
+    }
+
+    #[test]
+    fn test_sanitize_control_and_whitespace() {
+        assert_eq!(sanitize_filename("a\tb\n\nc"), "a b c");
+        assert_eq!(sanitize_filename("..."), "untitled");
+    }
+
+    #[test]
+    fn test_sanitize_reserved_names() {
+        assert_eq!(sanitize_filename("CON"), "_CON");
+        assert_eq!(sanitize_filename("com1.txt"), "_com1.txt");
+        assert_eq!(sanitize_filename("Console"), "Console");
+        assert_eq!(sanitize_filename("a€"), "a€");
+    }
+
+    #[test]
+    fn test_name_fields_from_metadata() {
+        let mut metadata = Metadata::new("Talk", "/tmp/talk.mp4", None, "base", None);
+        metadata.channel = Some("Chan".to_string());
+
+        let fields = NameFields::new(&metadata, "/tmp/talk.mp4");
+        assert_eq!(fields.stem, "talk");
+        assert_eq!(fields.uploader.as_deref(), Some("Chan"));
+
+        let fields = NameFields::new(&metadata, "https://cdn.example.com/ep%201.mp3");
+        assert_eq!(fields.stem, "ep 1");
+    }
+}
@@ -0,0 +1,316 @@
+// Voxtus - Transcribe YouTube videos and local media files to text
+// Copyright (C) 2024 Johan Thorén <johan@thoren.xyz>
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Playlist transcription bookkeeping.
+//!
+//! Each playlist entry is transcribed into its own output, named by a
+//! template. A playlist-level index maps video IDs to the files written,
+//! and is used on later runs to skip entries that are already done.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::download::http::url_file_stem;
+use crate::download::{PlaylistEntry, PlaylistInfo};
+use crate::error::{Error, Result};
+use crate::formats::csv::csv_field;
+use crate::output::NameFields;
+
+/// Default template for naming playlist entry outputs.
+pub const DEFAULT_ENTRY_TEMPLATE: &str = "{index} - {title}";
+
+/// Supported playlist index formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexFormat {
+    Json,
+    Csv,
+}
+
+impl std::str::FromStr for IndexFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            _ => Err(Error::InvalidFormat(s.to_string())),
+        }
+    }
+}
+
+impl IndexFormat {
+    /// Get the file extension for this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+        }
+    }
+}
+
+/// A transcribed playlist entry and the files written for it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub index: usize,
+    pub id: String,
+    pub title: String,
+    pub url: String,
+    /// Output file names, relative to the output directory.
+    pub outputs: Vec<String>,
+}
+
+/// Playlist-level index mapping video IDs to output files.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlaylistIndex {
+    pub id: String,
+    pub title: String,
+    pub source: String,
+    pub entries: Vec<IndexEntry>,
+}
+
+impl PlaylistIndex {
+    pub fn new(playlist: &PlaylistInfo, source: impl Into<String>) -> Self {
+        Self {
+            id: playlist.id.clone(),
+            title: playlist.title.clone(),
+            source: source.into(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Load a JSON index written by a previous run, if one exists.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+
+    /// Look up an entry by video ID.
+    pub fn get(&self, id: &str) -> Option<&IndexEntry> {
+        self.entries.iter().find(|e| e.id == id)
+    }
+
+    /// Insert an entry, replacing any previous entry for the same video ID.
+    pub fn upsert(&mut self, entry: IndexEntry) {
+        self.entries.retain(|e| e.id != entry.id);
+        self.entries.push(entry);
+        self.entries.sort_by_key(|e| e.index);
+    }
+
+    /// Check whether every output recorded for a video still exists.
+    pub fn is_complete(&self, id: &str, output_dir: &Path) -> bool {
+        self.get(id).is_some_and(|e| {
+            !e.outputs.is_empty() && e.outputs.iter().all(|o| output_dir.join(o).exists())
+        })
+    }
+
+    /// Format the index as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Format the index as CSV, one row per entry.
+    ///
+    /// Multiple outputs for the same entry are separated by `;`.
+    pub fn to_csv(&self) -> String {
+        let mut lines = vec!["index,id,title,url,outputs".to_string()];
+        for entry in &self.entries {
+            lines.push(
+                [
+                    entry.index.to_string(),
+                    csv_field(&entry.id),
+                    csv_field(&entry.title),
+                    csv_field(&entry.url),
+                    csv_field(&entry.outputs.join(";")),
+                ]
+                .join(","),
+            );
+        }
+        lines.join("\n")
+    }
+
+    /// Format the index in the given format.
+    pub fn format(&self, format: IndexFormat) -> String {
+        match format {
+            IndexFormat::Json => self.to_json(),
+            IndexFormat::Csv => self.to_csv(),
+        }
+    }
+}
+
+/// Output template fields known for a playlist entry before it is
+/// downloaded.
+///
+/// `{index}` is zero-padded to the playlist length. Fields that only the
+/// download reports, such as the upload date, are left unknown.
+///
+/// # Examples
+///
+/// ```
+/// use voxtus::download::{PlaylistEntry, PlaylistInfo};
+/// use voxtus::output::render_output_template;
+/// use voxtus::playlist::entry_name_fields;
+///
+/// let entry = PlaylistEntry {
+///     index: 3,
+///     id: "abc".to_string(),
+///     title: "Intro".to_string(),
+///     url: "https://www.youtube.com/watch?v=abc".to_string(),
+/// };
+/// let playlist = PlaylistInfo {
+///     id: "PL1".to_string(),
+///     title: "Course".to_string(),
+///     entries: vec![entry.clone(); 12],
+/// };
+///
+/// let fields = entry_name_fields(&entry, &playlist);
+/// assert_eq!(render_output_template("{index} - {title}", &fields), "03 - Intro");
+/// assert_eq!(render_output_template("{playlist}/{id}", &fields), "Course/abc");
+/// ```
+pub fn entry_name_fields(entry: &PlaylistEntry, playlist: &PlaylistInfo) -> NameFields {
+    let width = playlist.entries.len().to_string().len();
+    NameFields {
+        title: entry.title.clone(),
+        id: Some(entry.id.clone()),
+        stem: url_file_stem(&entry.url).unwrap_or_else(|| entry.title.clone()),
+        index: Some(format!("{:0width$}", entry.index, width = width)),
+        playlist: Some(playlist.title.clone()),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{render_output_template, validate_output_template};
+
+    fn sample_playlist() -> PlaylistInfo {
+        PlaylistInfo {
+            id: "PLabc".to_string(),
+            title: "Lectures".to_string(),
+            entries: vec![
+                PlaylistEntry {
+                    index: 1,
+                    id: "vid1".to_string(),
+                    title: "Intro, part 1".to_string(),
+                    url: "https://www.youtube.com/watch?v=vid1".to_string(),
+                },
+                PlaylistEntry {
+                    index: 2,
+                    id: "vid2".to_string(),
+                    title: "Wrap-up".to_string(),
+                    url: "https://www.youtube.com/watch?v=vid2".to_string(),
+                },
+            ],
+        }
+    }
+
+    fn index_entry(index: usize, id: &str, outputs: &[&str]) -> IndexEntry {
+        IndexEntry {
+            index,
+            id: id.to_string(),
+            title: format!("Title {}", index),
+            url: format!("https://www.youtube.com/watch?v={}", id),
+            outputs: outputs.iter().map(|o| o.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_index_format_from_str() {
+        assert_eq!("json".parse::<IndexFormat>().unwrap(), IndexFormat::Json);
+        assert_eq!("CSV".parse::<IndexFormat>().unwrap(), IndexFormat::Csv);
+        assert!(matches!(
+            "xml".parse::<IndexFormat>(),
+            Err(Error::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_entry_name_fields() {
+        let playlist = sample_playlist();
+        let fields = entry_name_fields(&playlist.entries[0], &playlist);
+        assert_eq!(
+            render_output_template(DEFAULT_ENTRY_TEMPLATE, &fields),
+            "1 - Intro, part 1"
+        );
+        assert_eq!(render_output_template("{id}", &fields), "vid1");
+        assert!(validate_output_template(DEFAULT_ENTRY_TEMPLATE).is_ok());
+    }
+
+    #[test]
+    fn test_entry_name_fields_are_sanitized() {
+        let mut playlist = sample_playlist();
+        playlist.title = "Talks 2024/25".to_string();
+        playlist.entries[0].title = "../What: is it?".to_string();
+        let fields = entry_name_fields(&playlist.entries[0], &playlist);
+
+        assert_eq!(
+            render_output_template("{playlist}/{title}", &fields),
+            "Talks 2024_25/_What_ is it_"
+        );
+    }
+
+    #[test]
+    fn test_upsert_replaces_and_sorts() {
+        let mut index = PlaylistIndex::new(&sample_playlist(), "https://example.com");
+        index.upsert(index_entry(2, "vid2", &["b.txt"]));
+        index.upsert(index_entry(1, "vid1", &["a.txt"]));
+        index.upsert(index_entry(2, "vid2", &["b2.txt"]));
+
+        assert_eq!(index.entries.len(), 2);
+        assert_eq!(index.entries[0].id, "vid1");
+        assert_eq!(index.get("vid2").unwrap().outputs, vec!["b2.txt"]);
+    }
+
+    #[test]
+    fn test_is_complete() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "done").unwrap();
+
+        let mut index = PlaylistIndex::new(&sample_playlist(), "src");
+        index.upsert(index_entry(1, "vid1", &["a.txt"]));
+        index.upsert(index_entry(2, "vid2", &["a.txt", "missing.srt"]));
+
+        assert!(index.is_complete("vid1", dir.path()));
+        assert!(!index.is_complete("vid2", dir.path()));
+        assert!(!index.is_complete("unknown", dir.path()));
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.json");
+
+        let mut index = PlaylistIndex::new(&sample_playlist(), "src");
+        index.upsert(index_entry(1, "vid1", &["a.txt", "a.srt"]));
+        std::fs::write(&path, index.to_json()).unwrap();
+
+        let loaded = PlaylistIndex::load(&path).unwrap().unwrap();
+        assert_eq!(loaded, index);
+        assert!(
+            PlaylistIndex::load(&dir.path().join("none.json"))
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let mut index = PlaylistIndex::new(&sample_playlist(), "src");
+        let mut entry = index_entry(1, "vid1", &["a.txt", "a.srt"]);
+        entry.title = "Intro, part 1".to_string();
+        index.upsert(entry);
+
+        let csv = index.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "index,id,title,url,outputs");
+        assert_eq!(
+            lines[1],
+            "1,vid1,\"Intro, part 1\",https://www.youtube.com/watch?v=vid1,a.txt;a.srt"
+        );
+    }
+}
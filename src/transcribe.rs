@@ -16,7 +16,7 @@ use std::fs;
 use std::io::Write;
 
 use crate::error::{Error, Result};
-use crate::formats::{Metadata, Segment, Transcript};
+use crate::formats::{Metadata, Segment, Transcript, Word};
 
 #[cfg(feature = "whisper")]
 unsafe extern "C" fn log_callback(level: u32, message: *const c_char, _user_data: *mut c_void) {
@@ -104,6 +104,49 @@ async fn ensure_model(model: &str) -> Result<PathBuf> {
     Ok(model_path)
 }
 
+/// Whisper decoding options used by [`transcribe`].
+///
+/// Part of the transcript cache key, so changing how Whisper decodes
+/// invalidates earlier results.
+pub const DECODING_OPTIONS: &str = "greedy,best_of=1,words,confidence";
+
+/// Join Whisper tokens, given as `(start, end, bytes)`, into words.
+///
+/// A token starting with whitespace starts a new word; other tokens, such as
+/// word pieces and punctuation, extend the current one. Tokens can split a
+/// multi-byte UTF-8 character, so each word is decoded only once complete.
+///
+/// # Examples
+///
+/// ```
+/// use voxtus::transcribe::words_from_tokens;
+///
+/// let words = words_from_tokens(&[(0.0, 0.3, " Hel"), (0.3, 0.5, "lo"), (0.5, 0.6, ","), (0.7, 1.0, " world")]);
+/// assert_eq!(words.len(), 2);
+/// assert_eq!(words[0].text, "Hello,");
+/// assert_eq!((words[0].start, words[0].end), (0.0, 0.6));
+/// ```
+pub fn words_from_tokens<T: AsRef<[u8]>>(tokens: &[(f64, f64, T)]) -> Vec<Word> {
+    let mut words: Vec<(f64, f64, Vec<u8>)> = Vec::new();
+    for (start, end, bytes) in tokens {
+        let bytes = bytes.as_ref();
+        if bytes.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        match words.last_mut() {
+            Some(word) if !bytes[0].is_ascii_whitespace() => {
+                word.2.extend_from_slice(bytes);
+                word.1 = *end;
+            }
+            _ => words.push((*start, *end, bytes.trim_ascii_start().to_vec())),
+        }
+    }
+    words
+        .into_iter()
+        .map(|(start, end, bytes)| Word::new(start, end, String::from_utf8_lossy(&bytes)))
+        .collect()
+}
+
 /// Transcribe audio file using Whisper.
 ///
 /// Downloads the model if not already cached and returns a transcript
@@ -174,7 +217,7 @@ fn run_whisper(
 
     // Convert audio directly to raw f32le PCM for Whisper (16kHz mono)
     let pcm_path = temp_dir.join("whisper_input.pcm");
-    let output = std::process::Command::new("ffmpeg")
+    let output = std::process::Command::new(crate::deps::ffmpeg_path())
         .args([
             "-i",
             &audio_path.to_string_lossy(),
@@ -215,6 +258,7 @@ fn run_whisper(
     params.set_print_progress(false);
     params.set_print_realtime(false);
     params.set_print_timestamps(false);
+    params.set_token_timestamps(true);
 
     state
         .full(params, &audio_data[..])
@@ -237,7 +281,35 @@ fn run_whisper(
         let start_sec = segment.start_timestamp() as f64 / 100.0;
         let end_sec = segment.end_timestamp() as f64 / 100.0;
 
-        segments.push(Segment::new(start_sec, end_sec, text));
+        // Special tokens (timestamps, end of text) sort after the text tokens
+        let mut tokens = Vec::new();
+        let mut probabilities = Vec::new();
+        for t in 0..segment.n_tokens() {
+            let Some(token) = segment.get_token(t) else {
+                continue;
+            };
+            if token.token_id() >= ctx.token_eot() {
+                continue;
+            }
+            let data = token.token_data();
+            probabilities.push(token.token_probability() as f64);
+            if let Ok(bytes) = token.to_bytes() {
+                tokens.push((
+                    data.t0 as f64 / 100.0,
+                    data.t1 as f64 / 100.0,
+                    bytes.to_vec(),
+                ));
+            }
+        }
+
+        let confidence = (!probabilities.is_empty())
+            .then(|| probabilities.iter().sum::<f64>() / probabilities.len() as f64);
+
+        segments.push(Segment {
+            words: words_from_tokens(&tokens),
+            confidence,
+            ..Segment::new(start_sec, end_sec, text)
+        });
     }
 
     // Get detected language from whisper
@@ -272,6 +344,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_words_from_tokens() {
+        let words = words_from_tokens(&[
+            (0.0, 0.2, " I"),
+            (0.2, 0.5, "'m"),
+            (0.5, 0.5, " "),
+            (0.6, 0.9, " here"),
+            (0.9, 1.0, "."),
+        ]);
+        assert_eq!(
+            words,
+            vec![Word::new(0.0, 0.5, "I'm"), Word::new(0.6, 1.0, "here.")]
+        );
+    }
+
+    #[test]
+    fn test_words_from_tokens_split_characters() {
+        // "på" and "日本" with their characters split across tokens
+        let words = words_from_tokens(&[
+            (0.0, 0.2, &b" p"[..]),
+            (0.2, 0.3, &[0xc3][..]),
+            (0.3, 0.4, &[0xa5][..]),
+            (0.5, 0.6, &[b' ', 0xe6, 0x97][..]),
+            (0.6, 0.7, &[0xa5, 0xe6, 0x9c, 0xac][..]),
+        ]);
+        assert_eq!(
+            words,
+            vec![Word::new(0.0, 0.4, "på"), Word::new(0.5, 0.7, "日本")]
+        );
+    }
+
+    #[test]
+    fn test_words_from_tokens_leading_piece() {
+        let words = words_from_tokens(&[(0.0, 0.2, "Hi"), (0.2, 0.4, " there")]);
+        assert_eq!(words[0].text, "Hi");
+        assert_eq!(words[1].text, "there");
+    }
+
     #[test]
     #[cfg(feature = "whisper")]
     fn test_get_models_dir() {
@@ -105,3 +105,82 @@ fn test_transcribe_verbose() {
         stderr
     );
 }
+
+#[test]
+fn test_transcribe_feed_skips_processed_episodes() {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let output_dir = temp_dir.path();
+
+    // Serve the sample episode once; the second run must not request it
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0u8; 1024];
+        let _ = stream.read(&mut request).unwrap();
+        let body = fs::read("tests/data/sample.mp3").unwrap();
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        )
+        .unwrap();
+        stream.write_all(&body).unwrap();
+    });
+
+    let feed_path = output_dir.join("show.rss");
+    fs::write(
+        &feed_path,
+        format!(
+            r#"<?xml version="1.0"?>
+<rss version="2.0">
+  <channel>
+    <title>Sample Show</title>
+    <item>
+      <title>Pilot</title>
+      <guid>pilot</guid>
+      <pubDate>Tue, 30 Jan 2024 08:00:00 GMT</pubDate>
+      <enclosure url="http://{}/pilot.mp3" type="audio/mpeg"/>
+    </item>
+  </channel>
+</rss>"#,
+            addr
+        ),
+    )
+    .unwrap();
+
+    let run = || {
+        Command::new(cargo_bin("voxtus"))
+            .arg(feed_path.to_str().unwrap())
+            .arg("--model")
+            .arg("tiny")
+            .arg("--output")
+            .arg(output_dir.to_str().unwrap())
+            .arg("--format")
+            .arg("json")
+            .output()
+            .unwrap()
+    };
+
+    let output = run();
+    assert!(
+        output.status.success(),
+        "Command failed with stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    server.join().unwrap();
+
+    let json = fs::read_to_string(output_dir.join("2024-01-30 - Pilot.json")).unwrap();
+    assert!(json.contains(r#""title": "Pilot""#));
+    assert!(json.contains(r#""channel": "Sample Show""#));
+    assert!(json.contains(r#""upload_date": "2024-01-30""#));
+
+    let state = fs::read_to_string(output_dir.join("Sample Show.feed.json")).unwrap();
+    assert!(state.contains(r#""guid": "pilot""#));
+
+    // Nothing is listening any more, so this only succeeds if the episode is skipped
+    assert!(run().status.success());
+}